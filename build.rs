@@ -0,0 +1,22 @@
+use std::process::Command;
+
+// Bakes in what `git plz version --verbose` reports about the build itself.
+fn main() {
+    let commit = output("git", &["rev-parse", "--short", "HEAD"]);
+    let rustc = output(&std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc")), &["--version"]);
+
+    println!("cargo:rustc-env=GIT_PLZ_COMMIT={}", commit.unwrap_or_else(|| String::from("unknown")));
+    println!("cargo:rustc-env=GIT_PLZ_RUSTC={}", rustc.unwrap_or_else(|| String::from("unknown")));
+    println!("cargo:rustc-env=GIT_PLZ_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
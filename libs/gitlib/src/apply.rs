@@ -0,0 +1,8 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyResult {
+    Applied,
+    // Would apply cleanly, but only a check was asked for.
+    Applies,
+    // Nothing was changed; git's reason, e.g. "patch does not apply".
+    Rejected(String),
+}
@@ -0,0 +1,30 @@
+use std::path::{Path, PathBuf};
+
+pub struct GitBlob {
+    id: String,
+    path: Option<PathBuf>,
+    size: u64,
+}
+
+impl GitBlob {
+    pub fn new(id: String, path: Option<PathBuf>, size: u64) -> Self {
+        Self {
+            id: id,
+            path: path,
+            size: size,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // The first path the blob was found at, if any reachable commit has it.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
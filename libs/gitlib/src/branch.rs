@@ -0,0 +1,19 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BranchDeletion {
+    Deleted,
+    NotFound,
+    // Checked out, so never deleted.
+    Current,
+    // Has commits neither its upstream nor HEAD has; only deleted when forced.
+    Unmerged,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BranchCreation {
+    Created,
+    CheckedOut,
+    // A local branch by that name is already there; it's left as it is.
+    Exists,
+    // The ref to start from isn't in this repo.
+    NoStartPoint,
+}
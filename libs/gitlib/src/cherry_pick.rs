@@ -0,0 +1,10 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CherryPick {
+    Picked,
+    // HEAD already has the commit itself.
+    AlreadyApplied,
+    // The repo doesn't have the commit to pick.
+    NotFound,
+    // Applying it conflicted and was aborted, leaving the branch as it was.
+    Conflicted,
+}
@@ -0,0 +1,47 @@
+use git2;
+
+use std::env;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+// For what libgit2 can't do, fall back to the git binary on PATH. Errors
+// carry git's own message.
+pub fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = run(dir, args)?;
+
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+        false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+// Runs git without a terminal to prompt on, so anything needing a password
+// fails instead of hanging a worker thread.
+pub fn run(dir: &Path, args: &[&str]) -> Result<Output, String> {
+    let mut command = Command::new("git");
+    command
+        .current_dir(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .env("GIT_TERMINAL_PROMPT", "0");
+
+    // The env var beats GIT_SSH and core.sshCommand, so only set it when
+    // neither would pick the ssh to run.
+    if env::var_os("GIT_SSH_COMMAND").is_none() && env::var_os("GIT_SSH").is_none() && !has_ssh_command(dir) {
+        command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+
+    command
+        .output()
+        .map_err(|e| format!("Could not run git: {}", e))
+}
+
+// Looks at the repo's own config too when `dir` is in one, since that's
+// where per-repo identities live.
+fn has_ssh_command(dir: &Path) -> bool {
+    git2::Repository::discover(dir)
+        .and_then(|r| r.config())
+        .or_else(|_| git2::Config::open_default())
+        .and_then(|c| c.get_string("core.sshCommand"))
+        .is_ok()
+}
@@ -0,0 +1,77 @@
+use git2;
+
+pub struct GitCommit {
+    id: String,
+    author: String,
+    email: String,
+    summary: String,
+    message: String,
+    time: i64,
+    offset_minutes: i32,
+    parents: usize,
+}
+
+impl GitCommit {
+    pub fn new(mut commit: git2::Commit) -> Self {
+        let summary = match commit.summary() {
+            Some(s) => String::from(s),
+            None => String::new()
+        };
+
+        let message = match commit.message() {
+            Some(m) => String::from(m),
+            None => String::new()
+        };
+
+        let author = commit.author();
+        let time = commit.time();
+
+        Self {
+            id: commit.id().to_string(),
+            author: author.name().map(String::from).unwrap_or_default(),
+            email: author.email().map(String::from).unwrap_or_default(),
+            summary: summary,
+            message: message,
+            time: time.seconds(),
+            offset_minutes: time.offset_minutes(),
+            parents: commit.parent_ids().count(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn short_id(&self) -> &str {
+        &self.id[..7.min(self.id.len())]
+    }
+
+    // Seconds since the Unix epoch.
+    pub fn time(&self) -> i64 {
+        self.time
+    }
+
+    pub fn offset_minutes(&self) -> i32 {
+        self.offset_minutes
+    }
+
+    pub fn is_merge(&self) -> bool {
+        self.parents > 1
+    }
+}
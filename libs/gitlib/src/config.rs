@@ -0,0 +1,68 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use git2;
+
+use super::GitError;
+
+// Paths at or beyond this length fail on Windows unless core.longpaths is set.
+const MAX_PATH: usize = 260;
+
+// A value from the user's global and system config, outside of any repo.
+pub fn global_config(key: &str) -> Option<String> {
+    git2::Config::open_default().and_then(|c| c.get_string(key)).ok()
+}
+
+#[derive(Debug, Clone)]
+pub struct GitCoreConfig {
+    long_paths: bool,
+}
+
+impl GitCoreConfig {
+    pub fn new(config: &git2::Config) -> Self {
+        Self { long_paths: config.get_bool("core.longpaths").unwrap_or(false) }
+    }
+
+    // libgit2 reads core.autocrlf and core.symlinks itself while filtering and
+    // writing files, so the builder only has to refuse paths git itself would
+    // refuse. The returned LongPath says which one, if the checkout failed.
+    pub(crate) fn checkout_builder<'cb>(&self, workdir_len: usize) -> (git2::build::CheckoutBuilder<'cb>, LongPath) {
+        let mut builder = git2::build::CheckoutBuilder::new();
+        let long_path = LongPath::default();
+
+        // Refusing a path from the notify callback cancels the whole checkout
+        // or reset, not just that file. libgit2 notifies before it writes
+        // anything, so the working tree is left as it was and the caller
+        // sees the operation fail.
+        if cfg!(windows) && !self.long_paths {
+            let refused = long_path.clone();
+
+            builder
+                .notify_on(git2::CHECKOUT_NOTIFICATION_UPDATED)
+                .notify(move |_, path, _, _, _| match path {
+                    Some(p) if workdir_len + p.as_os_str().len() >= MAX_PATH => {
+                        *refused.0.borrow_mut() = Some(p.to_path_buf());
+                        false
+                    }
+                    _ => true,
+                });
+        }
+
+        (builder, long_path)
+    }
+}
+
+// The path a checkout was cancelled over, once the notify callback refuses one.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LongPath(Rc<RefCell<Option<PathBuf>>>);
+
+impl LongPath {
+    // For a failed checkout: the long path if that's why, otherwise `error`.
+    pub(crate) fn or(&self, error: GitError) -> GitError {
+        match self.0.borrow_mut().take() {
+            Some(path) => GitError::PathTooLong(path),
+            None => error,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use git2;
+
+// libgit2 keeps calling back for as long as credentials are handed out, so a
+// rejected agent key would otherwise retry forever.
+const MAX_ATTEMPTS: usize = 3;
+
+pub fn remote_callbacks<'a>(config: &'a git2::Config) -> git2::RemoteCallbacks<'a> {
+    let mut attempts = 0;
+    let mut callbacks = git2::RemoteCallbacks::new();
+
+    callbacks.credentials(move |url, username, allowed| {
+        attempts += 1;
+
+        if attempts > MAX_ATTEMPTS {
+            return Err(git2::Error::from_str("Too many authentication attempts"));
+        }
+
+        if allowed.contains(git2::SSH_KEY) {
+            git2::Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else if allowed.contains(git2::USER_PASS_PLAINTEXT) {
+            git2::Cred::credential_helper(config, url, username)
+        } else {
+            git2::Cred::default()
+        }
+    });
+
+    callbacks
+}
@@ -0,0 +1,35 @@
+use git2;
+
+// Line and file counts for a diff, as `git diff --shortstat` reports them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GitDiffStats {
+    files: usize,
+    insertions: usize,
+    deletions: usize,
+}
+
+impl GitDiffStats {
+    pub fn new(stats: &git2::DiffStats) -> Self {
+        Self {
+            files: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+        }
+    }
+
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    pub fn insertions(&self) -> usize {
+        self.insertions
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.deletions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files == 0
+    }
+}
@@ -0,0 +1,9 @@
+// What a fetch cleans up once it has the remote's refs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prune {
+    Nothing,
+    // Remote-tracking branches whose branch is gone from the remote.
+    Branches,
+    // Those, plus local tags the remote no longer has, like `--prune-tags`.
+    BranchesAndTags,
+}
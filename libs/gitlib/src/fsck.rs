@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+// What `git fsck` found wrong with an object database. Dangling objects are
+// harmless leftovers; anything else means something is missing or corrupt.
+#[derive(Debug, Default)]
+pub struct GitFsck {
+    dangling: BTreeMap<String, usize>,
+    problems: Vec<String>,
+}
+
+impl GitFsck {
+    pub fn parse<'a, I: Iterator<Item = &'a str>>(lines: I) -> Self {
+        let mut fsck = Self::default();
+
+        for line in lines.map(str::trim).filter(|l| !l.is_empty()) {
+            if line.starts_with("dangling ") {
+                // "dangling commit <id>"
+                let kind = line.split_whitespace().nth(1).unwrap_or("object");
+                *fsck.dangling.entry(kind.to_string()).or_insert(0) += 1;
+            } else if !line.starts_with("notice:") {
+                // Notices are things like an unborn HEAD, which isn't damage.
+                fsck.problems.push(line.to_string());
+            }
+        }
+
+        fsck
+    }
+
+    pub fn add_problem(&mut self, problem: &str) {
+        self.problems.push(problem.to_string());
+    }
+
+    // Counts by object type, e.g. "commit" => 2.
+    pub fn dangling(&self) -> &BTreeMap<String, usize> {
+        &self.dangling
+    }
+
+    pub fn dangling_count(&self) -> usize {
+        self.dangling.values().sum()
+    }
+
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+
+// A line in a tracked file that matched a `git grep` pattern.
+pub struct GitGrepMatch {
+    path: PathBuf,
+    line: usize,
+    text: String,
+}
+
+impl GitGrepMatch {
+    // One record of `git grep --null --line-number`: "path\0line\0text".
+    pub fn parse(record: &str) -> Option<Self> {
+        let mut parts = record.splitn(3, '\0');
+
+        match (parts.next(), parts.next().and_then(|l| l.parse().ok()), parts.next()) {
+            (Some(path), Some(line), Some(text)) => {
+                Some(Self {
+                         path: PathBuf::from(path),
+                         line: line,
+                         text: text.to_string(),
+                     })
+            }
+            _ => None,
+        }
+    }
+
+    // Relative to the repository's root.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
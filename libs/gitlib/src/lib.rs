@@ -1,13 +1,46 @@
 extern crate git2;
 
+use std::path::PathBuf;
+
 #[derive(Debug)]
 pub enum GitError {
+    Apply,
+    Archive,
+    Blobs,
+    Branches,
+    Bundle,
     Checkout(GitBranch),
+    CherryPick,
+    Clone,
+    Commit,
+    Config,
+    Diff,
+    Fetch,
+    Fsck,
+    Gc,
+    Grep,
+    Head,
+    Index,
+    Init,
+    Lfs,
+    Log,
     Manifest,
+    Merge,
     OpenRepo,
+    // Too long to check out on Windows without core.longpaths.
+    PathTooLong(PathBuf),
+    Pull,
+    Push,
+    Rebase,
+    Remotes,
     RemoveUntracked,
     Reset,
+    Sparse,
+    Stash,
     Status,
+    Submodule,
+    Tag,
+    Upstream,
 }
 
 #[derive(Debug)]
@@ -25,11 +58,75 @@ impl From<git2::BranchType> for GitBranch {
     }
 }
 
+mod apply;
+pub use apply::ApplyResult;
+
+mod blob;
+pub use blob::GitBlob;
+
+mod branch;
+pub use branch::{BranchCreation, BranchDeletion};
+
+mod cherry_pick;
+pub use cherry_pick::CherryPick;
+
+mod command;
+
+mod commit;
+pub use commit::GitCommit;
+
+mod config;
+pub use config::{global_config, GitCoreConfig};
+
+mod credentials;
+
+mod diff;
+pub use diff::GitDiffStats;
+
+mod fetch;
+pub use fetch::Prune;
+
+mod fsck;
+pub use fsck::GitFsck;
+
+mod grep;
+pub use grep::GitGrepMatch;
+
+mod library;
+pub use library::{git_version, GitLibrary};
+
+mod merge;
+pub use merge::{MergeMode, MergeResult};
+
+mod pull;
+pub use pull::{PullMode, PullResult};
+
+mod push;
+pub use push::PushResult;
+
+mod reachability;
+pub use reachability::Reachability;
+
+mod rebase;
+pub use rebase::RebaseResult;
+
 mod reference;
-pub use reference::GitReference;
+pub use reference::{GitHead, GitReference};
+
+mod remote;
+pub use remote::GitRemote;
 
 mod repo;
-pub use repo::GitRepo;
+pub use repo::{GitRepo, RepoState};
+
+mod sparse;
+pub use sparse::GitSparseCheckout;
+
+mod stash;
+pub use stash::{GitStash, StashPop};
+
+mod submodule;
+pub use submodule::{GitSubmodule, SubmoduleState};
 
 mod status_entry;
 pub use status_entry::{GitStatusEntry, FileStatus};
@@ -0,0 +1,59 @@
+use std::os::raw::c_int;
+use std::path::Path;
+
+use super::command;
+
+// Mirrors GIT_FEATURE_* in libgit2's common.h.
+const FEATURE_THREADS: c_int = 1;
+const FEATURE_HTTPS: c_int = 1 << 1;
+const FEATURE_SSH: c_int = 1 << 2;
+
+// git2 0.6 doesn't wrap these, but libgit2 is linked in through it either way.
+extern "C" {
+    fn git_libgit2_version(major: *mut c_int, minor: *mut c_int, rev: *mut c_int);
+    fn git_libgit2_features() -> c_int;
+}
+
+// What the linked libgit2 was built with.
+#[derive(Debug, Clone)]
+pub struct GitLibrary {
+    version: (i32, i32, i32),
+    features: c_int,
+}
+
+impl GitLibrary {
+    pub fn linked() -> Self {
+        let (mut major, mut minor, mut rev) = (0, 0, 0);
+
+        let features = unsafe {
+            git_libgit2_version(&mut major, &mut minor, &mut rev);
+            git_libgit2_features()
+        };
+
+        Self {
+            version: (major, minor, rev),
+            features,
+        }
+    }
+
+    pub fn version(&self) -> String {
+        format!("{}.{}.{}", self.version.0, self.version.1, self.version.2)
+    }
+
+    pub fn threads(&self) -> bool {
+        self.features & FEATURE_THREADS != 0
+    }
+
+    pub fn https(&self) -> bool {
+        self.features & FEATURE_HTTPS != 0
+    }
+
+    pub fn ssh(&self) -> bool {
+        self.features & FEATURE_SSH != 0
+    }
+}
+
+// The git binary on PATH, which the commands libgit2 can't handle fall back to.
+pub fn git_version() -> Result<String, String> {
+    command::git(Path::new("."), &["--version"]).map(|v| v.trim().to_string())
+}
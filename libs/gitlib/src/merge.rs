@@ -0,0 +1,22 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeMode {
+    // Only ever move the branch forward; diverged branches are left alone.
+    FastForwardOnly,
+    // Always record a merge commit, like `git merge --no-ff`.
+    NoFastForward,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeResult {
+    UpToDate,
+    // How many upstream commits the branch moved forward by.
+    FastForwarded(usize),
+    // How many upstream commits the merge commit brought in.
+    Merged(usize),
+    // Local and upstream both have commits, and fast-forwarding was all that
+    // was asked for.
+    Diverged(usize, usize),
+    // The merge stopped on a conflict and was aborted, leaving the branch as it was.
+    Conflicted,
+    NoUpstream,
+}
@@ -0,0 +1,25 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PullMode {
+    // Only ever move the branch forward; diverged branches are left alone.
+    FastForward,
+    // Replay local commits onto the upstream, like `git pull --rebase`.
+    Rebase { autostash: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PullResult {
+    UpToDate,
+    // How many upstream commits the branch moved forward by.
+    FastForwarded(usize),
+    // How many local commits were replayed.
+    Rebased(usize),
+    // Rebased, but the stashed changes conflicted coming back; they're still
+    // in the stash.
+    AutostashConflicted(usize),
+    // Local and upstream both have commits, and fast-forwarding was all that
+    // was asked for.
+    Diverged(usize, usize),
+    // The rebase stopped on a conflict and was aborted, leaving the branch as it was.
+    Conflicted,
+    NoUpstream,
+}
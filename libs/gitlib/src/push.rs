@@ -0,0 +1,12 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum PushResult {
+    // How many commits went up.
+    Pushed(usize),
+    UpToDate,
+    NoUpstream,
+    // The upstream has commits the branch doesn't, so the push wouldn't
+    // fast-forward; it isn't attempted.
+    Behind(usize),
+    // The remote turned it down, with its reason.
+    Rejected(String),
+}
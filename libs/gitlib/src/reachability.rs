@@ -0,0 +1,46 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reachability {
+    Reachable,
+    // The host redirected to this URL.
+    Moved(String),
+    AuthFailed(String),
+    NotFound(String),
+    Unreachable(String),
+}
+
+impl Reachability {
+    // Classifies what `git ls-remote` printed, since the exit code alone
+    // doesn't say why a remote failed.
+    pub fn from_output(success: bool, stderr: &str) -> Self {
+        const REDIRECT: &str = "warning: redirecting to ";
+
+        let message = stderr
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with(REDIRECT))
+            .unwrap_or("")
+            .to_string();
+
+        if success {
+            return match stderr.lines().find(|l| l.starts_with(REDIRECT)) {
+                Some(l) => Reachability::Moved(l[REDIRECT.len()..].trim().to_string()),
+                None => Reachability::Reachable,
+            };
+        }
+
+        let lower = stderr.to_lowercase();
+
+        if ["authentication failed", "permission denied", "could not read username", "could not read password",
+            "403"]
+                   .iter()
+                   .any(|s| lower.contains(s)) {
+            Reachability::AuthFailed(message)
+        } else if ["repository not found", "does not appear to be a git repository", "404"]
+                      .iter()
+                      .any(|s| lower.contains(s)) {
+            Reachability::NotFound(message)
+        } else {
+            Reachability::Unreachable(message)
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebaseResult {
+    UpToDate,
+    // Nothing local to replay, so the branch just moved forward this many commits.
+    FastForwarded(usize),
+    // How many local commits were replayed.
+    Rebased(usize),
+    // Tracked files have changes, so the rebase wasn't started.
+    Dirty,
+    // The rebase stopped on a conflict and was aborted, leaving the branch as it was.
+    Conflicted,
+    NoUpstream,
+}
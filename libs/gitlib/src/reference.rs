@@ -1,7 +1,8 @@
 use git2;
 
 pub struct GitReference {
-    name: String
+    name: String,
+    shorthand: String,
 }
 
 impl GitReference {
@@ -11,10 +12,31 @@ impl GitReference {
             None => String::new()
         };
 
-        Self { name: name }
+        let shorthand = match reference.shorthand() {
+            Some(s) => String::from(s),
+            None => String::new()
+        };
+
+        Self {
+            name: name,
+            shorthand: shorthand,
+        }
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    pub fn shorthand(&self) -> &str {
+        &self.shorthand
+    }
+}
+
+// Where HEAD points: a branch by shorthand name, or a commit by short id.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitHead {
+    Branch(String),
+    Detached(String),
+    // A fresh repo with nothing committed yet.
+    Unborn,
 }
@@ -0,0 +1,35 @@
+use git2;
+
+pub struct GitRemote {
+    name: String,
+    url: Option<String>,
+    push_url: Option<String>,
+}
+
+impl GitRemote {
+    pub fn new(remote: &git2::Remote) -> Self {
+        Self {
+            name: remote.name().unwrap_or("").to_string(),
+            url: remote.url().map(String::from),
+            push_url: remote.pushurl().map(String::from),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // None when unset or not valid UTF-8.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    // Pushes go to the fetch URL unless remote.<name>.pushurl says otherwise.
+    pub fn push_url(&self) -> Option<&str> {
+        self.push_url.as_deref().or(self.url())
+    }
+
+    pub fn has_separate_push_url(&self) -> bool {
+        self.push_url.is_some() && self.push_url != self.url
+    }
+}
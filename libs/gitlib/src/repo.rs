@@ -1,7 +1,14 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::fs;
+use std::time::UNIX_EPOCH;
 
-use super::{git2, GitStatuses, GitError, GitReference, GitBranch, FileStatus};
+use super::{git2, command, credentials, ApplyResult, BranchCreation, BranchDeletion, CherryPick, GitStatuses, GitError,
+            GitReference, GitRemote, GitBranch, GitCoreConfig, GitSubmodule, GitCommit, GitBlob, GitDiffStats, GitFsck,
+            GitGrepMatch, GitHead, GitSparseCheckout, GitStash, MergeMode, MergeResult, Prune, PullMode, PullResult,
+            PushResult, Reachability, RebaseResult, FileStatus, StashPop};
+use super::config::LongPath;
 
 pub struct GitRepo {
     repo: git2::Repository,
@@ -23,79 +30,1084 @@ impl GitRepo {
            })
     }
 
+    // Clones with the git binary so credential helpers, ssh config and
+    // templates behave exactly as they would for a plain git clone.
+    // A depth makes a shallow clone of just that many commits.
+    pub fn clone_url<P: AsRef<Path>>(url: &str,
+                                     path: P,
+                                     template: Option<&Path>,
+                                     depth: Option<usize>)
+                                     -> Result<Self, GitError> {
+        let path_ref = path.as_ref();
+        let parent = path_ref.parent().unwrap_or_else(|| Path::new("."));
+        let dest = path_ref.to_str().ok_or(GitError::Clone)?;
+
+        fs::create_dir_all(parent).map_err(|_| GitError::Clone)?;
+
+        let template = template.map(|t| format!("--template={}", t.display()));
+        let depth = depth.map(|d| format!("--depth={}", d));
+        let mut args = vec!["clone", "--quiet"];
+        args.extend(template.as_deref());
+        args.extend(depth.as_deref());
+        args.extend(&["--", url, dest]);
+
+        command::git(parent, &args).map_err(|_| GitError::Clone)?;
+
+        Self::new(path_ref)
+    }
+
+    // Refuses to reinitialize an existing repo. Templates come from the given
+    // directory, or wherever git would otherwise look for them.
+    pub fn init<P: AsRef<Path>>(path: P, branch: &str, template: Option<&Path>) -> Result<Self, GitError> {
+        let path_ref = path.as_ref();
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.no_reinit(true).mkpath(true).initial_head(branch);
+
+        if let Some(t) = template {
+            opts.template_path(t);
+        }
+
+        let repo = git2::Repository::init_opts(path_ref, &opts).map_err(|_| GitError::Init)?;
+
+        Ok(Self {
+               repo: repo,
+               path: path_ref.to_owned(),
+           })
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
 
-    pub fn statuses(&self) -> Result<GitStatuses, GitError> {
-        // self.repo.graph_ahead_behind
+    // The .git directory, or the repo itself when bare.
+    pub fn git_dir(&self) -> &Path {
+        self.repo.path()
+    }
+
+    pub fn statuses(&self) -> Result<GitStatuses, GitError> {
+        let mut opts = git2::StatusOptions::new();
+
+        opts.include_ignored(false)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_unreadable_as_untracked(true)
+            .disable_pathspec_match(true)
+            .exclude_submodules(true);
+
+        let statuses = self.repo
+            .statuses(Some(&mut opts))
+            .map_err(|_| GitError::Status)?;
+
+        Ok(GitStatuses::new(statuses))
+    }
+
+    pub fn head_state(&self) -> GitHead {
+        let head = match self.repo.head() {
+            Ok(h) => h,
+            Err(_) => return GitHead::Unborn,
+        };
+
+        if head.is_branch() {
+            return GitHead::Branch(head.shorthand().unwrap_or("").to_string());
+        }
+
+        match head.target() {
+            Some(id) => GitHead::Detached(id.to_string()[..7].to_string()),
+            None => GitHead::Unborn,
+        }
+    }
+
+    pub fn submodules(&self) -> Result<Vec<GitSubmodule>, GitError> {
+        let submodules = self.repo.submodules().map_err(|_| GitError::Submodule)?;
+        let mut result = Vec::new();
+
+        for submodule in &submodules {
+            let name = submodule.name().ok_or(GitError::Submodule)?;
+
+            // Unspecified honours each submodule's configured ignore setting.
+            let status = self.repo
+                .submodule_status(name, git2::SubmoduleIgnore::Unspecified)
+                .map_err(|_| GitError::Submodule)?;
+
+            result.push(GitSubmodule::new(submodule, status));
+        }
+
+        Ok(result)
+    }
+
+    pub fn head(&self) -> Result<GitReference, GitError> {
+        let head = self.repo.head().map_err(|_| GitError::Head)?;
+
+        Ok(GitReference::new(head))
+    }
+
+    pub fn last_commit(&self) -> Result<GitCommit, GitError> {
+        let head = self.repo.head().map_err(|_| GitError::Commit)?;
+        let commit = head.peel(git2::ObjectType::Commit)
+            .map_err(|_| GitError::Commit)?
+            .into_commit()
+            .map_err(|_| GitError::Commit)?;
+
+        Ok(GitCommit::new(commit))
+    }
+
+    // Commits in the range ("v1.0..master"), or reachable from any local
+    // branch without one, newest first, stopping at the first one older than
+    // `since`.
+    pub fn commits(&self, range: Option<&str>, since: Option<i64>) -> Result<Vec<GitCommit>, GitError> {
+        let mut walk = self.repo.revwalk().map_err(|_| GitError::Log)?;
+        walk.set_sorting(git2::SORT_TIME);
+
+        match range {
+            Some(r) if r.contains("..") => walk.push_range(r),
+            Some(r) => {
+                self.repo
+                    .revparse_single(r)
+                    .and_then(|o| walk.push(o.id()))
+            }
+            None => walk.push_glob("heads"),
+        }.map_err(|_| GitError::Log)?;
+
+        let mut commits = Vec::new();
+
+        for id in walk {
+            let id = id.map_err(|_| GitError::Log)?;
+            let commit = self.repo.find_commit(id).map_err(|_| GitError::Log)?;
+
+            if let Some(since) = since {
+                if commit.time().seconds() < since {
+                    break;
+                }
+            }
+
+            commits.push(GitCommit::new(commit));
+        }
+
+        Ok(commits)
+    }
+
+    // Local branches with commits that no remote-tracking branch has, each
+    // with those commits, newest first.
+    pub fn unpushed(&self) -> Result<Vec<(String, Vec<GitCommit>)>, GitError> {
+        let branches = self.repo
+            .branches(Some(git2::BranchType::Local))
+            .map_err(|_| GitError::Branches)?;
+
+        let mut unpushed = Vec::new();
+
+        for branch in branches {
+            let (branch, _) = branch.map_err(|_| GitError::Branches)?;
+
+            let (name, tip) = match (branch.name(), branch.get().target()) {
+                (Ok(Some(n)), Some(t)) => (n.to_string(), t),
+                _ => continue,
+            };
+
+            let mut walk = self.repo.revwalk().map_err(|_| GitError::Log)?;
+            walk.set_sorting(git2::SORT_TIME);
+            walk.push(tip).map_err(|_| GitError::Log)?;
+            walk.hide_glob("remotes").map_err(|_| GitError::Log)?;
+
+            let mut commits = Vec::new();
+
+            for id in walk {
+                let id = id.map_err(|_| GitError::Log)?;
+                let commit = self.repo.find_commit(id).map_err(|_| GitError::Log)?;
+                commits.push(GitCommit::new(commit));
+            }
+
+            if !commits.is_empty() {
+                unpushed.push((name, commits));
+            }
+        }
+
+        Ok(unpushed)
+    }
+
+    // Commits (ahead, behind) relative to the current branch's upstream, or
+    // None when HEAD is detached or the branch doesn't track anything.
+    pub fn ahead_behind(&self) -> Result<Option<(usize, usize)>, GitError> {
+        let head = match self.repo.head() {
+            Ok(h) => h,
+            Err(_) => return Ok(None),
+        };
+
+        if !head.is_branch() {
+            return Ok(None);
+        }
+
+        self.upstream_counts(&git2::Branch::wrap(head))
+    }
+
+    // Same as ahead_behind, for any local branch rather than the current one.
+    pub fn branch_ahead_behind(&self, branch_name: &str) -> Result<Option<(usize, usize)>, GitError> {
+        match self.repo.find_branch(branch_name, git2::BranchType::Local) {
+            Ok(branch) => self.upstream_counts(&branch),
+            Err(_) => Ok(None),
+        }
+    }
+
+    // Tracked changes only, staged or not, as `git diff HEAD` counts them.
+    pub fn workdir_diff_stats(&self) -> Result<GitDiffStats, GitError> {
+        // An unborn branch diffs against the empty tree.
+        let tree = match self.repo.head() {
+            Ok(head) => Some(head.peel(git2::ObjectType::Tree).map_err(|_| GitError::Diff)?),
+            Err(_) => None,
+        };
+
+        let diff = self.repo
+            .diff_tree_to_workdir_with_index(tree.as_ref().and_then(|t| t.as_tree()), None)
+            .map_err(|_| GitError::Diff)?;
+        let stats = diff.stats().map_err(|_| GitError::Diff)?;
+
+        Ok(GitDiffStats::new(&stats))
+    }
+
+    // What a commit changed relative to its first parent, or everything in
+    // it for a root commit.
+    pub fn commit_diff_stats(&self, id: &str) -> Result<GitDiffStats, GitError> {
+        let commit = git2::Oid::from_str(id)
+            .and_then(|id| self.repo.find_commit(id))
+            .map_err(|_| GitError::Diff)?;
+
+        let tree = commit.tree().map_err(|_| GitError::Diff)?;
+        let parent = match commit.parent(0) {
+            Ok(p) => Some(p.tree().map_err(|_| GitError::Diff)?),
+            Err(_) => None,
+        };
+
+        let diff = self.repo
+            .diff_tree_to_tree(parent.as_ref(), Some(&tree), None)
+            .map_err(|_| GitError::Diff)?;
+        let stats = diff.stats().map_err(|_| GitError::Diff)?;
+
+        Ok(GitDiffStats::new(&stats))
+    }
+
+    // What the current branch has that `base` doesn't, like `git diff base...HEAD`.
+    // Without a base that's the branch's upstream; None if there's nothing to
+    // compare against.
+    pub fn diff_stats(&self, base: Option<&str>) -> Result<Option<GitDiffStats>, GitError> {
+        let head = match self.repo.head() {
+            Ok(h) => h,
+            Err(_) => return Ok(None),
+        };
+
+        let local = head.target().ok_or(GitError::Head)?;
+
+        let base = match base {
+            Some(name) => {
+                match self.repo.revparse_single(name) {
+                    Ok(object) => object.id(),
+                    Err(_) => return Ok(None),
+                }
+            }
+            None if head.is_branch() => {
+                match git2::Branch::wrap(head).upstream() {
+                    Ok(u) => u.get().target().ok_or(GitError::Upstream)?,
+                    Err(_) => return Ok(None),
+                }
+            }
+            None => return Ok(None),
+        };
+
+        // Diffing from the merge base leaves out whatever landed upstream since.
+        let merge_base = self.repo.merge_base(local, base).map_err(|_| GitError::Diff)?;
+
+        let tree = |id| {
+            self.repo
+                .find_commit(id)
+                .and_then(|c| c.tree())
+                .map_err(|_| GitError::Diff)
+        };
+
+        let diff = self.repo
+            .diff_tree_to_tree(Some(&tree(merge_base)?), Some(&tree(local)?), None)
+            .map_err(|_| GitError::Diff)?;
+        let stats = diff.stats().map_err(|_| GitError::Diff)?;
+
+        Ok(Some(GitDiffStats::new(&stats)))
+    }
+
+    // The branch origin/HEAD points at, falling back to whichever of main
+    // and master exists locally.
+    pub fn default_branch(&self) -> Option<String> {
+        const ORIGIN_PREFIX: &str = "refs/remotes/origin/";
+
+        let remote_head = self.repo
+            .find_reference("refs/remotes/origin/HEAD")
+            .ok()
+            .and_then(|r| r.symbolic_target().map(String::from));
+
+        if let Some(target) = remote_head {
+            if let Some(name) = target.strip_prefix(ORIGIN_PREFIX) {
+                return Some(name.to_string());
+            }
+        }
+
+        ["main", "master"]
+            .iter()
+            .find(|n| self.repo.find_branch(n, git2::BranchType::Local).is_ok())
+            .map(|n| n.to_string())
+    }
+
+    // When anything was last fetched, as seconds since the Unix epoch.
+    pub fn last_fetch(&self) -> Option<i64> {
+        let modified = fs::metadata(self.git_dir().join("FETCH_HEAD"))
+            .and_then(|m| m.modified())
+            .ok()?;
+
+        modified
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs() as i64)
+    }
+
+    pub fn remote_names(&self) -> Result<Vec<String>, GitError> {
+        let remotes = self.repo.remotes().map_err(|_| GitError::Remotes)?;
+
+        Ok(remotes.iter().flatten().map(String::from).collect())
+    }
+
+    pub fn remotes(&self) -> Result<Vec<GitRemote>, GitError> {
+        self.remote_names()?
+            .iter()
+            .map(|name| {
+                     self.repo
+                         .find_remote(name)
+                         .map(|r| GitRemote::new(&r))
+                         .map_err(|_| GitError::Remotes)
+                 })
+            .collect()
+    }
+
+    pub fn remote_url(&self, name: &str) -> Result<Option<String>, GitError> {
+        match self.repo.find_remote(name) {
+            Ok(remote) => Ok(remote.url().map(String::from)),
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(_) => Err(GitError::Remotes),
+        }
+    }
+
+    pub fn set_remote_url(&self, name: &str, url: &str) -> Result<(), GitError> {
+        self.repo.remote_set_url(name, url).map_err(|_| GitError::Remotes)
+    }
+
+    pub fn set_remote_push_url(&self, name: &str, url: &str) -> Result<(), GitError> {
+        self.repo.remote_set_pushurl(name, Some(url)).map_err(|_| GitError::Remotes)
+    }
+
+    // A lightweight `git ls-remote` against the remote. This goes through
+    // git itself since libgit2 doesn't report redirects.
+    pub fn check_remote(&self, name: &str) -> Result<Reachability, GitError> {
+        let output = command::run(&self.path, &["ls-remote", "--heads", name])
+            .map_err(|_| GitError::Remotes)?;
+
+        Ok(Reachability::from_output(output.status.success(), &String::from_utf8_lossy(&output.stderr)))
+    }
+
+    pub fn fetch(&self, prune: Prune) -> Result<(), GitError> {
+        match self.fetch_remotes(prune)?.into_iter().all(|(_, ok)| ok) {
+            true => Ok(()),
+            false => Err(GitError::Fetch),
+        }
+    }
+
+    // Fetches every remote, carrying on past any that fail, and says which
+    // ones worked.
+    pub fn fetch_remotes(&self, prune: Prune) -> Result<Vec<(String, bool)>, GitError> {
+        let config = self.repo.config().map_err(|_| GitError::Fetch)?;
+        let remotes = self.repo.remotes().map_err(|_| GitError::Fetch)?;
+
+        Ok(remotes
+               .iter()
+               .flatten()
+               .map(|name| (name.to_string(), self.fetch_remote(&config, name, prune).is_ok()))
+               .collect())
+    }
+
+    fn fetch_remote(&self, config: &git2::Config, name: &str, prune: Prune) -> Result<(), GitError> {
+        const TAGS_REFSPEC: &str = "refs/tags/*:refs/tags/*";
+
+        let mut remote = self.repo
+            .find_remote(name)
+            .map_err(|_| GitError::Fetch)?;
+
+        let mut options = git2::FetchOptions::new();
+        options.remote_callbacks(credentials::remote_callbacks(config));
+
+        // An empty refspec list fetches using the remote's configured refspecs.
+        let mut refspecs = Vec::new();
+
+        match prune {
+            Prune::Nothing => (),
+            Prune::Branches => {
+                options.prune(git2::FetchPrune::On);
+            }
+            Prune::BranchesAndTags => {
+                options.prune(git2::FetchPrune::On);
+
+                // Pruning only touches refs a refspec maps onto, so have
+                // one cover the tags the same way git does.
+                let configured = remote.fetch_refspecs().map_err(|_| GitError::Fetch)?;
+                refspecs.extend(configured.iter().flatten().map(String::from));
+                refspecs.push(String::from(TAGS_REFSPEC));
+            }
+        }
+
+        let refspecs = refspecs.iter().map(String::as_str).collect::<Vec<_>>();
+
+        remote
+            .fetch(&refspecs, Some(&mut options), None)
+            .map_err(|_| GitError::Fetch)
+    }
+
+    // Fetches with pruning and returns the remote-tracking branches that
+    // went away, e.g. "origin/feature".
+    pub fn prune(&self) -> Result<Vec<String>, GitError> {
+        let before = self.remote_branch_names()?;
+        self.fetch_remotes(Prune::Branches)?;
+        let after = self.remote_branch_names()?;
+
+        Ok(before.into_iter().filter(|b| !after.contains(b)).collect())
+    }
+
+    fn remote_branch_names(&self) -> Result<Vec<String>, GitError> {
+        let branches = self.repo
+            .branches(Some(git2::BranchType::Remote))
+            .map_err(|_| GitError::Branches)?;
+
+        let mut names = Vec::new();
+
+        for branch in branches {
+            let (branch, _) = branch.map_err(|_| GitError::Branches)?;
+
+            if branch.get().symbolic_target().is_some() {
+                continue;
+            }
+
+            if let Ok(Some(name)) = branch.name() {
+                names.push(name.to_string());
+            }
+        }
+
+        Ok(names)
+    }
+
+    // Fetches, then brings the current branch up to date with its upstream.
+    pub fn pull(&self, mode: PullMode) -> Result<PullResult, GitError> {
+        self.fetch(Prune::Nothing).map_err(|_| GitError::Pull)?;
+
+        let head = match self.repo.head() {
+            Ok(ref h) if h.is_branch() => h.name().map(String::from).ok_or(GitError::Pull)?,
+            _ => return Ok(PullResult::NoUpstream),
+        };
+
+        let branch = git2::Branch::wrap(self.repo.find_reference(&head).map_err(|_| GitError::Pull)?);
+
+        let (ahead, behind) = match self.upstream_counts(&branch).map_err(|_| GitError::Pull)? {
+            Some(counts) => counts,
+            None => return Ok(PullResult::NoUpstream),
+        };
+
+        if behind == 0 {
+            return Ok(PullResult::UpToDate);
+        }
+
+        match mode {
+            PullMode::FastForward if ahead > 0 => Ok(PullResult::Diverged(ahead, behind)),
+            PullMode::FastForward => {
+                self.fast_forward(branch, "pull: fast-forward").map_err(|_| GitError::Pull)?;
+                Ok(PullResult::FastForwarded(behind))
+            }
+            PullMode::Rebase { autostash } => self.rebase_onto_upstream(autostash, ahead, behind),
+        }
+    }
+
+    // Merges the already fetched upstream into the current branch. Anything
+    // but a clean merge is aborted, so no repo is left half merged.
+    pub fn merge_upstream(&self, mode: MergeMode) -> Result<MergeResult, GitError> {
+        let head = match self.repo.head() {
+            Ok(ref h) if h.is_branch() => h.name().map(String::from).ok_or(GitError::Merge)?,
+            _ => return Ok(MergeResult::NoUpstream),
+        };
+
+        let branch = git2::Branch::wrap(self.repo.find_reference(&head).map_err(|_| GitError::Merge)?);
+
+        let (ahead, behind) = match self.upstream_counts(&branch).map_err(|_| GitError::Merge)? {
+            Some(counts) => counts,
+            None => return Ok(MergeResult::NoUpstream),
+        };
+
+        if behind == 0 {
+            return Ok(MergeResult::UpToDate);
+        }
+
+        match mode {
+            MergeMode::FastForwardOnly if ahead > 0 => Ok(MergeResult::Diverged(ahead, behind)),
+            MergeMode::FastForwardOnly => {
+                self.fast_forward(branch, "merge: fast-forward").map_err(|_| GitError::Merge)?;
+                Ok(MergeResult::FastForwarded(behind))
+            }
+            MergeMode::NoFastForward => self.merge_commit(behind),
+        }
+    }
+
+    // Rebases the current branch onto its already fetched upstream. Repos
+    // with tracked changes are left alone rather than autostashed.
+    pub fn rebase_upstream(&self) -> Result<RebaseResult, GitError> {
+        let head = match self.repo.head() {
+            Ok(ref h) if h.is_branch() => h.name().map(String::from).ok_or(GitError::Rebase)?,
+            _ => return Ok(RebaseResult::NoUpstream),
+        };
+
+        let branch = git2::Branch::wrap(self.repo.find_reference(&head).map_err(|_| GitError::Rebase)?);
+
+        let (ahead, behind) = match self.upstream_counts(&branch).map_err(|_| GitError::Rebase)? {
+            Some(counts) => counts,
+            None => return Ok(RebaseResult::NoUpstream),
+        };
+
+        if behind == 0 {
+            return Ok(RebaseResult::UpToDate);
+        }
+
+        if self.has_tracked_changes()? {
+            return Ok(RebaseResult::Dirty);
+        }
+
+        match self.rebase_onto_upstream(false, ahead, behind).map_err(|_| GitError::Rebase)? {
+            PullResult::FastForwarded(n) => Ok(RebaseResult::FastForwarded(n)),
+            PullResult::Rebased(n) => Ok(RebaseResult::Rebased(n)),
+            PullResult::Conflicted => Ok(RebaseResult::Conflicted),
+            // Only an autostash can report anything else.
+            _ => Err(GitError::Rebase),
+        }
+    }
+
+    // Pushes the current branch to its upstream, but only when that's a
+    // fast-forward; nothing is ever forced.
+    pub fn push(&self) -> Result<PushResult, GitError> {
+        let name = match self.repo.head() {
+            Ok(ref h) if h.is_branch() => h.shorthand().map(String::from).ok_or(GitError::Push)?,
+            _ => return Ok(PushResult::NoUpstream),
+        };
+
+        let branch = self.repo
+            .find_branch(&name, git2::BranchType::Local)
+            .map_err(|_| GitError::Push)?;
+
+        let (ahead, behind) = match self.upstream_counts(&branch).map_err(|_| GitError::Push)? {
+            Some(counts) => counts,
+            None => return Ok(PushResult::NoUpstream),
+        };
+
+        if behind > 0 {
+            return Ok(PushResult::Behind(behind));
+        }
+
+        if ahead == 0 {
+            return Ok(PushResult::UpToDate);
+        }
+
+        let config = self.repo.config().map_err(|_| GitError::Push)?;
+        let remote_name = config
+            .get_string(&format!("branch.{}.remote", name))
+            .map_err(|_| GitError::Push)?;
+        let merge = config
+            .get_string(&format!("branch.{}.merge", name))
+            .map_err(|_| GitError::Push)?;
+
+        let mut remote = self.repo
+            .find_remote(&remote_name)
+            .map_err(|_| GitError::Push)?;
+
+        // No leading '+', so the remote refuses anything but a fast-forward
+        // even if it moved on since the last fetch.
+        let refspec = format!("refs/heads/{}:{}", name, merge);
+        let mut rejected = None;
+
+        {
+            let mut callbacks = credentials::remote_callbacks(&config);
+            callbacks.push_update_reference(|_, status| {
+                                                if let Some(status) = status {
+                                                    rejected = Some(status.to_string());
+                                                }
+
+                                                Ok(())
+                                            });
+
+            let mut options = git2::PushOptions::new();
+            options.remote_callbacks(callbacks);
+
+            remote
+                .push(&[refspec.as_str()], Some(&mut options))
+                .map_err(|_| GitError::Push)?;
+        }
+
+        Ok(match rejected {
+               Some(reason) => PushResult::Rejected(reason),
+               None => PushResult::Pushed(ahead),
+           })
+    }
+
+    pub fn is_shallow(&self) -> bool {
+        self.repo.is_shallow()
+    }
+
+    // Fetches the rest of a shallow clone's history.
+    pub fn unshallow(&self) -> Result<(), GitError> {
+        command::git(&self.path, &["fetch", "--quiet", "--unshallow"]).map_err(|_| GitError::Fetch)?;
+
+        Ok(())
+    }
+
+    // Tags HEAD, annotated when there's a message. False if the tag already
+    // exists, wherever it points.
+    pub fn tag(&self, name: &str, message: Option<&str>) -> Result<bool, GitError> {
+        if self.repo.find_reference(&format!("refs/tags/{}", name)).is_ok() {
+            return Ok(false);
+        }
+
+        let head = self.repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .ok_or(GitError::Head)?;
+        let target = self.repo.find_object(head, None).map_err(|_| GitError::Tag)?;
+
+        match message {
+            Some(message) => {
+                let tagger = self.repo.signature().map_err(|_| GitError::Tag)?;
+                self.repo.tag(name, &target, &tagger, message, false)
+            }
+            None => self.repo.tag_lightweight(name, &target, false),
+        }.map_err(|_| GitError::Tag)?;
+
+        Ok(true)
+    }
+
+    // Stages every change to a tracked file and commits it, returning how
+    // many files went in, or None when there was nothing to commit. This
+    // goes through `git commit --all` so hooks and commit signing still apply.
+    pub fn commit_tracked(&self, message: &str, dry_run: bool) -> Result<Option<usize>, GitError> {
+        let changed = self.tracked_changes()?;
+
+        if changed == 0 {
+            return Ok(None);
+        }
+
+        if !dry_run {
+            command::git(&self.path, &["commit", "--all", "--quiet", "--message", message])
+                .map_err(|_| GitError::Commit)?;
+        }
+
+        Ok(Some(changed))
+    }
+
+    pub fn stash(&mut self, message: &str) -> Result<(), GitError> {
+        let signature = self.repo.signature().map_err(|_| GitError::Stash)?;
+
+        self.repo
+            .stash_save(&signature, message, None)
+            .map_err(|_| GitError::Stash)?;
+
+        Ok(())
+    }
+
+    // Most recent first.
+    pub fn stashes(&mut self) -> Result<Vec<GitStash>, GitError> {
+        let mut stashes = Vec::new();
+
+        self.repo
+            .stash_foreach(|index, message, _| {
+                               stashes.push(GitStash::new(index, message));
+                               true
+                           })
+            .map_err(|_| GitError::Stash)?;
+
+        Ok(stashes)
+    }
+
+    pub fn stash_pop(&mut self, index: usize) -> Result<StashPop, GitError> {
+        let (builder, long_path) = self.checkout_builder();
+        let mut options = git2::StashApplyOptions::new();
+        options.checkout_options(builder);
+
+        match self.repo.stash_pop(index, Some(&mut options)) {
+            Ok(_) => Ok(StashPop::Popped),
+            Err(ref e) if e.code() == git2::ErrorCode::Conflict => Ok(StashPop::Conflicted),
+            Err(_) => Err(long_path.or(GitError::Stash)),
+        }
+    }
+
+    pub fn stash_count(&self) -> Result<usize, GitError> {
+        // Each stash is an entry in the refs/stash reflog, which reads as
+        // empty when nothing has ever been stashed.
+        let reflog = self.repo.reflog("refs/stash").map_err(|_| GitError::Stash)?;
+
+        Ok(reflog.len())
+    }
+
+    pub fn reset(&self) -> Result<GitReference, GitError> {
+        let head = self.repo.head().map_err(|_| GitError::Reset)?;
+        let obj = head.peel(git2::ObjectType::Any)
+            .map_err(|_| GitError::Reset)?;
+
+        let (mut builder, long_path) = self.checkout_builder();
+        builder
+            .remove_untracked(true) // this is ignored for a reset :()
+            .progress(|path, a, b| {
+                          if path == None {
+                              return;
+                          }
+
+                          println!("{:?} {:?} {:?}", path, a, b)
+                      });
+
+        self.repo
+            .reset(&obj, git2::ResetType::Hard, Some(&mut builder))
+            .map_err(|_| long_path.or(GitError::Reset))?;
+
+        Ok(GitReference::new(head))
+    }
+
+    // Returns whether it was a local branch or a remote one, left detached.
+    pub fn checkout(&self, branch_name: &str) -> Result<GitBranch, GitError> {
+        let branch_type = branch_type(branch_name);
+
+        let branch = self.repo
+            .find_branch(branch_name, branch_type)
+            .map_err(|_| GitError::Checkout(GitBranch::from(branch_type)))?;
+
+        let obj = branch
+            .get()
+            .peel(git2::ObjectType::Any)
+            .map_err(|_| GitError::Checkout(GitBranch::from(branch_type)))?;
+
+        let (mut builder, long_path) = self.checkout_builder();
+
+        self.repo
+            .checkout_tree(&obj, Some(&mut builder))
+            .map_err(|_| long_path.or(GitError::Checkout(GitBranch::from(branch_type))))?;
+
+        // Checking out the tree alone leaves HEAD behind, so the repo would
+        // report every switched file as a change.
+        match branch_type {
+            git2::BranchType::Local => {
+                let name = branch
+                    .get()
+                    .name()
+                    .ok_or(GitError::Checkout(GitBranch::from(branch_type)))?;
+                self.repo.set_head(name)
+            }
+            git2::BranchType::Remote => self.repo.set_head_detached(obj.id()),
+        }.map_err(|_| GitError::Checkout(GitBranch::from(branch_type)))?;
+
+        Ok(GitBranch::from(branch_type))
+    }
+
+    pub fn branch_names(&self) -> Result<Vec<String>, GitError> {
+        let branches = self.repo.branches(None).map_err(|_| GitError::Branches)?;
+        let mut names = Vec::new();
+
+        for branch in branches {
+            let (branch, _) = branch.map_err(|_| GitError::Branches)?;
+
+            // Symbolic remote refs like origin/HEAD aren't something to check out.
+            if branch.get().symbolic_target().is_some() {
+                continue;
+            }
+
+            if let Ok(Some(name)) = branch.name() {
+                names.push(name.to_string());
+            }
+        }
+
+        Ok(names)
+    }
+
+    // Local branches committed to at or after `since`.
+    pub fn active_branches(&self, since: i64) -> Result<Vec<String>, GitError> {
+        let branches = self.repo
+            .branches(Some(git2::BranchType::Local))
+            .map_err(|_| GitError::Branches)?;
+
+        let mut active = Vec::new();
+
+        for branch in branches {
+            let (branch, _) = branch.map_err(|_| GitError::Branches)?;
+
+            let tip = match branch.get().target().and_then(|t| self.repo.find_commit(t).ok()) {
+                Some(c) => c,
+                None => continue,
+            };
+
+            if tip.time().seconds() < since {
+                continue;
+            }
+
+            if let Ok(Some(name)) = branch.name() {
+                active.push(name.to_string());
+            }
+        }
+
+        Ok(active)
+    }
+
+    // The newest commit time on any local branch, even outside whatever
+    // period is being looked at.
+    pub fn last_activity(&self) -> Result<Option<i64>, GitError> {
+        let branches = self.repo
+            .branches(Some(git2::BranchType::Local))
+            .map_err(|_| GitError::Branches)?;
+
+        let mut newest = None;
+
+        for branch in branches {
+            let (branch, _) = branch.map_err(|_| GitError::Branches)?;
+
+            if let Some(tip) = branch.get().target().and_then(|t| self.repo.find_commit(t).ok()) {
+                newest = newest.max(Some(tip.time().seconds()));
+            }
+        }
+
+        Ok(newest)
+    }
+
+    // Starts from HEAD unless given a ref. A checkout that would overwrite
+    // local changes is refused, leaving the new branch created but not
+    // checked out.
+    pub fn create_branch(&self, name: &str, from: Option<&str>, checkout: bool) -> Result<BranchCreation, GitError> {
+        if self.repo.find_branch(name, git2::BranchType::Local).is_ok() {
+            return Ok(BranchCreation::Exists);
+        }
+
+        let start = match from {
+            Some(rev) => self.repo.revparse_single(rev),
+            None => self.repo.head().and_then(|h| h.peel(git2::ObjectType::Any)),
+        };
+
+        let commit = match start.and_then(|o| o.peel(git2::ObjectType::Commit)) {
+            Ok(object) => object.into_commit().map_err(|_| GitError::Branches)?,
+            Err(_) => return Ok(BranchCreation::NoStartPoint),
+        };
+
+        let branch = self.repo
+            .branch(name, &commit, false)
+            .map_err(|_| GitError::Branches)?;
+
+        if !checkout {
+            return Ok(BranchCreation::Created);
+        }
+
+        let (mut builder, _) = self.checkout_builder();
+
+        if self.repo.checkout_tree(commit.as_object(), Some(&mut builder)).is_err() {
+            return Ok(BranchCreation::Created);
+        }
+
+        let refname = branch.get().name().ok_or(GitError::Checkout(GitBranch::Local))?;
+        self.repo.set_head(refname).map_err(|_| GitError::Checkout(GitBranch::Local))?;
+
+        Ok(BranchCreation::CheckedOut)
+    }
+
+    // Like `git branch -d`: a branch is merged if its upstream, or failing
+    // that HEAD, already has every commit on it.
+    pub fn delete_branch(&self, name: &str, force: bool) -> Result<BranchDeletion, GitError> {
+        let mut branch = match self.repo.find_branch(name, git2::BranchType::Local) {
+            Ok(b) => b,
+            Err(_) => return Ok(BranchDeletion::NotFound),
+        };
+
+        if branch.is_head() {
+            return Ok(BranchDeletion::Current);
+        }
+
+        if !force {
+            let tip = branch.get().target().ok_or(GitError::Branches)?;
+            let base = match branch.upstream() {
+                Ok(u) => u.get().target(),
+                Err(_) => self.repo.head().ok().and_then(|h| h.target()),
+            };
+
+            let merged = match base {
+                Some(base) => {
+                    base == tip ||
+                    self.repo
+                        .graph_descendant_of(base, tip)
+                        .map_err(|_| GitError::Branches)?
+                }
+                None => false,
+            };
+
+            if !merged {
+                return Ok(BranchDeletion::Unmerged);
+            }
+        }
+
+        branch.delete().map_err(|_| GitError::Branches)?;
+
+        Ok(BranchDeletion::Deleted)
+    }
+
+    // Applies a unified diff to the working tree. git apply is all or
+    // nothing, so a rejected patch leaves the repo untouched.
+    pub fn apply_patch(&self, patch: &Path, check_only: bool) -> Result<ApplyResult, GitError> {
+        let patch = patch.to_str().ok_or(GitError::Apply)?;
 
-        let mut opts = git2::StatusOptions::new();
+        let mut args = vec!["apply"];
 
-        opts.include_ignored(false)
-            .include_untracked(true)
-            .recurse_untracked_dirs(true)
-            .include_unreadable_as_untracked(true)
-            .disable_pathspec_match(true)
-            .exclude_submodules(true);
+        if check_only {
+            args.push("--check");
+        }
 
-        let statuses = self.repo
-            .statuses(Some(&mut opts))
-            .map_err(|_| GitError::Status)?;
+        args.push(patch);
 
-        Ok(GitStatuses::new(statuses))
+        let output = command::run(&self.path, &args).map_err(|_| GitError::Apply)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().last().unwrap_or("").trim_start_matches("error: ");
+
+            return Ok(ApplyResult::Rejected(reason.to_string()));
+        }
+
+        Ok(match check_only {
+               true => ApplyResult::Applies,
+               false => ApplyResult::Applied,
+           })
     }
 
-    pub fn reset(&self) -> Result<GitReference, GitError> {
-        let head = self.repo.head().map_err(|_| GitError::Reset)?;
-        let obj = head.peel(git2::ObjectType::Any)
-            .map_err(|_| GitError::Reset)?;
+    // Applies the commit on top of HEAD, noting where it came from the way
+    // `cherry-pick -x` does. A conflict is aborted rather than left for later.
+    pub fn cherry_pick(&self, rev: &str) -> Result<CherryPick, GitError> {
+        let target = match self.repo.revparse_single(rev).and_then(|o| o.peel(git2::ObjectType::Commit)) {
+            Ok(o) => o.id(),
+            Err(_) => return Ok(CherryPick::NotFound),
+        };
 
-        let mut builder = git2::build::CheckoutBuilder::new();
-        let mut options = builder
-            .remove_untracked(true) // this is ignored for a reset :()
-            .progress(|path, a, b| {
-                          if path == None {
-                              return;
-                          }
+        let head = self.repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .ok_or(GitError::Head)?;
 
-                          println!("{:?} {:?} {:?}", path, a, b)
-                      });
+        if head == target || self.repo.graph_descendant_of(head, target).map_err(|_| GitError::CherryPick)? {
+            return Ok(CherryPick::AlreadyApplied);
+        }
 
-        self.repo
-            .reset(&obj, git2::ResetType::Hard, Some(options))
-            .map_err(|_| GitError::Reset)?;
+        let output = command::run(&self.path, &["cherry-pick", "-x", &target.to_string()])
+            .map_err(|_| GitError::CherryPick)?;
 
-        Ok(GitReference::new(head))
+        if output.status.success() {
+            return Ok(CherryPick::Picked);
+        }
+
+        // Refusing to start, e.g. over local changes, leaves nothing to abort.
+        if self.state() == RepoState::Clean {
+            return Err(GitError::CherryPick);
+        }
+
+        command::git(&self.path, &["cherry-pick", "--abort"]).map_err(|_| GitError::CherryPick)?;
+
+        Ok(CherryPick::Conflicted)
     }
 
-    pub fn checkout(&self, branch_name: &str) -> Result<(), GitError> {
-        let branch_type = match branch_name.find("origin/") {
-            Some(_) => git2::BranchType::Remote,
-            None => git2::BranchType::Local,
+    // Branches whose tip is, or descends from, the given commit or tag. None
+    // when the repo doesn't have the object at all.
+    pub fn branches_containing(&self, rev: &str) -> Result<Option<Vec<(String, GitBranch)>>, GitError> {
+        let target = match self.repo.revparse_single(rev).and_then(|o| o.peel(git2::ObjectType::Commit)) {
+            Ok(o) => o.id(),
+            Err(_) => return Ok(None),
         };
 
+        let branches = self.repo.branches(None).map_err(|_| GitError::Branches)?;
+        let mut containing = Vec::new();
+
+        for branch in branches {
+            let (branch, branch_type) = branch.map_err(|_| GitError::Branches)?;
+
+            if branch.get().symbolic_target().is_some() {
+                continue;
+            }
+
+            let tip = match branch.get().target() {
+                Some(t) => t,
+                None => continue,
+            };
+
+            let contains = tip == target ||
+                           self.repo
+                               .graph_descendant_of(tip, target)
+                               .map_err(|_| GitError::Branches)?;
+
+            if let (true, Ok(Some(name))) = (contains, branch.name()) {
+                containing.push((name.to_string(), GitBranch::from(branch_type)));
+            }
+        }
+
+        Ok(Some(containing))
+    }
+
+    pub fn checkout_preview(&self, branch_name: &str) -> Result<Vec<PathBuf>, GitError> {
+        let branch_type = branch_type(branch_name);
+        let err = |_| GitError::Checkout(GitBranch::from(branch_type));
+
         let branch = self.repo
             .find_branch(branch_name, branch_type)
-            .map_err(|_| GitError::Checkout(GitBranch::from(branch_type)))?;
+            .map_err(err)?;
 
-        let obj = branch
+        let target = branch
             .get()
-            .peel(git2::ObjectType::Any)
-            .map_err(|_| GitError::Checkout(GitBranch::from(branch_type)))?;
+            .peel(git2::ObjectType::Tree)
+            .map_err(err)?;
 
-        self.repo
-            .checkout_tree(&obj, None)
-            .map_err(|_| GitError::Checkout(GitBranch::from(branch_type)))?;
+        let current = self.repo
+            .head()
+            .and_then(|h| h.peel(git2::ObjectType::Tree))
+            .map_err(err)?;
 
-        println!("  {} {}",
-                 match branch_type {
-                     git2::BranchType::Local => " [Local]",
-                     git2::BranchType::Remote => "[Remote]",
-                 },
-                 branch_name);
+        let diff = self.repo
+            .diff_tree_to_tree(current.as_tree(), target.as_tree(), None)
+            .map_err(err)?;
 
-        Ok(())
+        let paths = diff.deltas()
+            .filter_map(|d| d.new_file().path().or_else(|| d.old_file().path()))
+            .map(Path::to_path_buf)
+            .collect();
+
+        Ok(paths)
+    }
+
+    // Stages new, changed and deleted files matching any of the pathspecs,
+    // like `git add`, returning how many were staged. Ignored files are left out.
+    pub fn stage(&self, pathspecs: &[&str]) -> Result<usize, GitError> {
+        let before = self.unstaged(pathspecs)?;
+
+        let mut index = self.repo.index().map_err(|_| GitError::Index)?;
+
+        index
+            .add_all(pathspecs, git2::ADD_DEFAULT, None)
+            .and_then(|_| index.update_all(pathspecs, None))
+            .and_then(|_| index.write())
+            .map_err(|_| GitError::Index)?;
+
+        // libgit2 reports every matching path to its callbacks, changed or
+        // not, so count what stopped being unstaged instead.
+        Ok(before.saturating_sub(self.unstaged(pathspecs)?))
+    }
+
+    // Paths of every file in the index, relative to the working directory.
+    pub fn tracked_paths(&self) -> Result<Vec<PathBuf>, GitError> {
+        let index = self.repo.index().map_err(|_| GitError::Index)?;
+
+        Ok(index
+               .iter()
+               .filter_map(|e| String::from_utf8(e.path).ok())
+               .map(PathBuf::from)
+               .collect())
     }
 
     pub fn remove_untracked(&self) -> Result<(), GitError> {
@@ -126,12 +1138,470 @@ impl GitRepo {
         Ok(())
     }
 
+    // What `git clean -d` would remove, with `ignored` as its -x. Wholly
+    // untracked directories come back as the directory itself, and nested
+    // repositories are left alone.
+    pub fn untracked(&self, ignored: bool) -> Result<Vec<PathBuf>, GitError> {
+        let mut opts = git2::StatusOptions::new();
+
+        opts.include_ignored(ignored)
+            .include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .recurse_ignored_dirs(false)
+            .exclude_submodules(true);
+
+        let statuses = self.repo
+            .statuses(Some(&mut opts))
+            .map_err(|_| GitError::Status)?;
+
+        let paths = statuses
+            .iter()
+            .filter(|e| e.status().intersects(git2::STATUS_WT_NEW | git2::STATUS_IGNORED))
+            .filter_map(|e| e.path().map(PathBuf::from))
+            .filter(|p| !self.path.join(p).join(".git").exists())
+            .collect();
+
+        Ok(paths)
+    }
+
+    // Removes everything `untracked` lists, returning what went.
+    pub fn clean(&self, ignored: bool) -> Result<Vec<PathBuf>, GitError> {
+        let paths = self.untracked(ignored)?;
+
+        for path in &paths {
+            let full = self.path.join(path);
+
+            match full.is_dir() {
+                true => fs::remove_dir_all(&full),
+                false => fs::remove_file(&full),
+            }.map_err(|_| GitError::RemoveUntracked)?;
+        }
+
+        Ok(paths)
+    }
+
+    // Largest blobs anywhere in the object database, biggest first.
+    pub fn largest_blobs(&self, count: usize) -> Result<Vec<GitBlob>, GitError> {
+        let mut blobs = self.blob_sizes()?;
+
+        blobs.sort_by_key(|&(_, size)| Reverse(size));
+        blobs.truncate(count);
+
+        self.name_blobs(blobs)
+    }
+
+    // Every blob of at least `min_size` bytes, biggest first.
+    pub fn blobs_over(&self, min_size: u64) -> Result<Vec<GitBlob>, GitError> {
+        let mut blobs = self.blob_sizes()?
+            .into_iter()
+            .filter(|&(_, size)| size >= min_size)
+            .collect::<Vec<_>>();
+
+        blobs.sort_by_key(|&(_, size)| Reverse(size));
+
+        self.name_blobs(blobs)
+    }
+
+    // libgit2 can't enumerate objects, so this goes through git itself.
+    fn blob_sizes(&self) -> Result<Vec<(String, u64)>, GitError> {
+        let objects = command::git(&self.path,
+                                   &["cat-file",
+                                     "--batch-all-objects",
+                                     "--batch-check=%(objecttype) %(objectname) %(objectsize)"])
+                .map_err(|_| GitError::Blobs)?;
+
+        Ok(objects
+               .lines()
+               .filter_map(|line| {
+                   let mut parts = line.split(' ');
+
+                   match (parts.next(), parts.next(), parts.next()) {
+                       (Some("blob"), Some(id), Some(size)) => size.parse::<u64>().ok().map(|s| (id.to_string(), s)),
+                       _ => None,
+                   }
+               })
+               .collect())
+    }
+
+    fn name_blobs(&self, blobs: Vec<(String, u64)>) -> Result<Vec<GitBlob>, GitError> {
+        if blobs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Unreachable blobs won't show up here and go without a path.
+        let listing = command::git(&self.path, &["rev-list", "--objects", "--all"])
+            .map_err(|_| GitError::Blobs)?;
+
+        let mut paths = HashMap::new();
+
+        for line in listing.lines() {
+            if let Some(i) = line.find(' ') {
+                paths.entry(&line[..i]).or_insert(&line[i + 1..]);
+            }
+        }
+
+        Ok(blobs
+               .into_iter()
+               .map(|(id, size)| {
+                        let path = paths.get(id.as_str()).map(PathBuf::from);
+                        GitBlob::new(id, path, size)
+                    })
+               .collect())
+    }
+
+    // Initialises and checks out each submodule at the commit the parent
+    // records, returning how many there are or None if there aren't any.
+    // libgit2 can't recurse or reuse the git binary's credentials, so this
+    // goes through git itself.
+    pub fn update_submodules(&self, recursive: bool) -> Result<Option<usize>, GitError> {
+        let count = self.repo.submodules().map_err(|_| GitError::Submodule)?.len();
+
+        if count == 0 {
+            return Ok(None);
+        }
+
+        let mut args = vec!["submodule", "update", "--init"];
+
+        if recursive {
+            args.push("--recursive");
+        }
+
+        command::git(&self.path, &args).map_err(|_| GitError::Submodule)?;
+
+        Ok(Some(count))
+    }
+
+    // Writes HEAD's tree, without any history, in a format git archive
+    // knows, e.g. "tar.gz" or "zip". Every path goes under `prefix`.
+    pub fn archive(&self, output: &Path, format: &str, prefix: &str) -> Result<(), GitError> {
+        let output = output.to_str().ok_or(GitError::Archive)?;
+        let format = format!("--format={}", format);
+        let prefix = format!("--prefix={}/", prefix);
+
+        command::git(&self.path, &["archive", &format, &prefix, "--output", output, "HEAD"])
+            .map_err(|_| GitError::Archive)?;
+
+        Ok(())
+    }
+
+    // Every ref, HEAD included, so cloning the bundle gives back the same
+    // branches with the same one checked out.
+    pub fn bundle(&self, output: &Path) -> Result<(), GitError> {
+        let output = output.to_str().ok_or(GitError::Bundle)?;
+
+        command::git(&self.path, &["bundle", "create", output, "--all"]).map_err(|_| GitError::Bundle)?;
+
+        Ok(())
+    }
+
+    // Corruption makes git exit non-zero, which is still a report; only
+    // failing to run git at all is an error.
+    pub fn fsck(&self) -> Result<GitFsck, GitError> {
+        let output = command::run(&self.path, &["fsck", "--no-progress"]).map_err(|_| GitError::Fsck)?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut fsck = GitFsck::parse(stdout.lines().chain(stderr.lines()));
+
+        if !output.status.success() && fsck.is_healthy() {
+            fsck.add_problem("git fsck failed without saying why");
+        }
+
+        Ok(fsck)
+    }
+
+    // Garbage collection through git itself, as libgit2 has none. Aggressive
+    // mode first expires every reflog entry so the objects only they kept
+    // alive can go too, then repacks from scratch.
+    pub fn gc(&self, aggressive: bool) -> Result<(), GitError> {
+        if aggressive {
+            command::git(&self.path, &["reflog", "expire", "--expire=now", "--all"])
+                .map_err(|_| GitError::Gc)?;
+            command::git(&self.path, &["gc", "--quiet", "--aggressive", "--prune=now"])
+                .map_err(|_| GitError::Gc)?;
+        } else {
+            command::git(&self.path, &["gc", "--quiet"]).map_err(|_| GitError::Gc)?;
+        }
+
+        Ok(())
+    }
+
+    // Needs git-lfs installed. Fetches what the current checkout points at
+    // and smudges it into the working tree.
+    pub fn lfs_pull(&self) -> Result<(), GitError> {
+        command::git(&self.path, &["lfs", "pull"]).map_err(|_| GitError::Lfs)?;
+
+        Ok(())
+    }
+
+    // Searches tracked files in the working tree, skipping binaries. git
+    // grep exits with 1 when nothing matches, which isn't a failure here.
+    pub fn grep(&self, pattern: &str, ignore_case: bool) -> Result<Vec<GitGrepMatch>, GitError> {
+        let mut args = vec!["grep", "--null", "--line-number", "-I", "--no-color"];
+
+        if ignore_case {
+            args.push("--ignore-case");
+        }
+
+        args.extend(&["-e", pattern]);
+
+        let output = command::run(&self.path, &args).map_err(|_| GitError::Grep)?;
+
+        match output.status.code() {
+            Some(0) => (),
+            Some(1) => return Ok(Vec::new()),
+            _ => return Err(GitError::Grep),
+        }
+
+        let matches = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(GitGrepMatch::parse)
+            .collect();
+
+        Ok(matches)
+    }
+
+    // None for a full checkout. Asks git for the patterns because cone mode
+    // stores them rewritten into a form only git reads back sensibly.
+    pub fn sparse_checkout(&self) -> Result<Option<GitSparseCheckout>, GitError> {
+        let config = self.repo.config().map_err(|_| GitError::Config)?;
+
+        if !config.get_bool("core.sparseCheckout").unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let cone = config.get_bool("core.sparseCheckoutCone").unwrap_or(false);
+        let patterns = command::git(&self.path, &["sparse-checkout", "list"]).map_err(|_| GitError::Sparse)?
+            .lines()
+            .map(String::from)
+            .collect();
+
+        Ok(Some(GitSparseCheckout::new(cone, patterns)))
+    }
+
+    // Turns sparse checkout on if needed and updates the working tree to
+    // match, which fails rather than dropping files with local changes.
+    pub fn set_sparse_checkout(&self, patterns: &[String], cone: bool) -> Result<(), GitError> {
+        let mut args = vec!["sparse-checkout", "set"];
+        args.push(match cone {
+                      true => "--cone",
+                      false => "--no-cone",
+                  });
+        args.push("--");
+        args.extend(patterns.iter().map(String::as_str));
+
+        command::git(&self.path, &args).map_err(|_| GitError::Sparse)?;
+
+        Ok(())
+    }
+
     pub fn state(&self) -> RepoState {
         RepoState::from(self.repo.state())
     }
+
+    // The user.name and user.email commits here would be made with, after
+    // every config level has been applied.
+    pub fn identity(&self) -> Result<(Option<String>, Option<String>), GitError> {
+        let config = self.repo.config().map_err(|_| GitError::Config)?;
+
+        Ok((config.get_string("user.name").ok(), config.get_string("user.email").ok()))
+    }
+
+    // The value after every config level has been applied, and whether it
+    // came from the repo's own config rather than being inherited.
+    pub fn get_config(&self, key: &str) -> Result<Option<(String, bool)>, GitError> {
+        let config = self.repo.config().map_err(|_| GitError::Config)?;
+
+        let entry = match config.get_entry(key) {
+            Ok(e) => e,
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(_) => return Err(GitError::Config),
+        };
+
+        let value = entry.value().ok_or(GitError::Config)?.to_string();
+
+        Ok(Some((value, entry.level() == git2::ConfigLevel::Local)))
+    }
+
+    // Written to the repo's own config, leaving global settings alone.
+    pub fn set_config(&self, key: &str, value: &str) -> Result<(), GitError> {
+        self.repo
+            .config()
+            .and_then(|c| c.open_level(git2::ConfigLevel::Local))
+            .and_then(|mut c| c.set_str(key, value))
+            .map_err(|_| GitError::Config)
+    }
+
+    // Where git looks for hooks, and whether core.hooksPath moved it there.
+    pub fn hooks_dir(&self) -> (PathBuf, bool) {
+        let configured = self.repo
+            .config()
+            .and_then(|c| c.get_path("core.hooksPath"))
+            .ok();
+
+        match configured {
+            // Relative paths are taken from the top of the working tree.
+            Some(p) => (self.path.join(p), true),
+            None => (self.git_dir().join("hooks"), false),
+        }
+    }
+
+    pub fn core_config(&self) -> GitCoreConfig {
+        // An unreadable config behaves like an empty one, same as git.
+        match self.repo.config() {
+            Ok(c) => GitCoreConfig::new(&c),
+            Err(_) => GitCoreConfig::new(&git2::Config::new().expect("Could not create empty config")),
+        }
+    }
+
+    fn unstaged(&self, pathspecs: &[&str]) -> Result<usize, GitError> {
+        let mut opts = git2::StatusOptions::new();
+
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .exclude_submodules(true);
+
+        for pathspec in pathspecs {
+            opts.pathspec(pathspec);
+        }
+
+        let statuses = self.repo
+            .statuses(Some(&mut opts))
+            .map_err(|_| GitError::Status)?;
+
+        let unstaged = git2::STATUS_WT_NEW | git2::STATUS_WT_MODIFIED | git2::STATUS_WT_DELETED |
+                       git2::STATUS_WT_TYPECHANGE | git2::STATUS_WT_RENAMED;
+
+        Ok(statuses.iter().filter(|e| e.status().intersects(unstaged)).count())
+    }
+
+    fn has_tracked_changes(&self) -> Result<bool, GitError> {
+        Ok(self.tracked_changes()? > 0)
+    }
+
+    // Files with staged or unstaged changes, leaving untracked ones out.
+    fn tracked_changes(&self) -> Result<usize, GitError> {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(false).exclude_submodules(true);
+
+        let statuses = self.repo
+            .statuses(Some(&mut opts))
+            .map_err(|_| GitError::Status)?;
+
+        Ok(statuses.len())
+    }
+
+    fn upstream_counts(&self, branch: &git2::Branch) -> Result<Option<(usize, usize)>, GitError> {
+        let local = branch.get().target().ok_or(GitError::Upstream)?;
+
+        let upstream = match branch.upstream() {
+            Ok(u) => u.get().target().ok_or(GitError::Upstream)?,
+            Err(_) => return Ok(None),
+        };
+
+        let counts = self.repo
+            .graph_ahead_behind(local, upstream)
+            .map_err(|_| GitError::Upstream)?;
+
+        Ok(Some(counts))
+    }
+
+    fn fast_forward(&self, branch: git2::Branch, reflog: &str) -> Result<(), GitError> {
+        let target = branch
+            .upstream()
+            .ok()
+            .and_then(|u| u.get().target())
+            .ok_or(GitError::Upstream)?;
+
+        let commit = self.repo.find_commit(target).map_err(|_| GitError::Upstream)?;
+
+        // A safe checkout refuses to overwrite local changes, rather than
+        // losing them the way a reset would.
+        let (mut builder, long_path) = self.checkout_builder();
+        builder.safe();
+
+        self.repo
+            .checkout_tree(commit.as_object(), Some(&mut builder))
+            .map_err(|_| long_path.or(GitError::Checkout(GitBranch::Local)))?;
+
+        branch
+            .into_reference()
+            .set_target(target, reflog)
+            .map_err(|_| GitError::Checkout(GitBranch::Local))?;
+
+        Ok(())
+    }
+
+    // libgit2 can merge, but leaves writing the commit and message to us;
+    // git already does both the way users expect.
+    fn merge_commit(&self, behind: usize) -> Result<MergeResult, GitError> {
+        let output = command::run(&self.path, &["merge", "--no-ff", "--no-edit", "@{upstream}"])
+            .map_err(|_| GitError::Merge)?;
+
+        if output.status.success() {
+            return Ok(MergeResult::Merged(behind));
+        }
+
+        // Refusing to start, e.g. over local changes, leaves nothing to abort.
+        if self.state() == RepoState::Clean {
+            return Err(GitError::Merge);
+        }
+
+        command::git(&self.path, &["merge", "--abort"]).map_err(|_| GitError::Merge)?;
+
+        Ok(MergeResult::Conflicted)
+    }
+
+    // libgit2 has no rebase porcelain worth the name here, so lean on git,
+    // and put things back the way they were if it stops on a conflict.
+    fn rebase_onto_upstream(&self, autostash: bool, ahead: usize, behind: usize) -> Result<PullResult, GitError> {
+        const AUTOSTASH_CONFLICT: &str = "Applying autostash resulted in conflicts";
+
+        let mut args = vec!["rebase"];
+
+        if autostash {
+            args.push("--autostash");
+        }
+
+        args.push("@{upstream}");
+
+        let output = command::run(&self.path, &args).map_err(|_| GitError::Pull)?;
+
+        if output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+
+            return Ok(match ahead {
+                          _ if stderr.contains(AUTOSTASH_CONFLICT) => PullResult::AutostashConflicted(ahead),
+                          0 => PullResult::FastForwarded(behind),
+                          n => PullResult::Rebased(n),
+                      });
+        }
+
+        // Refusing to start, e.g. over a dirty tree, leaves nothing to abort.
+        if self.state() == RepoState::Clean {
+            return Err(GitError::Pull);
+        }
+
+        command::git(&self.path, &["rebase", "--abort"]).map_err(|_| GitError::Pull)?;
+
+        Ok(PullResult::Conflicted)
+    }
+
+    fn checkout_builder(&self) -> (git2::build::CheckoutBuilder<'static>, LongPath) {
+        // Account for the separator between the working directory and entry paths.
+        let workdir_len = self.path.as_os_str().len() + 1;
+        self.core_config().checkout_builder(workdir_len)
+    }
+}
+
+fn branch_type(branch_name: &str) -> git2::BranchType {
+    match branch_name.find("origin/") {
+        Some(_) => git2::BranchType::Remote,
+        None => git2::BranchType::Local,
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RepoState {
     Clean,
     Merge,
@@ -0,0 +1,24 @@
+// The patterns a sparse checkout was set up with. In cone mode they're
+// directories rather than gitignore-style patterns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitSparseCheckout {
+    cone: bool,
+    patterns: Vec<String>,
+}
+
+impl GitSparseCheckout {
+    pub fn new(cone: bool, patterns: Vec<String>) -> Self {
+        Self {
+            cone: cone,
+            patterns: patterns,
+        }
+    }
+
+    pub fn cone(&self) -> bool {
+        self.cone
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+}
@@ -0,0 +1,30 @@
+pub struct GitStash {
+    index: usize,
+    message: String,
+}
+
+impl GitStash {
+    pub fn new(index: usize, message: &str) -> Self {
+        Self {
+            index: index,
+            message: String::from(message),
+        }
+    }
+
+    // 0 is the most recent, as in stash@{0}.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    // As git records it, e.g. "On master: <message>".
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StashPop {
+    Popped,
+    // The changes clash with the working tree; the stash is kept.
+    Conflicted,
+}
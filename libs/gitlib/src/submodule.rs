@@ -0,0 +1,46 @@
+use git2;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubmoduleState {
+    Clean,
+    // Tracked changes or untracked files inside the submodule.
+    Modified,
+    // Checked out at a different commit than the one the parent records.
+    OutOfSync,
+    Uninitialized,
+}
+
+pub struct GitSubmodule {
+    path: PathBuf,
+    state: SubmoduleState,
+}
+
+impl GitSubmodule {
+    pub fn new(submodule: &git2::Submodule, status: git2::SubmoduleStatus) -> Self {
+        let state = if status.contains(git2::SUBMODULE_STATUS_WD_UNINITIALIZED) {
+            SubmoduleState::Uninitialized
+        } else if status.contains(git2::SUBMODULE_STATUS_WD_MODIFIED) {
+            SubmoduleState::OutOfSync
+        } else if status.intersects(git2::SUBMODULE_STATUS_WD_INDEX_MODIFIED |
+                                    git2::SUBMODULE_STATUS_WD_WD_MODIFIED |
+                                    git2::SUBMODULE_STATUS_WD_UNTRACKED) {
+            SubmoduleState::Modified
+        } else {
+            SubmoduleState::Clean
+        };
+
+        Self {
+            path: submodule.path().to_path_buf(),
+            state: state,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn state(&self) -> SubmoduleState {
+        self.state
+    }
+}
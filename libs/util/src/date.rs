@@ -0,0 +1,153 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+
+// e.g. "2017-08-03", in the timezone the time was recorded in.
+pub fn format_date(seconds: i64, offset_minutes: i32) -> String {
+    let days = (seconds + i64::from(offset_minutes) * MINUTE).div_euclid(DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// e.g. "2017-08-03 14:05", in the timezone the time was recorded in.
+pub fn format_time(seconds: i64, offset_minutes: i32) -> String {
+    let local = seconds + i64::from(offset_minutes) * MINUTE;
+    let minutes = local.rem_euclid(DAY) / MINUTE;
+
+    format!("{} {:02}:{:02}", format_date(seconds, offset_minutes), minutes / 60, minutes % 60)
+}
+
+// e.g. "3 weeks ago"
+pub fn format_age(seconds: i64) -> String {
+    let age = now() - seconds;
+
+    let (n, unit) = match age {
+        a if a < MINUTE => return String::from("just now"),
+        a if a < HOUR => (a / MINUTE, "minute"),
+        a if a < DAY => (a / HOUR, "hour"),
+        a if a < 7 * DAY => (a / DAY, "day"),
+        a if a < 30 * DAY => (a / (7 * DAY), "week"),
+        a if a < 365 * DAY => (a / (30 * DAY), "month"),
+        a => (a / (365 * DAY), "year"),
+    };
+
+    match n {
+        1 => format!("1 {} ago", unit),
+        _ => format!("{} {}s ago", n, unit),
+    }
+}
+
+// Accepts "2017-08-03" (midnight UTC), "today", "yesterday", or an age like
+// "3d", "2w", "6m" or "1y".
+pub fn parse_date(text: &str) -> Option<i64> {
+    let text = text.trim();
+
+    match text {
+        "today" => return Some(now().div_euclid(DAY) * DAY),
+        "yesterday" => return Some((now().div_euclid(DAY) - 1) * DAY),
+        _ => (),
+    }
+
+    let mut parts = text.splitn(3, '-');
+
+    if let (Some(y), Some(m), Some(d)) = (parts.next(), parts.next(), parts.next()) {
+        let (year, month, day) = (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?);
+
+        if !(1..=9999).contains(&year) || !(1..=12).contains(&month) {
+            return None;
+        }
+
+        if !(1..=days_in_month(year, month)).contains(&day) {
+            return None;
+        }
+
+        return Some(days_from_civil(year, month, day) * DAY);
+    }
+
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let n = text[..split].parse::<i64>().ok()?;
+
+    let unit = match text[split..].trim() {
+        "d" | "day" | "days" => DAY,
+        "w" | "week" | "weeks" => 7 * DAY,
+        "m" | "month" | "months" => 30 * DAY,
+        "y" | "year" | "years" => 365 * DAY,
+        _ => return None,
+    };
+
+    // Validators run this on whatever was typed, so a huge count mustn't overflow.
+    n.checked_mul(unit).and_then(|s| now().checked_sub(s))
+}
+
+pub fn now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(_) => 0,
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Howard Hinnant's days-to-civil algorithm, days counted from 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = i64::from(if month > 2 { month - 3 } else { month + 9 });
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_date;
+
+    #[test]
+    fn epoch() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+    }
+
+    #[test]
+    fn month_ends() {
+        assert_eq!(parse_date("2024-12-31"), Some(1_735_603_200));
+        assert_eq!(parse_date("2024-04-31"), None);
+        assert_eq!(parse_date("2024-02-31"), None);
+    }
+
+    // Every fourth year, except centuries not divisible by 400.
+    #[test]
+    fn leap_days() {
+        assert_eq!(parse_date("2024-02-29"), Some(1_709_164_800));
+        assert_eq!(parse_date("2000-02-29"), Some(951_782_400));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("1900-02-29"), None);
+    }
+}
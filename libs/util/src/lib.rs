@@ -2,17 +2,37 @@
 extern crate serde_derive;
 extern crate serde;
 extern crate serde_json;
+extern crate serde_yaml;
+extern crate toml;
 extern crate gitlib;
 
+mod date;
+pub use date::{format_age, format_date, format_time, now, parse_date};
+
+mod digest;
+pub use digest::sha256;
+
 mod manifest;
-pub use manifest::{Manifest, ManifestError};
+pub use manifest::{Manifest, ManifestError, ManifestMerge, OriginConflict, WORKSPACE_MANIFEST};
+
+mod manifest_format;
+pub use manifest_format::{ManifestFormat, FORMATS as MANIFEST_FORMATS};
 
 mod manifest_iter;
 pub use manifest_iter::ManifestIterator;
 
+mod pattern;
+pub use pattern::{glob_match, path_glob_match};
+
 mod repo_iter;
 pub use repo_iter::GitRepositories;
 
+mod size;
+pub use size::{dir_size, format_bytes, worktree_size};
+
+mod status_cache;
+pub use status_cache::StatusCache;
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -1,17 +1,33 @@
-use serde_json;
 use gitlib::GitRepo;
+use manifest_format::ManifestFormat;
 use manifest_iter::ManifestIterator;
 use repo_iter::GitRepositories;
 
-use std::path::{PathBuf, Path};
-use std::fs::{File, DirBuilder};
-use std::io::Write;
-use std::collections::BTreeSet;
+use std::ffi::OsStr;
+use std::path::{Component, PathBuf, Path};
+use std::fs::{self, File, DirBuilder};
+use std::io::{self, Write};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Kept at the top of a workspace, e.g. checked into its meta-repo. It's
+// written without a root, since that's wherever the file is.
+pub const WORKSPACE_MANIFEST: &str = "gitplz.manifest";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ManifestData {
     root_path: PathBuf,
     repositories: BTreeSet<PathBuf>,
+    // Where each repo's origin fetches from, so the workspace can be cloned
+    // again elsewhere. Older manifests don't have these.
+    #[serde(default)]
+    origins: BTreeMap<PathBuf, String>,
+    // Globs like "**/node_modules/**", relative to the root, for repos that
+    // should never be picked up.
+    #[serde(default)]
+    excludes: BTreeSet<String>,
+    // Names like "backend" given to entries, so commands can run on a subset.
+    #[serde(default)]
+    groups: BTreeMap<PathBuf, BTreeSet<String>>,
 }
 
 impl ManifestData {
@@ -19,6 +35,9 @@ impl ManifestData {
         Self {
             repositories: BTreeSet::new(),
             root_path: path.to_path_buf(),
+            origins: BTreeMap::new(),
+            excludes: BTreeSet::new(),
+            groups: BTreeMap::new(),
         }
     }
 
@@ -34,9 +53,45 @@ impl ManifestData {
             }
         };
         let path = PathBuf::from(path_strip.to_str().unwrap());
+
+        match repo.remote_url("origin") {
+            Ok(Some(url)) => self.origins.insert(path.clone(), url),
+            _ => self.origins.remove(&path),
+        };
+
         self.repositories.insert(path);
     }
 
+    fn remove(&mut self, path: &Path) -> bool {
+        match path.strip_prefix(&self.root_path) {
+            Ok(p) => {
+                self.origins.remove(p);
+                self.groups.remove(p);
+                self.repositories.remove(p)
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn from_portable(portable: PortableData, root: &Path) -> Self {
+        Self {
+            root_path: root.to_path_buf(),
+            repositories: portable.repositories,
+            origins: portable.origins,
+            excludes: portable.excludes,
+            groups: portable.groups,
+        }
+    }
+
+    fn portable(&self) -> PortableData {
+        PortableData {
+            repositories: self.repositories.clone(),
+            origins: self.origins.clone(),
+            excludes: self.excludes.clone(),
+            groups: self.groups.clone(),
+        }
+    }
+
     pub fn root(&self) -> &Path {
         &self.root_path
     }
@@ -44,18 +99,64 @@ impl ManifestData {
     pub fn repos(&self) -> &BTreeSet<PathBuf> {
         &self.repositories
     }
+
+    pub fn origin(&self, path: &Path) -> Option<&str> {
+        self.origins.get(path).map(String::as_str)
+    }
+
+    pub fn excludes(&self) -> &BTreeSet<String> {
+        &self.excludes
+    }
+
+    pub fn groups(&self, path: &Path) -> Option<&BTreeSet<String>> {
+        self.groups.get(path)
+    }
+}
+
+// What a teammate needs to lay out the same workspace: everything but the
+// root, which is wherever they import it.
+#[derive(Serialize, Deserialize, Debug)]
+struct PortableData {
+    repositories: BTreeSet<PathBuf>,
+    #[serde(default)]
+    origins: BTreeMap<PathBuf, String>,
+    #[serde(default)]
+    excludes: BTreeSet<String>,
+    #[serde(default)]
+    groups: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+#[derive(Debug)]
+pub struct ManifestMerge {
+    // Entries that weren't already listed.
+    pub added: usize,
+    pub conflicts: Vec<OriginConflict>,
+}
+
+#[derive(Debug)]
+pub struct OriginConflict {
+    pub entry: PathBuf,
+    pub ours: String,
+    pub theirs: String,
 }
 
 #[derive(Debug)]
 pub enum ManifestError {
     BuildPath,
+    Invalid,
+    OutsideRoot,
     PathNotFound,
+    Unreadable,
+    Unwritable,
 }
 
 #[derive(Debug)]
 pub struct Manifest<'a> {
     path: &'a Path,
     data: ManifestData,
+    // Saved without the root, as an export is.
+    portable: bool,
+    format: ManifestFormat,
 }
 
 impl<'a> Manifest<'a> {
@@ -64,20 +165,117 @@ impl<'a> Manifest<'a> {
               Q: AsRef<Path>
     {
         let manifest_path_ref = manifest_path.as_ref();
+        let portable = is_workspace_manifest(manifest_path_ref);
+        let format = ManifestFormat::from_path(manifest_path_ref);
 
         let manifest_data = {
-            let root_ref = root.as_ref();
+            // A workspace manifest's root is always the directory it's in.
+            let root_ref = match portable {
+                true => manifest_path_ref.parent().unwrap_or(root.as_ref()),
+                false => root.as_ref(),
+            };
 
-            match File::open(manifest_path_ref) {
-                Ok(f) => serde_json::from_reader(&f).unwrap_or(ManifestData::empty(root_ref)),
-                Err(_) => ManifestData::empty(root_ref),
+            match (fs::read_to_string(manifest_path_ref), portable) {
+                (Ok(text), true) => {
+                    format.deserialize(&text)
+                        .map(|p| ManifestData::from_portable(p, root_ref))
+                        .unwrap_or(ManifestData::empty(root_ref))
+                }
+                (Ok(text), false) => format.deserialize(&text).unwrap_or(ManifestData::empty(root_ref)),
+                (Err(_), _) => ManifestData::empty(root_ref),
             }
         };
 
         Self {
             data: manifest_data,
             path: manifest_path_ref,
+            portable: portable,
+            format: format,
+        }
+    }
+
+    // Unlike open, which quietly starts over from an empty manifest, says why
+    // the file on disk can't be used. Returns how many repos it lists.
+    pub fn validate<P: AsRef<Path>>(manifest_path: P) -> Result<usize, ManifestError> {
+        let text = match fs::read_to_string(manifest_path.as_ref()) {
+            Ok(t) => t,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Err(ManifestError::PathNotFound),
+            Err(_) => return Err(ManifestError::Unreadable),
+        };
+        let format = ManifestFormat::from_path(manifest_path.as_ref());
+
+        match is_workspace_manifest(manifest_path.as_ref()) {
+            true => format.deserialize::<PortableData>(&text).map(|data| data.repositories.len()),
+            false => format.deserialize::<ManifestData>(&text).map(|data| data.repos().len()),
         }
+        .map_err(|_| ManifestError::Invalid)
+    }
+
+    pub fn export<P: AsRef<Path>>(&self, path: P, format: ManifestFormat) -> Result<(), ManifestError> {
+        let ser_data = format.serialize(&self.data.portable()).unwrap();
+        fs::write(path, ser_data).map_err(|_| ManifestError::Unwritable)
+    }
+
+    // Unions in the entries of an exported manifest, or of another machine's
+    // manifest, under this manifest's root. Entries are relative either way,
+    // so the other root doesn't matter. Where both record an origin for an
+    // entry and they differ, ours is kept and the conflict reported.
+    pub fn merge<P: AsRef<Path>>(&mut self, path: P) -> Result<ManifestMerge, ManifestError> {
+        let text = match fs::read_to_string(path.as_ref()) {
+            Ok(t) => t,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Err(ManifestError::PathNotFound),
+            Err(_) => return Err(ManifestError::Unreadable),
+        };
+
+        let format = ManifestFormat::from_path(path.as_ref());
+        let other = format.deserialize::<ManifestData>(&text)
+            .map(|data| data.portable())
+            .or_else(|_| format.deserialize::<PortableData>(&text))
+            .map_err(|_| ManifestError::Invalid)?;
+
+        // Restore clones to root.join(entry), so nothing may point outside it.
+        let within_root = other.repositories
+            .iter()
+            .chain(other.origins.keys())
+            .chain(other.groups.keys())
+            .all(|e| is_within_root(e));
+
+        if !within_root {
+            return Err(ManifestError::OutsideRoot);
+        }
+
+        let before = self.data.repositories.len();
+        let mut conflicts = Vec::new();
+
+        self.data.repositories.extend(other.repositories);
+        self.data.excludes.extend(other.excludes);
+
+        for (entry, theirs) in other.origins {
+            match self.data.origins.get(&entry) {
+                Some(ours) if *ours != theirs => {
+                    conflicts.push(OriginConflict {
+                                       entry: entry,
+                                       ours: ours.clone(),
+                                       theirs: theirs,
+                                   })
+                }
+                Some(_) => (),
+                None => {
+                    self.data.origins.insert(entry, theirs);
+                }
+            }
+        }
+
+        for (entry, groups) in other.groups {
+            self.data.groups.entry(entry).or_default().extend(groups);
+        }
+
+        self.save();
+
+        Ok(ManifestMerge {
+               added: self.data.repositories.len() - before,
+               conflicts: conflicts,
+           })
     }
 
     pub fn add_repositories(&mut self, repos: GitRepositories) {
@@ -85,10 +283,136 @@ impl<'a> Manifest<'a> {
             self.data.add(&repo);
         }
 
-        let ser_data = serde_json::to_string_pretty(&self.data).unwrap();
-        let mut file = self.get_file();
+        self.save();
+    }
+
+    pub fn add_repository(&mut self, repo: &GitRepo) {
+        self.data.add(repo);
+        self.save();
+    }
+
+    // Repoints the entry for a repo that was moved from `from`. Returns false,
+    // leaving the manifest alone, if `from` wasn't listed.
+    pub fn move_repository(&mut self, from: &Path, repo: &GitRepo) -> bool {
+        let groups = from.strip_prefix(&self.data.root_path)
+            .ok()
+            .and_then(|p| self.data.groups.get(p).cloned());
+
+        if !self.data.remove(from) {
+            return false;
+        }
+
+        self.data.add(repo);
+
+        if let (Some(groups), Ok(to)) = (groups, repo.path().strip_prefix(&self.data.root_path)) {
+            self.data.groups.insert(to.to_path_buf(), groups);
+        }
+
+        self.save();
+        true
+    }
+
+    // False if the glob was already there.
+    pub fn add_exclude(&mut self, glob: &str) -> bool {
+        if !self.data.excludes.insert(glob.to_string()) {
+            return false;
+        }
+
+        self.save();
+        true
+    }
+
+    // False if the glob wasn't there to remove.
+    pub fn remove_exclude(&mut self, glob: &str) -> bool {
+        if !self.data.excludes.remove(glob) {
+            return false;
+        }
+
+        self.save();
+        true
+    }
+
+    // False if the entry, relative to the root, was already in the group.
+    // Tagging something that isn't listed is up to the caller to refuse.
+    pub fn add_to_group(&mut self, entry: &Path, group: &str) -> bool {
+        if !self.data.groups.entry(entry.to_path_buf()).or_default().insert(group.to_string()) {
+            return false;
+        }
+
+        self.save();
+        true
+    }
+
+    // False if the entry wasn't in the group.
+    pub fn remove_from_group(&mut self, entry: &Path, group: &str) -> bool {
+        let groups = match self.data.groups.get_mut(entry) {
+            Some(g) => g,
+            None => return false,
+        };
+
+        if !groups.remove(group) {
+            return false;
+        }
 
-        match write!(file, "{}", ser_data) {
+        if groups.is_empty() {
+            self.data.groups.remove(entry);
+        }
+
+        self.save();
+        true
+    }
+
+    // Entries, relative to the root, that are no longer a repository on disk.
+    pub fn stale_entries(&self) -> Vec<PathBuf> {
+        self.data
+            .repos()
+            .iter()
+            .filter(|entry| GitRepo::new(self.data.root_path.join(entry)).is_err())
+            .cloned()
+            .collect()
+    }
+
+    // Drops the stale entries and returns them.
+    pub fn prune(&mut self) -> Vec<PathBuf> {
+        let stale = self.stale_entries();
+
+        for entry in &stale {
+            let path = self.data.root_path.join(entry);
+            self.data.remove(&path);
+        }
+
+        if !stale.is_empty() {
+            self.save();
+        }
+
+        stale
+    }
+
+    pub fn remove_repository(&mut self, path: &Path) -> bool {
+        if !self.data.remove(path) {
+            return false;
+        }
+
+        self.save();
+        true
+    }
+
+    // Written to a temporary file first, so an interrupted save leaves the
+    // previous manifest in place rather than a truncated one.
+    fn save(&self) {
+        let ser_data = match self.portable {
+            true => self.format.serialize(&self.data.portable()),
+            false => self.format.serialize(&self.data),
+        }
+        .unwrap();
+        let temp_path = self.path.with_extension(format!("{}.tmp", self.format.name()));
+        let mut file = self.get_file(&temp_path);
+
+        let result = write!(file, "{}", ser_data)
+            .and_then(|_| file.sync_all())
+            .and_then(|_| fs::rename(&temp_path, self.path));
+
+        match result {
             Ok(_) => (),
             Err(e) => println!("{:#?}", e),
         }
@@ -98,6 +422,38 @@ impl<'a> Manifest<'a> {
         ManifestIterator::new(&self.data)
     }
 
+    pub fn root(&self) -> &Path {
+        self.data.root()
+    }
+
+    pub fn entries(&self) -> &BTreeSet<PathBuf> {
+        self.data.repos()
+    }
+
+    // The origin URL recorded for an entry, relative to the root.
+    pub fn origin<P: AsRef<Path>>(&self, entry: P) -> Option<&str> {
+        self.data.origin(entry.as_ref())
+    }
+
+    pub fn excludes(&self) -> &BTreeSet<String> {
+        self.data.excludes()
+    }
+
+    // The groups an entry, relative to the root, has been tagged with.
+    pub fn groups<P: AsRef<Path>>(&self, entry: P) -> Option<&BTreeSet<String>> {
+        self.data.groups(entry.as_ref())
+    }
+
+    // Full paths of the entries tagged with the group.
+    pub fn group_members(&self, group: &str) -> Vec<PathBuf> {
+        self.data
+            .groups
+            .iter()
+            .filter(|&(_, groups)| groups.contains(group))
+            .map(|(entry, _)| self.data.root_path.join(entry))
+            .collect()
+    }
+
     pub fn path_in_manifest<P: AsRef<Path>>(&self, path: P) -> bool {
         path.as_ref().starts_with(&self.data.root_path)
     }
@@ -110,7 +466,7 @@ impl<'a> Manifest<'a> {
         self.data.repos().len()
     }
 
-    fn get_file(&self) -> File {
+    fn get_file(&self, path: &Path) -> File {
         let manifest_dir = self.path.parent().unwrap();
 
         if !manifest_dir.exists() {
@@ -120,6 +476,16 @@ impl<'a> Manifest<'a> {
                 .unwrap();
         }
 
-        File::create(&self.path).unwrap()
+        File::create(path).unwrap()
     }
 }
+
+fn is_workspace_manifest(path: &Path) -> bool {
+    path.file_name() == Some(OsStr::new(WORKSPACE_MANIFEST))
+}
+
+// Relative and without "..", so joining it onto the root stays inside.
+fn is_within_root(entry: &Path) -> bool {
+    entry.components().next().is_some() &&
+    entry.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
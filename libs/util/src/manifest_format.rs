@@ -0,0 +1,72 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+use serde_yaml;
+use toml;
+
+use std::ffi::OsStr;
+use std::path::Path;
+
+// JSON unless the file says otherwise, so existing manifests and the
+// extensionless gitplz.manifest keep reading as they always have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+pub const FORMATS: &[&str] = &["json", "toml", "yaml"];
+
+impl ManifestFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("toml") => ManifestFormat::Toml,
+            Some("yaml") | Some("yml") => ManifestFormat::Yaml,
+            _ => ManifestFormat::Json,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(ManifestFormat::Json),
+            "toml" => Some(ManifestFormat::Toml),
+            "yaml" => Some(ManifestFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ManifestFormat::Json => "json",
+            ManifestFormat::Toml => "toml",
+            ManifestFormat::Yaml => "yaml",
+        }
+    }
+
+    pub(crate) fn serialize<T: Serialize>(&self, value: &T) -> Result<String, String> {
+        match *self {
+            ManifestFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            ManifestFormat::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+            ManifestFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub(crate) fn deserialize<T: DeserializeOwned>(&self, text: &str) -> Result<T, String> {
+        match *self {
+            ManifestFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            ManifestFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+            ManifestFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+
+    // Where the text stops being JSON, TOML or YAML at all, as opposed to
+    // being well formed but not a manifest.
+    pub fn syntax_error(&self, text: &str) -> Option<String> {
+        match *self {
+            ManifestFormat::Json => self.deserialize::<serde_json::Value>(text).err(),
+            ManifestFormat::Toml => self.deserialize::<toml::Value>(text).err(),
+            ManifestFormat::Yaml => self.deserialize::<serde_yaml::Value>(text).err(),
+        }
+    }
+}
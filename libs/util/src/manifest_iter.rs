@@ -24,12 +24,21 @@ impl<'a> ManifestIterator<'a> {
 impl<'a> Iterator for ManifestIterator<'a> {
     type Item = GitRepo;
 
+    // Entries that aren't on disk yet, say after a merge or a failed restore,
+    // are skipped rather than taking the whole run down.
     fn next(&mut self) -> Option<Self::Item> {
-        match self.iter.next().map(|x| self.root.join(x)) {
-            Some(p) => {
-                Some(GitRepo::new(p).expect("Failed unwrapping GitRepo in ManifestIterator"))
+        for entry in self.iter.by_ref() {
+            let path = self.root.join(entry);
+
+            match GitRepo::new(&path) {
+                Ok(repo) => return Some(repo),
+                Err(_) => {
+                    eprintln!("Skipping {}: listed in the manifest but not a repository; see manifest restore or prune",
+                              path.display())
+                }
             }
-            None => None,
         }
+
+        None
     }
 }
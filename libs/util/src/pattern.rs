@@ -0,0 +1,40 @@
+use std::path::Path;
+
+// Shell-style matching where `*` stands for any run of characters and
+// everything else is literal, e.g. "*@example.com".
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+
+    // Without a star, split yields the whole pattern once.
+    let first = parts.next().unwrap_or("");
+
+    if !text.starts_with(first) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..];
+    let mut parts = parts.collect::<Vec<_>>();
+
+    let last = match parts.pop() {
+        Some(l) => l,
+        None => return rest.is_empty(),
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+// For globs over paths like "**/vendor/**". glob_match's `*` already crosses
+// directories, so the extra slashes let a leading or trailing "**/" also
+// match nothing, the way .gitignore does.
+pub fn path_glob_match(pattern: &str, path: &Path) -> bool {
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    glob_match(pattern, &path) || glob_match(pattern, &format!("/{}/", path))
+}
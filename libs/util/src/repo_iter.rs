@@ -1,7 +1,9 @@
 use gitlib::{GitRepo, GitError};
 use Manifest;
 use ManifestIterator;
+use pattern::path_glob_match;
 
+use std::collections::{HashSet, VecDeque};
 use std::fs::ReadDir;
 use std::path::{Path, PathBuf};
 
@@ -108,8 +110,25 @@ enum RepoMode<'a> {
     Manifest(ManifestMode<'a>),
 }
 
-pub struct GitRepositories<'a> {
+// Where repos come from, and what they're relative to for filtering.
+struct Source<'a> {
     mode: RepoMode<'a>,
+    root: PathBuf,
+    excludes: Vec<String>,
+}
+
+impl<'a> Source<'a> {
+    fn is_excluded(&self, repo: &GitRepo) -> bool {
+        let relative = repo.path().strip_prefix(&self.root).unwrap_or(repo.path());
+        self.excludes.iter().any(|x| path_glob_match(x, relative))
+    }
+}
+
+pub struct GitRepositories<'a> {
+    sources: VecDeque<Source<'a>>,
+    only: Vec<PathBuf>,
+    members: Option<HashSet<PathBuf>>,
+    seen: HashSet<PathBuf>,
 }
 
 impl<'a> GitRepositories<'a> {
@@ -122,13 +141,104 @@ impl<'a> GitRepositories<'a> {
             pending: vec![path_ref.to_owned()],
         };
 
-        Self { mode: RepoMode::Exploratory(exp) }
+        Self::from_source(Source {
+                              mode: RepoMode::Exploratory(exp),
+                              root: path_ref.to_owned(),
+                              excludes: Vec::new(),
+                          })
     }
 
+    // Entries matching the manifest's own excludes are skipped, even if they
+    // were added before the exclude was.
     pub fn from_manifest(manifest: &'a Manifest) -> Self {
         let man = ManifestMode { iter: manifest.repos() };
 
-        Self { mode: RepoMode::Manifest(man) }
+        Self::from_source(Source {
+                              mode: RepoMode::Manifest(man),
+                              root: manifest.root().to_owned(),
+                              excludes: manifest.excludes().iter().cloned().collect(),
+                          })
+    }
+
+    fn from_source(source: Source<'a>) -> Self {
+        Self {
+            sources: Some(source).into_iter().collect(),
+            only: Vec::new(),
+            members: None,
+            seen: HashSet::new(),
+        }
+    }
+
+    // Appends another set of repos, e.g. from a second root. Repos reachable
+    // from both are only yielded once.
+    pub fn chain(mut self, other: GitRepositories<'a>) -> Self {
+        self.sources.extend(other.sources);
+        self.only.extend(other.only);
+
+        if let Some(members) = other.members {
+            self.members.get_or_insert_with(HashSet::new).extend(members);
+        }
+
+        self
+    }
+
+    // Restricts iteration to repos matching one of the given paths, either
+    // relative to the root or by directory name. An empty list keeps everything.
+    pub fn only<I>(mut self, repos: I) -> Self
+        where I: IntoIterator,
+              I::Item: Into<PathBuf>
+    {
+        self.only.extend(repos.into_iter().map(Into::into));
+        self
+    }
+
+    // Restricts iteration to repos the manifest has tagged with any of the
+    // groups. No groups keeps everything.
+    pub fn in_groups<'b, I>(mut self, manifest: &Manifest, groups: I) -> Self
+        where I: IntoIterator<Item = &'b String>
+    {
+        let mut groups = groups.into_iter().peekable();
+
+        if groups.peek().is_some() {
+            let members = self.members.get_or_insert_with(HashSet::new);
+            members.extend(groups.flat_map(|g| manifest.group_members(g)));
+        }
+
+        self
+    }
+
+    // Skips repos whose path relative to their root matches one of the globs.
+    // Only applies to the sources added so far, since the globs are relative
+    // to those roots.
+    pub fn exclude<'b, I>(mut self, globs: I) -> Self
+        where I: IntoIterator<Item = &'b String>
+    {
+        let globs = globs.into_iter().cloned().collect::<Vec<_>>();
+
+        for source in &mut self.sources {
+            source.excludes.extend(globs.iter().cloned());
+        }
+
+        self
+    }
+
+    fn is_selected(&self, root: &Path, repo: &GitRepo) -> bool {
+        if let Some(ref members) = self.members {
+            if !members.contains(repo.path()) {
+                return false;
+            }
+        }
+
+        if self.only.is_empty() {
+            return true;
+        }
+
+        let path = repo.path();
+        let relative = path.strip_prefix(root).unwrap_or(path);
+
+        self.only
+            .iter()
+            .any(|x| relative == x.as_path() || path.file_name() == Some(x.as_os_str()))
     }
 }
 
@@ -136,9 +246,32 @@ impl<'a> Iterator for GitRepositories<'a> {
     type Item = GitRepo;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.mode {
-            RepoMode::Exploratory(ref mut em) => em.next(),
-            RepoMode::Manifest(ref mut mm) => mm.next(),
+        loop {
+            let (repo, root) = {
+                let source = self.sources.front_mut()?;
+
+                let repo = match source.mode {
+                    RepoMode::Exploratory(ref mut em) => em.next(),
+                    RepoMode::Manifest(ref mut mm) => mm.next(),
+                };
+
+                match repo {
+                    Some(ref r) if source.is_excluded(r) => continue,
+                    _ => (repo, source.root.clone()),
+                }
+            };
+
+            let repo = match repo {
+                Some(r) => r,
+                None => {
+                    self.sources.pop_front();
+                    continue;
+                }
+            };
+
+            if self.is_selected(&root, &repo) && self.seen.insert(repo.path().to_path_buf()) {
+                return Some(repo);
+            }
         }
     }
 }
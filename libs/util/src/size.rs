@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::Path;
+
+const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+
+// e.g. "12.3 MiB"
+pub fn format_bytes(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
+
+// Total size of every file under the path. Symlinks count as themselves
+// rather than what they point at.
+pub fn dir_size<P: AsRef<Path>>(path: P) -> u64 {
+    walk(path.as_ref(), false)
+}
+
+// Like dir_size, but skips the repo's own .git and any repos nested inside it
+// so each byte is only counted once across a workspace.
+pub fn worktree_size<P: AsRef<Path>>(path: P) -> u64 {
+    walk(path.as_ref(), true)
+}
+
+fn walk(path: &Path, worktree: bool) -> u64 {
+    let entries = match path.read_dir() {
+        Ok(e) => e,
+        Err(_) => return 0,
+    };
+
+    let mut total = 0;
+
+    for entry in entries.flatten() {
+        let meta = match fs::symlink_metadata(entry.path()) {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        if !meta.is_dir() {
+            total += meta.len();
+            continue;
+        }
+
+        let child = entry.path();
+
+        if worktree && (entry.file_name() == ".git" || child.join(".git").exists()) {
+            continue;
+        }
+
+        total += walk(&child, worktree);
+    }
+
+    total
+}
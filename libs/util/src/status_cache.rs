@@ -0,0 +1,65 @@
+use serde_json;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use date;
+
+// The summary lines from the last status run, for answering instantly
+// without reading any working trees.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StatusCache {
+    saved: i64,
+    checked: usize,
+    detached: usize,
+    repos: BTreeMap<PathBuf, String>,
+}
+
+impl StatusCache {
+    pub fn new(checked: usize, detached: usize, repos: BTreeMap<PathBuf, String>) -> Self {
+        Self {
+            saved: date::now(),
+            checked,
+            detached,
+            repos,
+        }
+    }
+
+    // None when there's no cache yet or it can't be read.
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        serde_json::from_reader(&file).ok()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let data = serde_json::to_string(self).map_err(io::Error::other)?;
+        let temp_path = path.with_extension("json.tmp");
+
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, path)
+    }
+
+    pub fn saved(&self) -> i64 {
+        self.saved
+    }
+
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+
+    pub fn detached(&self) -> usize {
+        self.detached
+    }
+
+    pub fn repos(&self) -> &BTreeMap<PathBuf, String> {
+        &self.repos
+    }
+}
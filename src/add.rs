@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct AddOptions {
+    pub pathspecs: Vec<String>,
+}
+
+// Only repos where something was staged are listed.
+pub fn process_add(repos: GitRepositories, pool: &ThreadPool, options: &AddOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let pathspecs = options.pathspecs.clone();
+
+        pool.execute(move || {
+                         let pathspecs = pathspecs.iter().map(String::as_str).collect::<Vec<_>>();
+                         let result = repo.stage(&pathspecs);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut staged = 0;
+    let mut repos = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(0) => (),
+            Ok(n) => {
+                let files = match n {
+                    1 => String::from("1 file"),
+                    n => format!("{} files", n),
+                };
+
+                println!("{}  {}", path.display(), BrightGreen.paint(format!("staged {}", files)));
+                staged += n;
+                repos += 1;
+            }
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not stage")),
+        }
+    }
+
+    match staged {
+        0 => println!("Nothing matching {} to stage", options.pathspecs.join(" ")),
+        n => println!("Staged {} files in {} repositories", n, repos),
+    }
+}
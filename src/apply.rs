@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::ApplyResult;
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct ApplyOptions {
+    pub patch: PathBuf,
+    pub dry_run: bool,
+}
+
+pub fn process_apply(repos: GitRepositories, pool: &ThreadPool, options: &ApplyOptions) {
+    // git runs in each repo, so a relative path would resolve differently for every one.
+    let patch = match fs::canonicalize(&options.patch) {
+        Ok(p) => p,
+        Err(_) => {
+            println!("{}", BrightRed.paint(format!("Could not find {}", options.patch.display())));
+            return;
+        }
+    };
+
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let patch = patch.clone();
+        let dry_run = options.dry_run;
+
+        pool.execute(move || {
+                         let result = repo.apply_patch(&patch, dry_run);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut clean = 0;
+
+    for (path, result) in &results {
+        let message = match *result {
+            Ok(ApplyResult::Applied) => styled(BrightGreen, "applied"),
+            Ok(ApplyResult::Applies) => styled(BrightGreen, "applies cleanly"),
+            Ok(ApplyResult::Rejected(ref reason)) => styled(BrightRed, format!("failed: {}", reason)),
+            Err(_) => styled(BrightRed, "could not run git apply"),
+        };
+
+        if matches!(*result, Ok(ApplyResult::Applied) | Ok(ApplyResult::Applies)) {
+            clean += 1;
+        }
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    match options.dry_run {
+        true => println!("Applies cleanly to {} of {} repositories", clean, results.len()),
+        false => println!("Applied to {} of {} repositories", clean, results.len()),
+    }
+}
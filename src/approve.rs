@@ -0,0 +1,62 @@
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prompt {
+    Never,
+    PerRepo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Answer {
+    Yes,
+    No,
+    Quit,
+}
+
+pub struct Approval {
+    prompt: Prompt,
+    all: bool,
+}
+
+impl Approval {
+    pub fn new(prompt: Prompt) -> Self {
+        Self { prompt, all: false }
+    }
+
+    pub fn ask(&mut self, question: &str) -> Answer {
+        if self.prompt == Prompt::Never || self.all {
+            return Answer::Yes;
+        }
+
+        let stdin = io::stdin();
+
+        loop {
+            print!("{} [y,n,a,q,?]? ", question);
+            io::stdout().flush().expect("Could not flush stdout");
+
+            let mut line = String::new();
+
+            // Treat a closed stdin as a refusal for everything that's left.
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) | Err(_) => return Answer::Quit,
+                Ok(_) => (),
+            }
+
+            match line.trim() {
+                "y" => return Answer::Yes,
+                "n" => return Answer::No,
+                "a" => {
+                    self.all = true;
+                    return Answer::Yes;
+                }
+                "q" => return Answer::Quit,
+                _ => {
+                    println!("y - apply to this repository");
+                    println!("n - skip this repository");
+                    println!("a - apply to this and all remaining repositories");
+                    println!("q - quit; leave this and all remaining repositories alone");
+                }
+            }
+        }
+    }
+}
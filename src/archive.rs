@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+pub const FORMATS: &[&str] = &["tar", "tar.gz", "zip"];
+
+#[derive(Debug, Clone)]
+pub struct ArchiveOptions {
+    pub output: PathBuf,
+    pub format: String,
+}
+
+// One archive per repo, named after its path under the root so that
+// "a/tools" and "b/tools" don't overwrite each other.
+pub fn process_archive(repos: GitRepositories, pool: &ThreadPool, roots: &[PathBuf], options: &ArchiveOptions) {
+    if let Err(e) = fs::create_dir_all(&options.output) {
+        println!("{}", BrightRed.paint(format!("Could not create {}: {}", options.output.display(), e)));
+        return;
+    }
+
+    // git runs in each repo, so a relative path would resolve differently for every one.
+    let output = fs::canonicalize(&options.output).unwrap_or_else(|_| options.output.clone());
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let name = archive_name(repo.path(), roots);
+        let file = output.join(format!("{}.{}", name, options.format));
+        let format = options.format.clone();
+
+        pool.execute(move || {
+                         let result = repo.archive(&file, &format, &name).map(|_| file);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut total = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref file) => {
+                let size = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+                total += size;
+
+                println!("{}  {} {}",
+                         path.display(),
+                         BrightGreen.paint(file.display()),
+                         util::format_bytes(size));
+            }
+            // Most often an unborn branch, with no HEAD to archive.
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not archive")),
+        }
+    }
+
+    let archived = results.values().filter(|r| r.is_ok()).count();
+    println!("Archived {} of {} repositories, {}", archived, results.len(), util::format_bytes(total));
+}
+
+fn archive_name(path: &Path, roots: &[PathBuf]) -> String {
+    let relative = roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .find(|p| !p.as_os_str().is_empty());
+
+    let components = match relative {
+        Some(p) => p.iter().map(|c| c.to_string_lossy().into_owned()).collect::<Vec<_>>(),
+        None => path.file_name().map(|n| n.to_string_lossy().into_owned()).into_iter().collect(),
+    };
+
+    match components.is_empty() {
+        true => String::from("repository"),
+        false => components.join("-"),
+    }
+}
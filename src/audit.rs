@@ -0,0 +1,481 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::{GitHead, GitRepo, Reachability};
+use styled::{self, styled};
+use util::{self, GitRepositories};
+
+use lfs;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub enum AuditOption {
+    Conflicts,
+    Detached,
+    Diverged,
+    Duplicates,
+    Hooks(Vec<String>),
+    Identity(IdentityOptions),
+    Lfs,
+    NoRemote,
+    Reachability,
+    Stale,
+    Unpushed,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IdentityOptions {
+    // e.g. "*@example.com"
+    pub expect: Option<String>,
+    // Only hold repos whose origin URL contains this to the expectation.
+    pub remote: Option<String>,
+}
+
+pub fn process_audit(repos: GitRepositories, pool: &ThreadPool, option: &AuditOption) {
+    match *option {
+        AuditOption::Conflicts => conflicts(repos, pool),
+        AuditOption::Detached => detached(repos, pool),
+        AuditOption::Diverged => diverged(repos, pool),
+        AuditOption::Duplicates => duplicates(repos, pool),
+        AuditOption::Hooks(ref required) => hooks(repos, pool, required),
+        AuditOption::Identity(ref opts) => identity(repos, pool, opts),
+        AuditOption::Lfs => lfs(repos, pool),
+        AuditOption::NoRemote => no_remote(repos, pool),
+        AuditOption::Reachability => reachability(repos, pool),
+        AuditOption::Stale => stale(repos, pool),
+        AuditOption::Unpushed => unpushed(repos, pool),
+    }
+}
+
+// Runs the check against every repo in parallel, keeping only the repos it
+// flagged, in path order.
+fn collect<T, F>(repos: GitRepositories, pool: &ThreadPool, check: F) -> Vec<(PathBuf, T)>
+    where T: Send + 'static,
+          F: Fn(&GitRepo) -> Option<T> + Send + Sync + 'static
+{
+    let (tx, rx) = channel();
+    let check = Arc::new(check);
+
+    for repo in repos {
+        let tx = tx.clone();
+        let check = check.clone();
+
+        pool.execute(move || {
+                         let finding = check(&repo).map(|f| (repo.path().to_path_buf(), f));
+                         tx.send(finding).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let mut findings = rx.iter().flatten().collect::<Vec<_>>();
+    findings.sort_by(|a, b| a.0.cmp(&b.0));
+    findings
+}
+
+fn print_total(count: usize, what: &str) {
+    match count {
+        0 => println!("No repositories {}", what),
+        1 => println!("{}", BrightYellow.paint(format!("1 repository {}", what))),
+        n => println!("{}", BrightYellow.paint(format!("{} repositories {}", n, what))),
+    }
+}
+
+// Local-only work is gone for good if the disk dies.
+fn no_remote(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| match repo.remote_names() {
+        Ok(ref names) if names.is_empty() => Some(()),
+        _ => None,
+    });
+
+    for (path, _) in &findings {
+        println!("{}", path.display());
+    }
+
+    print_total(findings.len(), "without a remote");
+}
+
+// Bulk checkout and reset move HEAD without asking, and a detached HEAD
+// that no branch contains takes its commits with it.
+fn detached(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| match repo.head_state() {
+        GitHead::Detached(id) => {
+            let branches = repo.branches_containing("HEAD").ok().flatten().map_or(0, |b| b.len());
+            Some((id, branches))
+        }
+        _ => None,
+    });
+
+    for (path, (id, branches)) in &findings {
+        let reach = match *branches {
+            0 => styled(BrightRed, "on no branch"),
+            1 => styled(BrightBlack, "on 1 branch"),
+            n => styled(BrightBlack, format!("on {} branches", n)),
+        };
+
+        println!("{}  {} {}", path.display(), BrightYellow.paint(format!("@ {}", id)), reach.paint());
+    }
+
+    print_total(findings.len(), "on a detached HEAD");
+}
+
+// Being both ahead and behind needs a rebase or merge decision, unlike a
+// plain push or fast-forward.
+fn diverged(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| match repo.ahead_behind() {
+        Ok(Some((ahead, behind))) if ahead > 0 && behind > 0 => {
+            let branch = repo.head().map(|h| h.shorthand().to_string()).unwrap_or_default();
+            Some((branch, ahead, behind))
+        }
+        _ => None,
+    });
+
+    for (path, (branch, ahead, behind)) in &findings {
+        println!("{}  {} {} {}",
+                 path.display(),
+                 BrightCyan.paint(branch),
+                 BrightGreen.paint(format!("⇡{}", ahead)),
+                 BrightRed.paint(format!("⇣{}", behind)));
+    }
+
+    print_total(findings.len(), "diverged from upstream");
+}
+
+// Several checkouts of one remote waste disk and make it easy to push from
+// the wrong copy.
+fn duplicates(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| match repo.remote_url("origin") {
+        Ok(Some(url)) => Some(normalize_url(&url)),
+        _ => None,
+    });
+
+    let mut groups = BTreeMap::new();
+
+    for (path, url) in findings {
+        groups.entry(url).or_insert_with(Vec::new).push(path);
+    }
+
+    let mut count = 0;
+
+    for (url, paths) in groups.iter().filter(|&(_, p)| p.len() > 1) {
+        println!("{}", BrightCyan.paint(url));
+
+        for path in paths {
+            println!("  {}", path.display());
+        }
+
+        count += 1;
+    }
+
+    match count {
+        0 => println!("No remotes are cloned more than once"),
+        1 => println!("{}", BrightYellow.paint("1 remote is cloned more than once")),
+        n => println!("{}", BrightYellow.paint(format!("{} remotes are cloned more than once", n))),
+    }
+}
+
+// Reduces the many spellings of one remote to "host/owner/name":
+// https://github.com/a/b.git, git@github.com:a/b and ssh://git@github.com/a/b/
+// all agree.
+fn normalize_url(url: &str) -> String {
+    let url = url.trim().to_lowercase();
+
+    let rest = match url.find("://") {
+        Some(i) => url[i + 3..].to_string(),
+        // scp-like syntax separates host and path with a colon.
+        None => url.replacen(':', "/", 1),
+    };
+
+    let rest = match rest.find('@') {
+        Some(i) if i < rest.find('/').unwrap_or(rest.len()) => &rest[i + 1..],
+        _ => &rest[..],
+    };
+
+    let rest = rest.trim_end_matches('/');
+    let rest = rest.strip_suffix(".git").unwrap_or(rest);
+
+    rest.to_string()
+}
+
+// Markers that slipped into a commit after a batch merge. A file only counts
+// when it has both ends of a conflict, so docs that show a lone marker don't.
+fn conflicts(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| {
+        let markers = repo.tracked_paths()
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|p| conflict_lines(&repo.path().join(&p)).into_iter().map(move |l| (p.clone(), l)))
+            .collect::<Vec<_>>();
+
+        match markers.is_empty() {
+            true => None,
+            false => Some(markers),
+        }
+    });
+
+    for (path, markers) in &findings {
+        println!("{}", path.display());
+
+        for (file, line) in markers {
+            println!("  {}:{}", BrightRed.paint(file.display()), line);
+        }
+    }
+
+    print_total(findings.len(), "with conflict markers");
+}
+
+// 1-based line numbers of each conflict start marker in the file.
+fn conflict_lines(file: &Path) -> Vec<usize> {
+    let contents = match fs::read(file) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    // Binary files can match by accident.
+    if contents.contains(&0) {
+        return Vec::new();
+    }
+
+    let text = String::from_utf8_lossy(&contents);
+    let is_marker = |line: &str, marker: &str| line == marker || line.starts_with(&format!("{} ", marker));
+
+    if !text.lines().any(|l| is_marker(l, ">>>>>>>")) {
+        return Vec::new();
+    }
+
+    text.lines()
+        .enumerate()
+        .filter(|&(_, l)| is_marker(l, "<<<<<<<"))
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
+// Everything that only exists on this machine, per branch.
+fn unpushed(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| match repo.unpushed() {
+        Ok(ref branches) if branches.is_empty() => None,
+        Ok(branches) => Some(branches),
+        Err(_) => None,
+    });
+
+    for (path, branches) in &findings {
+        println!("{}", path.display());
+
+        for (branch, commits) in branches {
+            let oldest = commits.last().expect("Unpushed branches have commits");
+
+            println!("  {} {} since {}",
+                     BrightCyan.paint(branch),
+                     BrightYellow.paint(match commits.len() {
+                                            1 => String::from("1 commit"),
+                                            n => format!("{} commits", n),
+                                        }),
+                     BrightBlack.paint(format!("{} ({})",
+                                               util::format_date(oldest.time(), oldest.offset_minutes()),
+                                               util::format_age(oldest.time()))));
+        }
+    }
+
+    print_total(findings.len(), "with unpushed commits");
+}
+
+// Default branches that have fallen behind, furthest first; ties go to the
+// repo fetched longest ago.
+fn stale(repos: GitRepositories, pool: &ThreadPool) {
+    let mut findings = collect(repos, pool, |repo| {
+        let branch = repo.default_branch()?;
+
+        match repo.branch_ahead_behind(&branch) {
+            Ok(Some((_, behind))) if behind > 0 => Some((branch, behind, repo.last_fetch())),
+            _ => None,
+        }
+    });
+
+    findings.sort_by(|&(_, (_, a_behind, a_fetched)), &(_, (_, b_behind, b_fetched))| {
+                         b_behind.cmp(&a_behind).then(a_fetched.unwrap_or(0).cmp(&b_fetched.unwrap_or(0)))
+                     });
+
+    for (path, (branch, behind, fetched)) in &findings {
+        let fetched = match *fetched {
+            Some(t) => format!("fetched {}", util::format_age(t)),
+            None => String::from("never fetched"),
+        };
+
+        println!("{}  {} {}  {}",
+                 path.display(),
+                 BrightCyan.paint(branch),
+                 BrightRed.paint(format!("⇣{}", behind)),
+                 BrightBlack.paint(fetched));
+    }
+
+    print_total(findings.len(), "behind upstream on their default branch");
+}
+
+// Finds dead remotes before a big sync trips over them.
+fn reachability(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| {
+        let url = repo.remote_url("origin").ok()??;
+
+        match repo.check_remote("origin") {
+            Ok(Reachability::Reachable) => None,
+            Ok(r) => Some((url, r)),
+            Err(_) => Some((url, Reachability::Unreachable(String::from("Could not run git")))),
+        }
+    });
+
+    for (path, (url, reachability)) in &findings {
+        let (label, detail) = match *reachability {
+            Reachability::Reachable => continue,
+            Reachability::Moved(ref to) => (BrightYellow.paint("moved"), format!("now at {}", to)),
+            Reachability::AuthFailed(ref m) => (BrightRed.paint("auth failed"), m.clone()),
+            Reachability::NotFound(ref m) => (BrightRed.paint("not found"), m.clone()),
+            Reachability::Unreachable(ref m) => (BrightRed.paint("unreachable"), m.clone()),
+        };
+
+        println!("{}  {} {}", path.display(), label, BrightBlack.paint(url));
+
+        if !detail.is_empty() {
+            println!("  {}", detail);
+        }
+    }
+
+    print_total(findings.len(), "with an origin that needs attention");
+}
+
+// Every repo's effective email, with the ones that would commit under the
+// wrong identity called out.
+fn identity(repos: GitRepositories, pool: &ThreadPool, options: &IdentityOptions) {
+    let options = options.clone();
+
+    let findings = collect(repos, pool, move |repo| {
+        let (name, email) = repo.identity().ok()?;
+
+        let applies = match options.remote {
+            Some(ref r) => {
+                repo.remote_url("origin")
+                    .unwrap_or(None)
+                    .is_some_and(|u| u.contains(r.as_str()))
+            }
+            None => true,
+        };
+
+        let problem = match (email.as_ref(), options.expect.as_ref()) {
+            (None, _) => Some(String::from("no user.email")),
+            (Some(e), Some(p)) if applies && !util::glob_match(&p.to_lowercase(), &e.to_lowercase()) => {
+                Some(format!("expected {}", p))
+            }
+            _ => None,
+        };
+
+        Some((name, email, problem))
+    });
+
+    let mut flagged = 0;
+
+    for (path, (name, email, problem)) in &findings {
+        let who = match (name.as_ref(), email.as_ref()) {
+            (Some(n), Some(e)) => format!("{} <{}>", n, e),
+            (None, Some(e)) => format!("<{}>", e),
+            (_, None) => String::new(),
+        };
+
+        match *problem {
+            Some(ref p) => {
+                flagged += 1;
+                println!("{}  {} {}", path.display(), BrightRed.paint(who), BrightRed.paint(format!("({})", p)));
+            }
+            None => println!("{}  {}", path.display(), BrightGreen.paint(who)),
+        }
+    }
+
+    print_total(flagged, "with an unexpected identity");
+}
+
+// Required hooks that are missing or wouldn't run. Repos pointing
+// core.hooksPath elsewhere are named, since that directory is usually shared
+// and managed outside the repo.
+fn hooks(repos: GitRepositories, pool: &ThreadPool, required: &[String]) {
+    let required = required.to_vec();
+
+    let findings = collect(repos, pool, move |repo| {
+        let (dir, custom) = repo.hooks_dir();
+
+        let missing = required
+            .iter()
+            .filter(|h| !is_executable(&dir.join(h)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        match (missing.is_empty(), custom) {
+            (true, false) => None,
+            _ => Some((dir, custom, missing)),
+        }
+    });
+
+    let mut flagged = 0;
+
+    for (path, (dir, custom, missing)) in &findings {
+        let mut parts = Vec::new();
+
+        if !missing.is_empty() {
+            flagged += 1;
+            parts.push(vec![styled(BrightRed, format!("missing {}", missing.join(", ")))]);
+        }
+
+        if *custom {
+            parts.push(vec![styled(BrightYellow, format!("core.hooksPath {}", dir.display()))]);
+        }
+
+        styled::print_repo_line(path, &styled::join(parts, " "));
+    }
+
+    print_total(flagged, "missing required hooks");
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match fs::metadata(path) {
+        Ok(m) => m.is_file() && m.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+// Git for Windows runs any hook file regardless of permissions.
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// The same scan `lfs status` reports on.
+fn lfs(repos: GitRepositories, pool: &ThreadPool) {
+    let findings = collect(repos, pool, |repo| lfs::scan(repo).map(|s| (s.pointers, s.missing, s.size)));
+
+    let mut broken = 0;
+
+    for (path, (pointers, missing, size)) in &findings {
+        let state = match (*pointers, *missing) {
+            (0, _) => styled(BrightGreen, "checked out"),
+            (n, 0) => styled(BrightYellow, format!("{} pointer files not checked out", n)),
+            (n, m) => styled(BrightRed, format!("{} of {} pointer files without local objects", m, n)),
+        };
+
+        if *pointers > 0 {
+            broken += 1;
+        }
+
+        println!("{}  {}  {}", path.display(), state.paint(), BrightBlack.paint(util::format_bytes(*size)));
+    }
+
+    print_total(findings.len(), "using LFS");
+    print_total(broken, "with LFS pointers in place of their files");
+}
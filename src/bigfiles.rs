@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use styled::{plain, styled};
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct BigfilesOptions {
+    pub min_size: u64,
+}
+
+// One list for the whole workspace, biggest first, so the repos most worth
+// moving to LFS float to the top. History counts, not just the checkout.
+pub fn process_bigfiles(repos: GitRepositories, pool: &ThreadPool, options: &BigfilesOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let min_size = options.min_size;
+
+        pool.execute(move || {
+                         let blobs = repo.blobs_over(min_size);
+                         tx.send((repo.path().to_path_buf(), blobs)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut files = Vec::new();
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref blobs) => files.extend(blobs.iter().map(|b| (path, b))),
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not read objects")),
+        }
+    }
+
+    files.sort_by(|a, b| b.1.size().cmp(&a.1.size()).then_with(|| a.0.cmp(b.0)));
+
+    for &(path, blob) in &files {
+        let name = match blob.path() {
+            Some(p) => plain(p.display().to_string()),
+            None => styled(BrightBlack, format!("{} (unreachable)", blob.id())),
+        };
+
+        println!("{:>10}  {}  {}",
+                 BrightYellow.paint(util::format_bytes(blob.size())),
+                 BrightCyan.paint(path.display()),
+                 name.paint());
+    }
+
+    let repos = results.values().filter(|r| r.as_ref().is_ok_and(|b| !b.is_empty())).count();
+    let total = files.iter().map(|&(_, b)| b.size()).sum();
+
+    match files.len() {
+        0 => println!("No blobs of {} or more", util::format_bytes(options.min_size)),
+        n => {
+            println!("{} blobs of {} or more in {} repositories, {} in all",
+                     n,
+                     util::format_bytes(options.min_size),
+                     repos,
+                     util::format_bytes(total))
+        }
+    }
+}
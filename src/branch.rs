@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightGreen, BrightRed, BrightYellow};
+use threadpool::ThreadPool;
+
+use gitlib::{BranchCreation, BranchDeletion, GitHead};
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub enum BranchOption {
+    List,
+    Create(CreateOptions),
+    Delete(DeleteOptions),
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateOptions {
+    pub name: String,
+    // Start from this ref instead of HEAD.
+    pub from: Option<String>,
+    pub checkout: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteOptions {
+    pub name: String,
+    pub force: bool,
+}
+
+pub fn process_branch(repos: GitRepositories, pool: &ThreadPool, option: &BranchOption) {
+    match *option {
+        BranchOption::List => list(repos, pool),
+        BranchOption::Create(ref opts) => create(repos, pool, opts),
+        BranchOption::Delete(ref opts) => delete(repos, pool, opts),
+    }
+}
+
+// Lists the branch each repo has checked out, then how many are on each.
+fn list(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         tx.send((repo.path().to_path_buf(), repo.head_state())).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let heads = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut counts = BTreeMap::new();
+
+    for (path, head) in &heads {
+        let (label, key) = match *head {
+            GitHead::Branch(ref name) => (styled(BrightCyan, name.as_str()), name.clone()),
+            GitHead::Detached(ref id) => {
+                (styled(BrightRed, format!("(detached @ {})", id)), String::from("(detached)"))
+            }
+            GitHead::Unborn => (styled(BrightBlack, "(no commits)"), String::from("(no commits)")),
+        };
+
+        println!("{}  {}", path.display(), label.paint());
+        *counts.entry(key).or_insert(0) += 1;
+    }
+
+    let summary = counts
+        .iter()
+        .map(|(name, n)| format!("{} {}", n, name))
+        .collect::<Vec<_>>();
+
+    match summary.is_empty() {
+        true => println!("No repositories"),
+        false => println!("{}", summary.join(", ")),
+    }
+}
+
+fn create(repos: GitRepositories, pool: &ThreadPool, options: &CreateOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let result = repo.create_branch(&options.name, options.from.as_deref(), options.checkout);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut created = 0;
+
+    for (path, result) in &results {
+        let message = match *result {
+            Ok(BranchCreation::Created) if options.checkout => {
+                created += 1;
+                styled(BrightYellow, "created, but local changes stopped the checkout")
+            }
+            Ok(BranchCreation::Created) => {
+                created += 1;
+                styled(BrightGreen, "created")
+            }
+            Ok(BranchCreation::CheckedOut) => {
+                created += 1;
+                styled(BrightGreen, "created and checked out")
+            }
+            Ok(BranchCreation::Exists) => styled(BrightBlack, "already exists; left alone"),
+            Ok(BranchCreation::NoStartPoint) => {
+                let from = options.from.as_deref().unwrap_or("HEAD");
+                styled(BrightYellow, format!("no {} to start from; skipped", from))
+            }
+            Err(_) => styled(BrightRed, "could not create"),
+        };
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    println!("Created {} in {} of {} repositories", options.name, created, results.len());
+}
+
+// Only repos that have the branch are mentioned.
+fn delete(repos: GitRepositories, pool: &ThreadPool, options: &DeleteOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let result = repo.delete_branch(&options.name, options.force);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut deleted = 0;
+
+    for (path, result) in &results {
+        let message = match *result {
+            Ok(BranchDeletion::NotFound) => continue,
+            Ok(BranchDeletion::Deleted) => {
+                deleted += 1;
+                styled(BrightGreen, "deleted")
+            }
+            Ok(BranchDeletion::Current) => styled(BrightYellow, "checked out; skipped"),
+            Ok(BranchDeletion::Unmerged) => {
+                styled(BrightYellow, "not fully merged; skipped, use --force to delete anyway")
+            }
+            Err(_) => styled(BrightRed, "could not delete"),
+        };
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    let found = results.values().filter(|r| !matches!(**r, Ok(BranchDeletion::NotFound))).count();
+
+    match found {
+        0 => println!("No repository has a branch named {}", options.name),
+        n => println!("Deleted {} in {} of {} repositories that had it", options.name, deleted, n),
+    }
+}
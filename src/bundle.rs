@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitRepo;
+use util::{self, GitRepositories, Manifest};
+
+use clone;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+const EXTENSION: &str = "bundle";
+
+#[derive(Debug, Clone)]
+pub struct BundleOptions {
+    pub output: PathBuf,
+}
+
+// Bundles mirror the workspace layout, "tools/gitplz" becoming
+// "<output>/tools/gitplz.bundle", so restoring can put each one back.
+pub fn process_bundle(repos: GitRepositories, pool: &ThreadPool, roots: &[PathBuf], options: &BundleOptions) {
+    if let Err(e) = fs::create_dir_all(&options.output) {
+        println!("{}", BrightRed.paint(format!("Could not create {}: {}", options.output.display(), e)));
+        return;
+    }
+
+    // git runs in each repo, so a relative path would resolve differently for every one.
+    let output = fs::canonicalize(&options.output).unwrap_or_else(|_| options.output.clone());
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let mut file = output.join(relative_path(repo.path(), roots));
+        file.set_extension(EXTENSION);
+
+        pool.execute(move || {
+                         let result = file.parent()
+                             .map_or(Ok(()), fs::create_dir_all)
+                             .map_err(|_| ())
+                             .and_then(|_| repo.bundle(&file).map_err(|_| ()))
+                             .map(|_| file);
+
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut total = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref file) => {
+                let size = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+                total += size;
+
+                println!("{}  {} {}",
+                         path.display(),
+                         BrightGreen.paint(file.display()),
+                         util::format_bytes(size));
+            }
+            // Most often a repo without any commits yet.
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not bundle")),
+        }
+    }
+
+    let bundled = results.values().filter(|r| r.is_ok()).count();
+    println!("Bundled {} of {} repositories, {}", bundled, results.len(), util::format_bytes(total));
+}
+
+// The other half of process_bundle: clones every bundle under `dir` back to
+// the same place under the root. Bundles don't carry remotes, so origin is
+// pointed back at the manifest's recorded URL where there is one.
+pub fn process_clone_bundles(manifest: &mut Manifest, root: &Path, dir: &Path) {
+    let mut bundles = Vec::new();
+    find_bundles(dir, &mut bundles);
+    bundles.sort();
+
+    if bundles.is_empty() {
+        println!("{}", BrightRed.paint(format!("No .{} files under {}", EXTENSION, dir.display())));
+        return;
+    }
+
+    let mut cloned = 0;
+
+    for bundle in &bundles {
+        let relative = bundle.strip_prefix(dir).unwrap_or(bundle).with_extension("");
+        let path = root.join(&relative);
+
+        if path.exists() {
+            println!("{}  {}", path.display(), BrightBlack.paint("already present"));
+            continue;
+        }
+
+        let url = match fs::canonicalize(bundle).ok().and_then(|b| b.to_str().map(String::from)) {
+            Some(u) => u,
+            None => {
+                println!("{}  {}", path.display(), BrightRed.paint("bundle path is not valid UTF-8"));
+                continue;
+            }
+        };
+
+        let repo = match GitRepo::clone_url(&url, &path, None, None) {
+            Ok(r) => r,
+            Err(_) => {
+                println!("{}  {}", path.display(), BrightRed.paint("clone failed"));
+                continue;
+            }
+        };
+
+        cloned += 1;
+
+        match manifest.origin(&relative).map(String::from) {
+            Some(origin) => {
+                match repo.set_remote_url("origin", &origin) {
+                    Ok(_) => println!("{}  {} {}", path.display(), BrightGreen.paint("cloned"), origin),
+                    Err(_) => println!("{}  {}", path.display(), BrightRed.paint("cloned; could not restore origin")),
+                }
+            }
+            None => println!("{}  {}", path.display(), BrightGreen.paint("cloned; origin is the bundle")),
+        }
+
+        clone::register(manifest, &repo);
+    }
+
+    println!("Cloned {} of {} bundles", cloned, bundles.len());
+}
+
+fn find_bundles(dir: &Path, bundles: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_dir() {
+            find_bundles(&path, bundles);
+        } else if path.extension() == Some(OsStr::new(EXTENSION)) {
+            bundles.push(path);
+        }
+    }
+}
+
+fn relative_path(path: &Path, roots: &[PathBuf]) -> PathBuf {
+    let relative = roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .find(|p| !p.as_os_str().is_empty());
+
+    match relative {
+        Some(p) => p.to_path_buf(),
+        None => PathBuf::from(path.file_name().unwrap_or_else(|| "repository".as_ref())),
+    }
+}
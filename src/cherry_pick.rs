@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::CherryPick;
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct CherryPickOptions {
+    // A commit to pick everywhere, or a file mapping repos to commits.
+    pub target: String,
+}
+
+pub fn process_cherry_pick(repos: GitRepositories, pool: &ThreadPool, options: &CherryPickOptions) {
+    let mapping = match Path::new(&options.target).is_file() {
+        true => {
+            match load_mapping(Path::new(&options.target)) {
+                Ok(m) => Some(m),
+                Err(e) => {
+                    println!("{}", BrightRed.paint(e));
+                    return;
+                }
+            }
+        }
+        false => None,
+    };
+
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let rev = match mapping {
+            Some(ref m) => {
+                match m.iter().find(|(path, _)| repo.path().ends_with(path)) {
+                    Some((_, rev)) => rev.clone(),
+                    None => continue,
+                }
+            }
+            None => options.target.clone(),
+        };
+
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let result = repo.cherry_pick(&rev);
+                         tx.send((repo.path().to_path_buf(), (rev, result))).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut picked = 0;
+
+    for (path, (rev, result)) in &results {
+        let message = match *result {
+            // Only worth a mention when the mapping said the repo should have it.
+            Ok(CherryPick::NotFound) if mapping.is_none() => continue,
+            Ok(CherryPick::NotFound) => styled(BrightYellow, format!("no commit {}", rev)),
+            Ok(CherryPick::Picked) => {
+                picked += 1;
+                styled(BrightGreen, format!("picked {}", rev))
+            }
+            Ok(CherryPick::AlreadyApplied) => styled(BrightBlack, "already has it"),
+            Ok(CherryPick::Conflicted) => styled(BrightRed, "conflicted; aborted"),
+            Err(_) => styled(BrightRed, "could not cherry-pick"),
+        };
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    let attempted = results.values().filter(|(_, r)| !matches!(*r, Ok(CherryPick::NotFound))).count();
+
+    match attempted {
+        0 => println!("No repository has {}", options.target),
+        n => println!("Picked into {} of {} repositories", picked, n),
+    }
+}
+
+// One "<repo path> <commit>" per line. Paths match any repo whose path ends
+// with them, so "drewbrend/gitplz" works from any root; blank lines and
+// lines starting with '#' are skipped.
+fn load_mapping(path: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let mut mapping = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(repo), Some(rev), None) => mapping.push((PathBuf::from(repo), rev.to_string())),
+            _ => return Err(format!("{}:{}: expected \"<repo path> <commit>\"", path.display(), i + 1)),
+        }
+    }
+
+    Ok(mapping)
+}
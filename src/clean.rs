@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+use approve::{Answer, Approval, Prompt};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct CleanOptions {
+    pub dry_run: bool,
+    // Also remove ignored files, so build output goes too.
+    pub ignored: bool,
+    pub prompt: Prompt,
+}
+
+// Unlike reset, nothing tracked is touched, staged or not.
+pub fn process_clean(repos: GitRepositories, pool: &ThreadPool, options: &CleanOptions) {
+    if options.prompt == Prompt::PerRepo {
+        return clean_interactive(repos, options);
+    }
+
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let result = match options.dry_run {
+                             true => repo.untracked(options.ignored),
+                             false => repo.clean(options.ignored),
+                         };
+
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut removed = 0;
+
+    let verb = match options.dry_run {
+        true => "would remove",
+        false => "removed",
+    };
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref paths) if paths.is_empty() => (),
+            Ok(ref paths) => {
+                println!("{}", path.display());
+
+                for p in paths {
+                    println!("  {} {}", BrightYellow.paint(verb), p.display());
+                }
+
+                removed += paths.len();
+            }
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not clean")),
+        }
+    }
+
+    let noun = match removed {
+        1 => "path",
+        _ => "paths",
+    };
+
+    match (removed, options.dry_run) {
+        (0, _) => println!("Nothing to clean"),
+        (n, true) => println!("Would remove {} untracked {}", n, noun),
+        (n, false) => println!("Removed {} untracked {}", n, noun),
+    }
+}
+
+// One repo at a time, so each list is on screen when its question is asked.
+fn clean_interactive(repos: GitRepositories, options: &CleanOptions) {
+    let mut approval = Approval::new(Prompt::PerRepo);
+
+    for repo in repos {
+        let paths = match repo.untracked(options.ignored) {
+            Ok(p) => p,
+            Err(_) => {
+                println!("{}  {}", repo.path().display(), BrightRed.paint("could not list untracked files"));
+                continue;
+            }
+        };
+
+        if paths.is_empty() {
+            continue;
+        }
+
+        println!("{}", repo.path().display());
+
+        for p in &paths {
+            println!("    {}", p.display());
+        }
+
+        match approval.ask("Clean") {
+            Answer::Yes => (),
+            Answer::No => continue,
+            Answer::Quit => return,
+        }
+
+        if repo.clean(options.ignored).is_err() {
+            println!("{}  {}", repo.path().display(), BrightRed.paint("could not clean"));
+        }
+    }
+}
@@ -1,44 +1,842 @@
-use clap::{Arg, App, SubCommand, Shell};
+use std::path::Path;
+
+use clap::{Arg, App, AppSettings, SubCommand};
+
+use archive;
+use completions;
+use sizes;
+use standup;
+use util;
 
 pub const APP_NAME: &str = "git plz";
-pub const CMD_CLEAN: &str = "clean";
+pub const BIN_NAME: &str = "git-plz";
+pub const VERSION: &str = "0.1";
+pub const CMD_ADD: &str = "add";
+pub const CMD_APPLY: &str = "apply";
+pub const CMD_ARCHIVE: &str = "archive";
+pub const CMD_AUDIT: &str = "audit";
+pub const CMD_AUTHORS: &str = "authors";
+pub const CMD_BIGFILES: &str = "bigfiles";
+pub const CMD_BRANCH: &str = "branch";
+pub const CMD_BUNDLE: &str = "bundle";
 pub const CMD_CHECKOUT: &str = "checkout";
+pub const CMD_CHERRY_PICK: &str = "cherry-pick";
+pub const CMD_CLEAN: &str = "clean";
+pub const CMD_CLONE: &str = "clone";
+pub const CMD_CREATE: &str = "create";
+pub const CMD_DELETE: &str = "delete";
+pub const CMD_DETACHED: &str = "detached";
+pub const CMD_DIFF: &str = "diff";
+pub const CMD_DIFFSTAT: &str = "diffstat";
+pub const CMD_DIVERGED: &str = "diverged";
+pub const CMD_DOCTOR: &str = "doctor";
+pub const CMD_DUPLICATES: &str = "duplicates";
+pub const CMD_COMMIT: &str = "commit";
 pub const CMD_COMPLETIONS: &str = "completions";
+pub const CMD_CONFIG: &str = "config";
+pub const CMD_CONFLICTS: &str = "conflicts";
+pub const CMD_CONTAINS: &str = "contains";
+pub const CMD_CONTRIBUTORS: &str = "contributors";
+pub const CMD_COMPLETE_BRANCHES: &str = "__complete-branches";
+pub const CMD_COMPLETE_REPOS: &str = "__complete-repos";
+pub const CMD_EXEC: &str = "exec";
+pub const CMD_EXPORT: &str = "export";
+pub const CMD_FETCH: &str = "fetch";
+pub const CMD_FIND_COMMIT: &str = "find-commit";
+pub const CMD_FSCK: &str = "fsck";
+pub const CMD_GC: &str = "gc";
+pub const CMD_GREP: &str = "grep";
+pub const CMD_GET: &str = "get";
+pub const CMD_HOOKS: &str = "hooks";
+pub const CMD_IDENTITY: &str = "identity";
+pub const CMD_IGNORE: &str = "ignore";
+pub const CMD_IMPORT: &str = "import";
+pub const CMD_INIT: &str = "init";
+pub const CMD_INSTALL: &str = "install";
+pub const CMD_LFS: &str = "lfs";
+pub const CMD_LIST: &str = "list";
+pub const CMD_LOG: &str = "log";
+pub const CMD_MAN: &str = "man";
 pub const CMD_MANIFEST: &str = "manifest";
+pub const CMD_MERGE: &str = "merge";
+pub const CMD_MERGE_UPSTREAM: &str = "merge-upstream";
+pub const CMD_MV: &str = "mv";
+pub const CMD_NO_REMOTE: &str = "no-remote";
+pub const CMD_POP: &str = "pop";
 pub const CMD_PREVIEW: &str = "preview";
+pub const CMD_PRUNE: &str = "prune";
+pub const CMD_PULL: &str = "pull";
+pub const CMD_PUSH: &str = "push";
+pub const CMD_REACHABILITY: &str = "reachability";
+pub const CMD_REBASE: &str = "rebase";
+pub const CMD_REMOTE: &str = "remote";
+pub const CMD_REMOVE: &str = "remove";
 pub const CMD_RESET: &str = "reset";
+pub const CMD_RESTORE: &str = "restore";
+pub const CMD_RM: &str = "rm";
+pub const CMD_SELF_UPDATE: &str = "self-update";
+pub const CMD_SET: &str = "set";
+pub const CMD_SET_URL: &str = "set-url";
+pub const CMD_SIZES: &str = "sizes";
+pub const CMD_SPARSE: &str = "sparse";
+pub const CMD_STALE: &str = "stale";
+pub const CMD_STANDUP: &str = "standup";
+pub const CMD_STASH: &str = "stash";
+pub const CMD_STATS: &str = "stats";
 pub const CMD_STATUS: &str = "status";
+pub const CMD_SUBMODULE: &str = "submodule";
+pub const CMD_TAG: &str = "tag";
+pub const CMD_TUI: &str = "tui";
+pub const CMD_UNPUSHED: &str = "unpushed";
+pub const CMD_UNSHALLOW: &str = "unshallow";
 pub const CMD_UPDATE: &str = "update";
+pub const CMD_VERIFY: &str = "verify";
+pub const CMD_VERSION: &str = "version";
+pub const AGGRESSIVE: &str = "aggressive";
+pub const AUTHOR: &str = "author";
+pub const AUTOSTASH: &str = "autostash";
+pub const BASE: &str = "base";
+pub const BLOBS: &str = "blobs";
 pub const BRANCH: &str = "branch";
+pub const BY_REPO: &str = "by-repo";
+pub const CACHED: &str = "cached";
+pub const CHECK: &str = "check";
+pub const CHECKOUT: &str = "checkout";
+pub const COMMAND: &str = "command";
+pub const COMMIT: &str = "commit";
+pub const CONE: &str = "cone";
+pub const DEFAULT: &str = "default";
+pub const DEPTH: &str = "depth";
+pub const DIR: &str = "dir";
+pub const DRY_RUN: &str = "dry-run";
+pub const EMAIL: &str = "email";
+pub const EXPECT: &str = "expect";
+pub const FILE: &str = "file";
+pub const FORCE: &str = "force";
+pub const FORMAT: &str = "format";
+pub const FROM: &str = "from";
+pub const FROM_BUNDLES: &str = "from-bundles";
+pub const GLOB: &str = "glob";
+pub const GREP: &str = "grep";
+pub const GROUP: &str = "group";
+pub const HOOKS_PATH: &str = "hooks-path";
+pub const IGNORED: &str = "ignored";
+pub const IGNORE_CASE: &str = "ignore-case";
+pub const INITIAL_BRANCH: &str = "initial-branch";
+pub const INTERACTIVE: &str = "interactive";
+pub const JSON: &str = "json";
+pub const KEY: &str = "key";
+pub const LAST_COMMIT: &str = "last-commit";
+pub const MESSAGE: &str = "message";
+pub const MIN_SIZE: &str = "min-size";
+pub const NAME: &str = "name";
+pub const NO_FF: &str = "no-ff";
+pub const OFF_DEFAULT: &str = "off-default";
+pub const OUT_DIR: &str = "out-dir";
+pub const OUTPUT: &str = "output";
+pub const PATH: &str = "path";
+pub const PATCH: &str = "patch";
+pub const PATHSPEC: &str = "pathspec";
+pub const PATTERN: &str = "pattern";
+pub const PATTERNS: &str = "patterns";
+pub const PROFILE: &str = "profile";
+pub const PRUNE: &str = "prune";
+pub const PRUNE_TAGS: &str = "prune-tags";
+pub const RANGE: &str = "range";
+pub const REBASE: &str = "rebase";
+pub const RECURSIVE: &str = "recursive";
+pub const REFRESH: &str = "refresh";
+pub const REMOTE: &str = "remote";
+pub const REMOVE: &str = "remove";
+pub const REPO: &str = "repo";
+pub const REQUIRE: &str = "require";
+pub const REV: &str = "rev";
+pub const REWRITE: &str = "rewrite";
+pub const ROOT: &str = "root";
+pub const REPO_SHORT: &str = "r";
 pub const SHELL: &str = "shell";
+pub const SHORT: &str = "short";
+pub const SINCE: &str = "since";
+pub const STALE: &str = "stale";
+pub const SORT: &str = "sort";
+pub const SYMLINK: &str = "symlink";
+pub const TEMPLATE: &str = "template";
+pub const TO: &str = "to";
+pub const UNTIL: &str = "until";
+pub const URL: &str = "url";
+pub const VALUE: &str = "value";
+pub const VERBOSE: &str = "verbose";
+pub const WATCH: &str = "watch";
+
+pub const ABOUT: &str = "Run commands on a set of git repositories in a folder tree";
+
+// Every visible command's path and description, in the order they're
+// defined below. Man pages are generated from this rather than from clap's
+// parser internals.
+pub const COMMANDS: &[(&[&str], &str)] = &[
+    (&[CMD_ADD], "Stage files matching a pathspec in every repository"),
+    (&[CMD_APPLY], "Apply a patch to the working tree of every repository"),
+    (&[CMD_ARCHIVE], "Write a snapshot of each repository's HEAD, without its history, to a directory"),
+    (&[CMD_AUDIT], "Report repositories that need attention"),
+    (&[CMD_AUDIT, CMD_CONFLICTS], "Find leftover conflict markers in tracked files"),
+    (&[CMD_AUDIT, CMD_DETACHED], "List repositories on a detached HEAD and whether any branch contains it"),
+    (&[CMD_AUDIT, CMD_DIVERGED], "List repositories whose branch is both ahead of and behind its upstream"),
+    (&[CMD_AUDIT, CMD_DUPLICATES], "Group repositories that are clones of the same origin"),
+    (&[CMD_AUDIT, CMD_HOOKS], "List repositories missing required hooks"),
+    (&[CMD_AUDIT, CMD_IDENTITY], "Show the user.email commits would use in each repository"),
+    (&[CMD_AUDIT, CMD_LFS], "List repositories using Git LFS, their storage, and pointers without local objects"),
+    (&[CMD_AUDIT, CMD_NO_REMOTE], "List repositories with no remote configured"),
+    (&[CMD_AUDIT, CMD_REACHABILITY], "Check that each repository's origin can be reached and read"),
+    (&[CMD_AUDIT, CMD_STALE], "Rank repositories whose default branch is behind its upstream"),
+    (&[CMD_AUDIT, CMD_UNPUSHED], "List branches with commits that aren't on any remote"),
+    (&[CMD_BIGFILES], "List the largest blobs in every repository's history, e.g. to find candidates for LFS"),
+    (&[CMD_BRANCH], "Show the branch checked out in each repository"),
+    (&[CMD_BRANCH, CMD_CREATE], "Create the same new branch in every repository"),
+    (&[CMD_BRANCH, CMD_DELETE], "Delete a local branch from every repository that has it"),
+    (&[CMD_CHECKOUT], "Checkout branch across repos"),
+    (&[CMD_BUNDLE], "Back up every ref of each repository to a git bundle in a directory"),
+    (&[CMD_CHERRY_PICK], "Apply a commit on top of HEAD in every repository that has it"),
+    (&[CMD_CLEAN], "Remove untracked files and directories, leaving tracked changes alone"),
+    (&[CMD_CLONE], "Clone a repository under the root and add it to the manifest"),
+    (&[CMD_COMMIT], "Commit every change to tracked files in each repository that has some"),
+    (&[CMD_COMPLETIONS], "Generates completion scripts for your shell"),
+    (&[CMD_CONFIG], "Read or write a git config value in every repository"),
+    (&[CMD_CONFIG, CMD_GET], "Show the value each repository ends up with, and where it's inherited"),
+    (&[CMD_CONFIG, CMD_SET], "Write a value to each repository's own config"),
+    (&[CMD_CONTAINS], "Show which branches contain a commit or tag in each repository"),
+    (&[CMD_CONTRIBUTORS], "Commit counts per author across all repositories"),
+    (&[CMD_DIFF], "Show how many lines each repository's working tree has changed since HEAD"),
+    (&[CMD_DIFFSTAT], "Show how many lines each repository's current branch would ship to its upstream"),
+    (&[CMD_DOCTOR], "Check git support, credentials, the manifest and proxy settings for common problems"),
+    (&[CMD_EXEC], "Run a command in every repository, prefixing its output with the repository's path"),
+    (&[CMD_FETCH], "Fetch every remote of every repository without touching the working trees"),
+    (&[CMD_FIND_COMMIT], "Search commit messages across all repositories"),
+    (&[CMD_FSCK], "Check each repository's object database for corrupt, missing and dangling objects"),
+    (&[CMD_GC], "Garbage collect every repository and report the space reclaimed"),
+    (&[CMD_GREP], "Search the tracked files of every repository"),
+    (&[CMD_HOOKS], "Manage the git hooks of every repository"),
+    (&[CMD_HOOKS, CMD_INSTALL], "Copy a shared hooks directory into every repository's hooks"),
+    (&[CMD_INIT], "Start a new repository under the root and add it to the manifest"),
+    (&[CMD_LFS], "Find Git LFS content missing from each repository's working tree"),
+    (&[CMD_LFS, CMD_STATUS], "Count LFS files that haven't been fetched or checked out"),
+    (&[CMD_LFS, CMD_PULL], "Run git lfs pull in every repository with LFS files not checked out"),
+    (&[CMD_LOG], "Show recent commits from every repository as one timeline, newest first"),
+    (&[CMD_MAN], "Generates man pages from the command line definitions"),
+    (&[CMD_MANIFEST], "Inspect or generate manifest files"),
+    (&[CMD_MANIFEST, CMD_ADD], "List one repository in the manifest without searching the whole root"),
+    (&[CMD_MANIFEST, CMD_CLEAN], "Delete manifest if it exists"),
+    (&[CMD_MANIFEST, CMD_EXPORT],
+     "Write the manifest's entries and their origins, without this machine's root, to a file"),
+    (&[CMD_MANIFEST, CMD_IGNORE], "Skip repositories matching a glob, found or listed; shows the globs without one"),
+    (&[CMD_MANIFEST, CMD_IMPORT],
+     "Add an exported manifest's entries under this root, then clone whichever are missing"),
+    (&[CMD_MANIFEST, CMD_LIST], "List the default manifest and every named profile, with their roots"),
+    (&[CMD_MANIFEST, CMD_MERGE],
+     "Add the entries of another manifest or export, keeping this one's origins where they differ"),
+    (&[CMD_MANIFEST, CMD_PREVIEW], "Preview the repositories that will be present in a manifest"),
+    (&[CMD_MANIFEST, CMD_PRUNE], "Drop entries that are no longer a repository on disk"),
+    (&[CMD_MANIFEST, CMD_REMOVE], "Drop a repository from the manifest, leaving it on disk"),
+    (&[CMD_MANIFEST, CMD_RESTORE], "Clone every repository in the manifest that's missing from disk"),
+    (&[CMD_MANIFEST, CMD_TAG], "Add a listed repository to a group for --group; shows its groups without one"),
+    (&[CMD_MANIFEST, CMD_UPDATE], "Update or generate manifest file"),
+    (&[CMD_MANIFEST, CMD_VERIFY], "Check the manifest parses and every entry is a distinct repository; fails if not"),
+    (&[CMD_MERGE_UPSTREAM], "Merge each repository's fetched upstream into its current branch"),
+    (&[CMD_MV], "Move a repository within the root and update its manifest entry"),
+    (&[CMD_PRUNE], "Remove remote-tracking branches whose branch was deleted on the remote"),
+    (&[CMD_PULL], "Fetch and fast-forward every repository's current branch"),
+    (&[CMD_PUSH], "Push every repository's current branch to its upstream when it fast-forwards"),
+    (&[CMD_REBASE], "Rebase each repository's current branch onto its fetched upstream, skipping dirty ones"),
+    (&[CMD_REMOTE], "List every repository's remotes with their fetch and push URLs"),
+    (&[CMD_REMOTE, CMD_SET_URL], "Rewrite remote URLs across all repositories, e.g. when a git server moves"),
+    (&[CMD_RESET], "Recursive hard reset"),
+    (&[CMD_RM], "Delete a repository from disk and the manifest, unless it has unpushed work"),
+    (&[CMD_SELF_UPDATE], "Replace this executable with the latest release build for this platform"),
+    (&[CMD_SIZES], "Report working tree, .git and largest blob sizes per repo"),
+    (&[CMD_SPARSE], "Apply or audit sparse-checkout patterns across repositories"),
+    (&[CMD_SPARSE, CMD_SET], "Check out only what a patterns file matches in every repository"),
+    (&[CMD_SPARSE, CMD_STATUS], "Show which repositories are sparse and how much of each is checked out"),
+    (&[CMD_STANDUP], "List your recent commits across all repositories, grouped by repository"),
+    (&[CMD_STASH], "Stash uncommitted changes in every repository that has them"),
+    (&[CMD_STASH, CMD_LIST], "Show how many stashes each repository has"),
+    (&[CMD_STASH, CMD_POP], "Pop the most recent stash this tool made in each repository"),
+    (&[CMD_STATS], "Report commits, lines changed, active branches and last activity per repository over a period"),
+    (&[CMD_STATUS], "Recursive directory search version of git status"),
+    (&[CMD_SUBMODULE], "Work with the submodules of every repository"),
+    (&[CMD_SUBMODULE, CMD_UPDATE], "Initialise submodules and check out the commits their parents record"),
+    (&[CMD_TAG], "Tag HEAD in every repository"),
+    (&[CMD_TUI], "Interactive dashboard of repository status"),
+    (&[CMD_UNSHALLOW], "Fetch the full history of every shallow clone"),
+    (&[CMD_VERSION], "Prints version information"),
+];
+
+// Every visible argument, under the path of the command it belongs to, in
+// the order they're defined below; the root's are global. The builder makes
+// each one through param, and nushell completions are generated from this
+// rather than from clap's parser internals.
+pub const PARAMS: &[Param] = &[
+    Param::value(&[], ROOT, "Workspace to operate on instead of the current directory; repeat to combine several")
+        .multiple(),
+    Param::value(&[], REPO, "Only operate on this repository, by path relative to the root or by name")
+        .short(REPO_SHORT).multiple(),
+    Param::value(&[], GROUP, "Only operate on repositories the manifest has tagged with this group; repeat for several")
+        .multiple(),
+    Param::value(&[], PROFILE, "Use this named manifest, and the root it was made for, instead of the default one"),
+    Param::positional(&[CMD_ADD], PATHSPEC,
+                      "Files to stage, e.g. '.github/workflows/*'; quote globs so the shell leaves them alone")
+        .required().multiple(),
+    Param::positional(&[CMD_APPLY], PATCH, "A unified diff, as git diff or format-patch writes them").required(),
+    Param::flag(&[CMD_APPLY], DRY_RUN, "Only check where it would apply cleanly").short("n"),
+    Param::value(&[CMD_ARCHIVE], OUTPUT, "Directory to write the archives to; created if needed").short("o").required(),
+    Param::value(&[CMD_ARCHIVE], FORMAT, "Archive format").values(archive::FORMATS),
+    Param::value(&[CMD_AUDIT, CMD_HOOKS], REQUIRE, "Hooks every repository must have, e.g. pre-commit,commit-msg")
+        .required(),
+    Param::value(&[CMD_AUDIT, CMD_IDENTITY], EXPECT,
+                 "Flag emails that don't match this pattern, e.g. \"*@example.com\""),
+    Param::value(&[CMD_AUDIT, CMD_IDENTITY], REMOTE,
+                 "Only expect the pattern where the origin URL contains this, e.g. github.com/example"),
+    Param::value(&[CMD_BIGFILES], MIN_SIZE, "Only list blobs at least this big"),
+    Param::positional(&[CMD_BRANCH, CMD_CREATE], BRANCH, "The branch to create").required(),
+    Param::value(&[CMD_BRANCH, CMD_CREATE], FROM, "Start it from this ref instead of HEAD"),
+    Param::flag(&[CMD_BRANCH, CMD_CREATE], CHECKOUT, "Check out the new branch as well").short("c"),
+    Param::positional(&[CMD_BRANCH, CMD_DELETE], BRANCH, "The branch to delete").required(),
+    Param::flag(&[CMD_BRANCH, CMD_DELETE], FORCE, "Delete it even where it isn't fully merged").short("f"),
+    Param::positional(&[CMD_CHECKOUT], BRANCH, "Branch name"),
+    Param::flag(&[CMD_CHECKOUT], DEFAULT, "Check out each repository's default branch, as origin/HEAD names it"),
+    Param::flag(&[CMD_CHECKOUT], INTERACTIVE, INTERACTIVE_HELP).short("i"),
+    Param::value(&[CMD_BUNDLE], OUTPUT, "Directory to write the bundles to; created if needed").short("o").required(),
+    Param::positional(&[CMD_CHERRY_PICK], COMMIT,
+                      "The commit to pick, or a file of \"<repo path> <commit>\" lines to pick per repository")
+        .required(),
+    Param::flag(&[CMD_CLEAN], DRY_RUN, "List what would be removed without removing it").short("n"),
+    Param::flag(&[CMD_CLEAN], IGNORED, "Also remove ignored files, such as build output").short("x"),
+    Param::flag(&[CMD_CLEAN], INTERACTIVE, INTERACTIVE_HELP).short("i"),
+    Param::positional(&[CMD_CLONE], URL, "Repository to clone"),
+    Param::positional(&[CMD_CLONE], PATH, "Where to put it, relative to the root; defaults to owner/name from the URL"),
+    Param::value(&[CMD_CLONE], FROM_BUNDLES, "Restore every bundle written by bundle under this directory instead"),
+    Param::value(&[CMD_CLONE], TEMPLATE, TEMPLATE_HELP),
+    Param::value(&[CMD_CLONE], EMAIL, "user.email to commit with in the new clone"),
+    Param::value(&[CMD_CLONE], DEPTH, "Only fetch this many commits of history; see unshallow for the rest"),
+    Param::value(&[CMD_COMMIT], MESSAGE, "The commit message, used in every repository").short("m").required(),
+    Param::flag(&[CMD_COMMIT], DRY_RUN, "List the repositories that would commit without committing").short("n"),
+    Param::positional(&[CMD_COMPLETIONS], SHELL, "The shell to generate the script for").required().values(SHELLS),
+    Param::positional(&[CMD_CONFIG, CMD_GET], KEY, "The config key, e.g. user.email").required(),
+    Param::positional(&[CMD_CONFIG, CMD_SET], KEY, "The config key, e.g. user.email").required(),
+    Param::positional(&[CMD_CONFIG, CMD_SET], VALUE, "The value to write").required(),
+    Param::positional(&[CMD_CONTAINS], REV, "Commit hash or tag to look for").required(),
+    Param::value(&[CMD_CONTRIBUTORS], SINCE, "Only count commits since this date, e.g. 2017-08-03, yesterday or 2w"),
+    Param::flag(&[CMD_CONTRIBUTORS], BY_REPO, "Break the counts down per repository"),
+    Param::value(&[CMD_DIFFSTAT], BASE, "Compare against this branch in every repository instead of each upstream"),
+    Param::positional(&[CMD_EXEC], COMMAND, "The command and its arguments, after --").required().multiple(),
+    Param::flag(&[CMD_FETCH], PRUNE, "Remove remote-tracking branches whose branch was deleted on the remote"),
+    Param::flag(&[CMD_FETCH], PRUNE_TAGS, "Also remove local tags the remote no longer has; implies --prune"),
+    Param::value(&[CMD_FIND_COMMIT], GREP, "Text to look for in commit messages").required(),
+    Param::flag(&[CMD_FIND_COMMIT], IGNORE_CASE, "Match regardless of case").short("i"),
+    Param::value(&[CMD_FIND_COMMIT], SINCE, "Only search commits since this date"),
+    Param::value(&[CMD_FIND_COMMIT], UNTIL, "Only search commits up to this date"),
+    Param::value(&[CMD_FIND_COMMIT], RANGE,
+                 "Only search this ref or range, e.g. release or v1.0..master, instead of every local branch"),
+    Param::flag(&[CMD_GC], AGGRESSIVE, "Also expire all reflogs and repack from scratch; slow, but reclaims the most"),
+    Param::positional(&[CMD_GREP], PATTERN, "Pattern to look for, as git grep takes it").required(),
+    Param::flag(&[CMD_GREP], IGNORE_CASE, "Match regardless of case").short("i"),
+    Param::positional(&[CMD_HOOKS, CMD_INSTALL], DIR, "Directory of hooks, named the way git expects, e.g. pre-commit")
+        .required(),
+    Param::flag(&[CMD_HOOKS, CMD_INSTALL], SYMLINK,
+                "Link to the shared hooks instead of copying them, so later edits apply everywhere"),
+    Param::flag(&[CMD_HOOKS, CMD_INSTALL], HOOKS_PATH,
+                "Point core.hooksPath at the directory instead of touching .git/hooks"),
+    Param::flag(&[CMD_HOOKS, CMD_INSTALL], FORCE, "Replace hooks that are already there but differ").short("f"),
+    Param::positional(&[CMD_INIT], NAME, "Directory to create, relative to the root").required(),
+    Param::value(&[CMD_INIT], INITIAL_BRANCH, "Branch to start on; defaults to init.defaultBranch, then master")
+        .short("b"),
+    Param::value(&[CMD_INIT], TEMPLATE, TEMPLATE_HELP),
+    Param::value(&[CMD_LOG], SINCE, "Only show commits since this date, e.g. 2017-08-03, yesterday or 2w"),
+    Param::positional(&[CMD_MAN], COMMAND, "Subcommand to print the page for, e.g. \"manifest clean\"").multiple(),
+    Param::value(&[CMD_MAN], OUT_DIR, "Write a page for every command into this directory").short("o"),
+    Param::positional(&[CMD_MANIFEST, CMD_ADD], PATH, "Repository to add, relative to the root").required(),
+    Param::positional(&[CMD_MANIFEST, CMD_EXPORT], FILE,
+                      "Where to write it; as gitplz.manifest at the top of a workspace, it's used there instead")
+        .required(),
+    Param::value(&[CMD_MANIFEST, CMD_EXPORT], FORMAT,
+                 "Defaults to the file's extension: .toml, .yaml or .yml, otherwise json")
+        .values(util::MANIFEST_FORMATS),
+    Param::positional(&[CMD_MANIFEST, CMD_IGNORE], GLOB,
+                      "Path relative to the root, e.g. '**/node_modules/**'; quote it for the shell"),
+    Param::flag(&[CMD_MANIFEST, CMD_IGNORE], REMOVE, "Stop excluding the glob instead"),
+    Param::positional(&[CMD_MANIFEST, CMD_IMPORT], FILE, "A file written by manifest export").required(),
+    Param::value(&[CMD_MANIFEST, CMD_IMPORT], DEPTH, "Make shallow clones with just this many commits of history"),
+    Param::positional(&[CMD_MANIFEST, CMD_MERGE], FILE, "The other manifest, e.g. copied from another machine")
+        .required(),
+    Param::flag(&[CMD_MANIFEST, CMD_PRUNE], DRY_RUN, "Only report what would be dropped").short("n"),
+    Param::positional(&[CMD_MANIFEST, CMD_REMOVE], PATH, "Repository to remove, relative to the root").required(),
+    Param::value(&[CMD_MANIFEST, CMD_RESTORE], DEPTH, "Make shallow clones with just this many commits of history"),
+    Param::positional(&[CMD_MANIFEST, CMD_TAG], PATH, "Repository to tag, relative to the root").required(),
+    Param::positional(&[CMD_MANIFEST, CMD_TAG], NAME, "The group, e.g. backend"),
+    Param::flag(&[CMD_MANIFEST, CMD_TAG], REMOVE, "Take it out of the group instead"),
+    Param::flag(&[CMD_MERGE_UPSTREAM], NO_FF, "Always record a merge commit instead of only fast-forwarding"),
+    Param::positional(&[CMD_MV], FROM, "Repository to move, relative to the root").required(),
+    Param::positional(&[CMD_MV], TO, "Where to move it, relative to the root").required(),
+    Param::flag(&[CMD_PULL], REBASE, "Rebase local commits onto the upstream instead of skipping diverged branches"),
+    Param::flag(&[CMD_PULL], AUTOSTASH, "Stash local changes before rebasing and reapply them after"),
+    Param::value(&[CMD_REMOTE, CMD_SET_URL], REWRITE, "Replace this prefix of fetch and push URLs with another")
+        .required(),
+    Param::flag(&[CMD_REMOTE, CMD_SET_URL], DRY_RUN, "Show what would be rewritten without changing anything")
+        .short("n"),
+    Param::flag(&[CMD_RESET], INTERACTIVE, INTERACTIVE_HELP).short("i"),
+    Param::positional(&[CMD_RM], PATH, "Repository to remove, relative to the working directory").required(),
+    Param::flag(&[CMD_RM], FORCE, "Remove it even with uncommitted, stashed or unpushed work").short("f"),
+    Param::flag(&[CMD_SELF_UPDATE], CHECK, "Only report whether a newer release exists"),
+    Param::value(&[CMD_SIZES], SORT, "What to order repositories by; sizes sort biggest first").values(sizes::SORTS),
+    Param::value(&[CMD_SIZES], BLOBS, "How many of the largest blobs to list per repository"),
+    Param::positional(&[CMD_SPARSE, CMD_SET], PATTERNS, "File of patterns, one per line; directories with --cone")
+        .required(),
+    Param::flag(&[CMD_SPARSE, CMD_SET], CONE,
+                "Treat the patterns as directories to include, which git matches much faster"),
+    Param::positional(&[CMD_SPARSE, CMD_STATUS], PATTERNS, "Flag repositories whose patterns differ from this file's"),
+    Param::value(&[CMD_STANDUP], SINCE, "Only show commits since this date"),
+    Param::value(&[CMD_STANDUP], AUTHOR, "Name or email to match; \"me\" is whoever user.email is in each repository"),
+    Param::value(&[CMD_STATS], SINCE, "Start of the period"),
+    Param::flag(&[CMD_STATS], JSON, "Print the report as JSON"),
+    Param::flag(&[CMD_STATUS], CACHED, "Print the status saved by the last run instead of reading every repository"),
+    Param::flag(&[CMD_STATUS], LAST_COMMIT,
+                "List every repository with the hash, date, author and subject of its last commit").short("l"),
+    Param::flag(&[CMD_STATUS], OFF_DEFAULT, "Only list repositories that aren't on their default branch"),
+    Param::flag(&[CMD_STATUS], SHORT, "Only show each repository's summary line, not the files in it").short("s"),
+    Param::value(&[CMD_STATUS], STALE,
+                 "Only list repositories with uncommitted changes untouched for more than this many days"),
+    Param::flag(&[CMD_STATUS], REFRESH, "Update the saved status in the background after printing it"),
+    Param::flag(&[CMD_STATUS], WATCH, "Keep running and refresh repositories as their files change"),
+    Param::flag(&[CMD_SUBMODULE, CMD_UPDATE], RECURSIVE, "Also update submodules inside submodules"),
+    Param::positional(&[CMD_TAG], NAME, "The tag to create").required(),
+    Param::value(&[CMD_TAG], MESSAGE, "Make an annotated tag with this message").short("m"),
+    Param::flag(&[CMD_VERSION], VERBOSE, "Also show the build's commit, target, and the libgit2 and git it runs with")
+        .short("v"),
+];
+
+const INTERACTIVE_HELP: &str = "Show what would change in each repository and ask before touching it";
+const TEMPLATE_HELP: &str = "Template directory to copy hooks and other files from, as with git's --template";
+
+pub const SHELLS: &[&str] = &["zsh", "bash", "fish", "powershell", "elvish", completions::NUSHELL];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamKind {
+    Positional,
+    Flag,
+    // An option that takes a value.
+    Value,
+}
+
+// Only what completions need; the builder adds validators, defaults and how
+// arguments relate to each other.
+#[derive(Debug)]
+pub struct Param {
+    pub command: &'static [&'static str],
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub short: Option<&'static str>,
+    pub required: bool,
+    pub multiple: bool,
+    pub values: &'static [&'static str],
+    pub help: &'static str,
+}
+
+impl Param {
+    const fn new(command: &'static [&'static str], name: &'static str, kind: ParamKind, help: &'static str) -> Self {
+        Param {
+            command: command,
+            name: name,
+            kind: kind,
+            short: None,
+            required: false,
+            multiple: false,
+            values: &[],
+            help: help,
+        }
+    }
+
+    const fn positional(command: &'static [&'static str], name: &'static str, help: &'static str) -> Self {
+        Param::new(command, name, ParamKind::Positional, help)
+    }
+
+    const fn flag(command: &'static [&'static str], name: &'static str, help: &'static str) -> Self {
+        Param::new(command, name, ParamKind::Flag, help)
+    }
+
+    const fn value(command: &'static [&'static str], name: &'static str, help: &'static str) -> Self {
+        Param::new(command, name, ParamKind::Value, help)
+    }
+
+    const fn short(mut self, short: &'static str) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    const fn multiple(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    const fn values(mut self, values: &'static [&'static str]) -> Self {
+        self.values = values;
+        self
+    }
+}
 
 pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    build_visible_cli()
+        .subcommand(SubCommand::with_name(CMD_COMPLETE_BRANCHES)
+            .setting(AppSettings::Hidden)
+            .about("List branch names across repositories for shell completion"))
+        .subcommand(SubCommand::with_name(CMD_COMPLETE_REPOS)
+            .setting(AppSettings::Hidden)
+            .about("List manifest repositories for shell completion"))
+}
+
+// Everything but the completion helpers, whose names clap's script
+// generators can't handle and which never need completing themselves.
+pub fn build_visible_cli<'a, 'b>() -> App<'a, 'b> {
     App::new("Git, please")
         .bin_name(APP_NAME)
-        .version("0.1")
+        .version(VERSION)
         .author("Kyle Gretchev")
-        .about("Run commands on a set of git repositories in a folder tree")
-        .subcommand(SubCommand::with_name(CMD_CHECKOUT)
-            .about("Checkout branch across repos")
-            .arg(Arg::with_name(BRANCH)
-                .required(true)
-                .help("Branch name")))
-        .subcommand(SubCommand::with_name(CMD_COMPLETIONS)
-            .about("Generates completion scripts for your shell")
-            .arg(Arg::with_name(SHELL)
-                .required(true)
-                .possible_values(&Shell::variants())
-                .help("The shell to generate the script for")))
-        .subcommand(SubCommand::with_name(CMD_MANIFEST)
-            .about("Inspect or generate manifest files")
-            .subcommand(SubCommand::with_name(CMD_CLEAN)
-                .about("Delete manifest if it exists"))
-            .subcommand(SubCommand::with_name(CMD_PREVIEW)
-                .about("Preview the repositories that will be present in a manifest"))
-            .subcommand(SubCommand::with_name(CMD_UPDATE)
-                .about("Update or generate manifest file")))
-        .subcommand(SubCommand::with_name(CMD_RESET)
-            .about("Recursive hard reset"))
-        .subcommand(SubCommand::with_name(CMD_STATUS)
-            .about("Recursive directory search version of git status"))
+        .about(ABOUT)
+        .arg(param(&[], ROOT)
+            .value_name("PATH")
+            .number_of_values(1)
+            .validator(is_dir))
+        .arg(param(&[], REPO)
+            .number_of_values(1))
+        .arg(param(&[], GROUP)
+            .value_name("NAME")
+            .number_of_values(1))
+        .arg(param(&[], PROFILE)
+            .value_name("NAME")
+            .validator(is_profile_name))
+        .subcommand(command(&[CMD_ADD])
+            .arg(param(&[CMD_ADD], PATHSPEC)))
+        .subcommand(command(&[CMD_APPLY])
+            .arg(param(&[CMD_APPLY], PATCH))
+            .arg(param(&[CMD_APPLY], DRY_RUN)))
+        .subcommand(command(&[CMD_ARCHIVE])
+            .arg(param(&[CMD_ARCHIVE], OUTPUT))
+            .arg(param(&[CMD_ARCHIVE], FORMAT)
+                .default_value("tar.gz")))
+        .subcommand(command(&[CMD_AUDIT])
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(command(&[CMD_AUDIT, CMD_CONFLICTS]))
+            .subcommand(command(&[CMD_AUDIT, CMD_DETACHED]))
+            .subcommand(command(&[CMD_AUDIT, CMD_DIVERGED]))
+            .subcommand(command(&[CMD_AUDIT, CMD_DUPLICATES]))
+            .subcommand(command(&[CMD_AUDIT, CMD_HOOKS])
+                .arg(param(&[CMD_AUDIT, CMD_HOOKS], REQUIRE)
+                    .use_delimiter(true)
+                    .value_name("HOOKS")))
+            .subcommand(command(&[CMD_AUDIT, CMD_IDENTITY])
+                .arg(param(&[CMD_AUDIT, CMD_IDENTITY], EXPECT)
+                    .value_name("PATTERN"))
+                .arg(param(&[CMD_AUDIT, CMD_IDENTITY], REMOTE)
+                    .requires(EXPECT)))
+            .subcommand(command(&[CMD_AUDIT, CMD_LFS]))
+            .subcommand(command(&[CMD_AUDIT, CMD_NO_REMOTE]))
+            .subcommand(command(&[CMD_AUDIT, CMD_REACHABILITY]))
+            .subcommand(command(&[CMD_AUDIT, CMD_STALE]))
+            .subcommand(command(&[CMD_AUDIT, CMD_UNPUSHED])))
+        .subcommand(command(&[CMD_BIGFILES])
+            .arg(param(&[CMD_BIGFILES], MIN_SIZE)
+                .value_name("BYTES")
+                .default_value("1048576")
+                .validator(is_size)))
+        .subcommand(command(&[CMD_BRANCH])
+            .subcommand(command(&[CMD_BRANCH, CMD_CREATE])
+                .arg(param(&[CMD_BRANCH, CMD_CREATE], BRANCH))
+                .arg(param(&[CMD_BRANCH, CMD_CREATE], FROM))
+                .arg(param(&[CMD_BRANCH, CMD_CREATE], CHECKOUT)))
+            .subcommand(command(&[CMD_BRANCH, CMD_DELETE])
+                .arg(param(&[CMD_BRANCH, CMD_DELETE], BRANCH))
+                .arg(param(&[CMD_BRANCH, CMD_DELETE], FORCE))))
+        .subcommand(command(&[CMD_CHECKOUT])
+            .arg(param(&[CMD_CHECKOUT], BRANCH)
+                .required_unless(DEFAULT)
+                .conflicts_with(DEFAULT))
+            .arg(param(&[CMD_CHECKOUT], DEFAULT))
+            .arg(param(&[CMD_CHECKOUT], INTERACTIVE)))
+        .subcommand(command(&[CMD_BUNDLE])
+            .arg(param(&[CMD_BUNDLE], OUTPUT)))
+        .subcommand(command(&[CMD_CHERRY_PICK])
+            .arg(param(&[CMD_CHERRY_PICK], COMMIT)))
+        .subcommand(command(&[CMD_CLEAN])
+            .arg(param(&[CMD_CLEAN], DRY_RUN))
+            .arg(param(&[CMD_CLEAN], IGNORED))
+            .arg(param(&[CMD_CLEAN], INTERACTIVE)
+                .conflicts_with(DRY_RUN)))
+        .subcommand(command(&[CMD_CLONE])
+            .arg(param(&[CMD_CLONE], URL)
+                .required_unless(FROM_BUNDLES))
+            .arg(param(&[CMD_CLONE], PATH))
+            .arg(param(&[CMD_CLONE], FROM_BUNDLES)
+                .value_name("DIR")
+                .conflicts_with_all(&[URL, PATH, TEMPLATE, DEPTH]))
+            .arg(param(&[CMD_CLONE], TEMPLATE)
+                .value_name("DIR")
+                .validator(is_dir))
+            .arg(param(&[CMD_CLONE], EMAIL))
+            .arg(param(&[CMD_CLONE], DEPTH)
+                .validator(is_number)))
+        .subcommand(command(&[CMD_COMMIT])
+            .arg(param(&[CMD_COMMIT], MESSAGE))
+            .arg(param(&[CMD_COMMIT], DRY_RUN)))
+        .subcommand(command(&[CMD_COMPLETIONS])
+            .arg(param(&[CMD_COMPLETIONS], SHELL)))
+        .subcommand(command(&[CMD_CONFIG])
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(command(&[CMD_CONFIG, CMD_GET])
+                .arg(param(&[CMD_CONFIG, CMD_GET], KEY)))
+            .subcommand(command(&[CMD_CONFIG, CMD_SET])
+                .arg(param(&[CMD_CONFIG, CMD_SET], KEY))
+                .arg(param(&[CMD_CONFIG, CMD_SET], VALUE))))
+        .subcommand(command(&[CMD_CONTAINS])
+            .arg(param(&[CMD_CONTAINS], REV)))
+        .subcommand(command(&[CMD_CONTRIBUTORS])
+            .visible_alias(CMD_AUTHORS)
+            .arg(param(&[CMD_CONTRIBUTORS], SINCE)
+                .validator(is_date))
+            .arg(param(&[CMD_CONTRIBUTORS], BY_REPO)))
+        .subcommand(command(&[CMD_DIFF]))
+        .subcommand(command(&[CMD_DIFFSTAT])
+            .arg(param(&[CMD_DIFFSTAT], BASE)))
+        .subcommand(command(&[CMD_DOCTOR]))
+        .subcommand(command(&[CMD_EXEC])
+            .arg(param(&[CMD_EXEC], COMMAND)
+                .last(true)))
+        .subcommand(command(&[CMD_FETCH])
+            .arg(param(&[CMD_FETCH], PRUNE))
+            .arg(param(&[CMD_FETCH], PRUNE_TAGS)))
+        .subcommand(command(&[CMD_FIND_COMMIT])
+            .arg(param(&[CMD_FIND_COMMIT], GREP))
+            .arg(param(&[CMD_FIND_COMMIT], IGNORE_CASE))
+            .arg(param(&[CMD_FIND_COMMIT], SINCE)
+                .validator(is_date))
+            .arg(param(&[CMD_FIND_COMMIT], UNTIL)
+                .validator(is_date))
+            .arg(param(&[CMD_FIND_COMMIT], RANGE)))
+        .subcommand(command(&[CMD_FSCK]))
+        .subcommand(command(&[CMD_GC])
+            .arg(param(&[CMD_GC], AGGRESSIVE)))
+        .subcommand(command(&[CMD_GREP])
+            .arg(param(&[CMD_GREP], PATTERN))
+            .arg(param(&[CMD_GREP], IGNORE_CASE)))
+        .subcommand(command(&[CMD_HOOKS])
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(command(&[CMD_HOOKS, CMD_INSTALL])
+                .arg(param(&[CMD_HOOKS, CMD_INSTALL], DIR))
+                .arg(param(&[CMD_HOOKS, CMD_INSTALL], SYMLINK)
+                    .conflicts_with(HOOKS_PATH))
+                .arg(param(&[CMD_HOOKS, CMD_INSTALL], HOOKS_PATH))
+                .arg(param(&[CMD_HOOKS, CMD_INSTALL], FORCE))))
+        .subcommand(command(&[CMD_INIT])
+            .arg(param(&[CMD_INIT], NAME))
+            .arg(param(&[CMD_INIT], INITIAL_BRANCH)
+                .value_name("BRANCH"))
+            .arg(param(&[CMD_INIT], TEMPLATE)
+                .value_name("DIR")
+                .validator(is_dir)))
+        .subcommand(command(&[CMD_LFS])
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(command(&[CMD_LFS, CMD_STATUS]))
+            .subcommand(command(&[CMD_LFS, CMD_PULL])))
+        .subcommand(command(&[CMD_LOG])
+            .arg(param(&[CMD_LOG], SINCE)
+                .default_value("1w")
+                .validator(is_date)))
+        .subcommand(command(&[CMD_MAN])
+            .arg(param(&[CMD_MAN], COMMAND))
+            .arg(param(&[CMD_MAN], OUT_DIR)
+                .conflicts_with(COMMAND)))
+        .subcommand(command(&[CMD_MANIFEST])
+            .subcommand(command(&[CMD_MANIFEST, CMD_ADD])
+                .arg(param(&[CMD_MANIFEST, CMD_ADD], PATH)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_CLEAN]))
+            .subcommand(command(&[CMD_MANIFEST, CMD_EXPORT])
+                .arg(param(&[CMD_MANIFEST, CMD_EXPORT], FILE))
+                .arg(param(&[CMD_MANIFEST, CMD_EXPORT], FORMAT)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_IGNORE])
+                .arg(param(&[CMD_MANIFEST, CMD_IGNORE], GLOB))
+                .arg(param(&[CMD_MANIFEST, CMD_IGNORE], REMOVE)
+                    .requires(GLOB)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_IMPORT])
+                .arg(param(&[CMD_MANIFEST, CMD_IMPORT], FILE))
+                .arg(param(&[CMD_MANIFEST, CMD_IMPORT], DEPTH)
+                    .validator(is_number)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_LIST]))
+            .subcommand(command(&[CMD_MANIFEST, CMD_MERGE])
+                .arg(param(&[CMD_MANIFEST, CMD_MERGE], FILE)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_PREVIEW]))
+            .subcommand(command(&[CMD_MANIFEST, CMD_PRUNE])
+                .arg(param(&[CMD_MANIFEST, CMD_PRUNE], DRY_RUN)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_REMOVE])
+                .arg(param(&[CMD_MANIFEST, CMD_REMOVE], PATH)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_RESTORE])
+                .arg(param(&[CMD_MANIFEST, CMD_RESTORE], DEPTH)
+                    .validator(is_number)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_TAG])
+                .arg(param(&[CMD_MANIFEST, CMD_TAG], PATH))
+                .arg(param(&[CMD_MANIFEST, CMD_TAG], NAME))
+                .arg(param(&[CMD_MANIFEST, CMD_TAG], REMOVE)
+                    .requires(NAME)))
+            .subcommand(command(&[CMD_MANIFEST, CMD_UPDATE]))
+            .subcommand(command(&[CMD_MANIFEST, CMD_VERIFY])))
+        .subcommand(command(&[CMD_MERGE_UPSTREAM])
+            .arg(param(&[CMD_MERGE_UPSTREAM], NO_FF)))
+        .subcommand(command(&[CMD_MV])
+            .arg(param(&[CMD_MV], FROM))
+            .arg(param(&[CMD_MV], TO)))
+        .subcommand(command(&[CMD_PRUNE]))
+        .subcommand(command(&[CMD_PULL])
+            .arg(param(&[CMD_PULL], REBASE))
+            .arg(param(&[CMD_PULL], AUTOSTASH)
+                .requires(REBASE)))
+        .subcommand(command(&[CMD_PUSH]))
+        .subcommand(command(&[CMD_REBASE]))
+        .subcommand(command(&[CMD_REMOTE])
+            .subcommand(command(&[CMD_REMOTE, CMD_SET_URL])
+                .arg(param(&[CMD_REMOTE, CMD_SET_URL], REWRITE)
+                    .number_of_values(2)
+                    .value_names(&["from-prefix", "to-prefix"]))
+                .arg(param(&[CMD_REMOTE, CMD_SET_URL], DRY_RUN))))
+        .subcommand(command(&[CMD_RESET])
+            .arg(param(&[CMD_RESET], INTERACTIVE)))
+        .subcommand(command(&[CMD_RM])
+            .arg(param(&[CMD_RM], PATH))
+            .arg(param(&[CMD_RM], FORCE)))
+        .subcommand(command(&[CMD_SELF_UPDATE])
+            .arg(param(&[CMD_SELF_UPDATE], CHECK)))
+        .subcommand(command(&[CMD_SIZES])
+            .arg(param(&[CMD_SIZES], SORT)
+                .case_insensitive(true)
+                .default_value("Name"))
+            .arg(param(&[CMD_SIZES], BLOBS)
+                .default_value("3")
+                .validator(is_number)))
+        .subcommand(command(&[CMD_SPARSE])
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(command(&[CMD_SPARSE, CMD_SET])
+                .arg(param(&[CMD_SPARSE, CMD_SET], PATTERNS))
+                .arg(param(&[CMD_SPARSE, CMD_SET], CONE)))
+            .subcommand(command(&[CMD_SPARSE, CMD_STATUS])
+                .arg(param(&[CMD_SPARSE, CMD_STATUS], PATTERNS))))
+        .subcommand(command(&[CMD_STANDUP])
+            .arg(param(&[CMD_STANDUP], SINCE)
+                .default_value("yesterday")
+                .validator(is_date))
+            .arg(param(&[CMD_STANDUP], AUTHOR)
+                .default_value(standup::ME)))
+        .subcommand(command(&[CMD_STASH])
+            .subcommand(command(&[CMD_STASH, CMD_LIST]))
+            .subcommand(command(&[CMD_STASH, CMD_POP])))
+        .subcommand(command(&[CMD_STATS])
+            .arg(param(&[CMD_STATS], SINCE)
+                .default_value("1w")
+                .validator(is_date))
+            .arg(param(&[CMD_STATS], JSON)))
+        .subcommand(command(&[CMD_STATUS])
+            .arg(param(&[CMD_STATUS], CACHED)
+                .conflicts_with(WATCH))
+            .arg(param(&[CMD_STATUS], LAST_COMMIT))
+            .arg(param(&[CMD_STATUS], OFF_DEFAULT)
+                .conflicts_with_all(&[CACHED, WATCH]))
+            .arg(param(&[CMD_STATUS], SHORT))
+            .arg(param(&[CMD_STATUS], STALE)
+                .value_name("DAYS")
+                .validator(is_number)
+                .conflicts_with_all(&[CACHED, WATCH]))
+            .arg(param(&[CMD_STATUS], REFRESH)
+                .requires(CACHED))
+            .arg(param(&[CMD_STATUS], WATCH)))
+        .subcommand(command(&[CMD_SUBMODULE])
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(command(&[CMD_SUBMODULE, CMD_UPDATE])
+                .arg(param(&[CMD_SUBMODULE, CMD_UPDATE], RECURSIVE))))
+        .subcommand(command(&[CMD_TAG])
+            .arg(param(&[CMD_TAG], NAME))
+            .arg(param(&[CMD_TAG], MESSAGE)))
+        .subcommand(command(&[CMD_TUI]))
+        .subcommand(command(&[CMD_UNSHALLOW]))
+        .subcommand(command(&[CMD_VERSION])
+            .arg(param(&[CMD_VERSION], VERBOSE)))
+}
+
+pub fn about(path: &[&str]) -> Option<&'static str> {
+    COMMANDS.iter().find(|c| c.0 == path).map(|c| c.1)
+}
+
+fn command<'a, 'b>(path: &[&'a str]) -> App<'a, 'b> {
+    let name = path[path.len() - 1];
+    SubCommand::with_name(name).about(about(path).expect("Every command is listed in COMMANDS"))
+}
+
+fn param<'a, 'b>(command: &[&str], name: &str) -> Arg<'a, 'b> {
+    let param = PARAMS
+        .iter()
+        .find(|p| p.command == command && p.name == name)
+        .expect("Every argument is listed in PARAMS");
+
+    let mut arg = Arg::with_name(param.name)
+        .required(param.required)
+        .multiple(param.multiple)
+        .global(param.command.is_empty())
+        .help(param.help);
+
+    if param.kind != ParamKind::Positional {
+        arg = arg.long(param.name).takes_value(param.kind == ParamKind::Value);
+    }
+
+    if let Some(short) = param.short {
+        arg = arg.short(short);
+    }
+
+    match param.values.is_empty() {
+        true => arg,
+        false => arg.possible_values(param.values),
+    }
+}
+
+fn is_number(value: String) -> Result<(), String> {
+    value
+        .parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| format!("{} is not a number", value))
+}
+
+// Sizes are read as u64, which may be wider than usize.
+fn is_size(value: String) -> Result<(), String> {
+    value
+        .parse::<u64>()
+        .map(|_| ())
+        .map_err(|_| format!("{} is not a size in bytes", value))
+}
+
+fn is_date(value: String) -> Result<(), String> {
+    match util::parse_date(&value) {
+        Some(_) => Ok(()),
+        None => Err(format!("{} is not a date like 2017-08-03, yesterday or 2w", value)),
+    }
+}
+
+// It becomes a file name, so keep it to something every platform accepts.
+fn is_profile_name(value: String) -> Result<(), String> {
+    match !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        true => Ok(()),
+        false => Err(format!("{} is not a profile name; use letters, digits, '-' and '_'", value)),
+    }
+}
+
+fn is_dir(value: String) -> Result<(), String> {
+    match Path::new(&value).is_dir() {
+        true => Ok(()),
+        false => Err(format!("{} is not a directory", value)),
+    }
 }
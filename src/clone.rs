@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+
+use gitlib::GitRepo;
+use util::Manifest;
+
+#[derive(Debug, Clone)]
+pub struct CloneOptions {
+    pub url: String,
+    pub path: Option<PathBuf>,
+    pub template: Option<PathBuf>,
+    pub email: Option<String>,
+    pub depth: Option<usize>,
+}
+
+pub fn process_clone(manifest: &mut Manifest, root: &Path, options: &CloneOptions) {
+    let relative = match options.path.clone().or_else(|| path_from_url(&options.url)) {
+        Some(p) => p,
+        None => {
+            println!("{}", BrightRed.paint(format!("Could not work out where to clone {}; give a path", options.url)));
+            return;
+        }
+    };
+
+    let path = root.join(relative);
+
+    if path.exists() {
+        println!("{}", BrightRed.paint(format!("{} already exists", path.display())));
+        return;
+    }
+
+    println!("Cloning {} into {}", options.url, path.display());
+
+    let repo = match GitRepo::clone_url(&options.url, &path, options.template.as_deref(), options.depth) {
+        Ok(r) => r,
+        Err(_) => {
+            println!("{}", BrightRed.paint("Clone failed"));
+            return;
+        }
+    };
+
+    if let Some(ref email) = options.email {
+        if repo.set_config("user.email", email).is_err() {
+            println!("{}", BrightRed.paint("Could not set user.email"));
+        }
+    }
+
+    register(manifest, &repo);
+
+    println!("{}", BrightGreen.paint(format!("Cloned {}", repo.path().display())));
+}
+
+// Without a manifest every run searches the root anyway, and starting one
+// here would hide every other repo from later runs.
+pub fn register(manifest: &mut Manifest, repo: &GitRepo) {
+    match !manifest.is_empty() && manifest.path_in_manifest(repo.path()) {
+        true => manifest.add_repository(repo),
+        false => println!("{}", BrightBlack.paint("No manifest for this root; nothing to register")),
+    }
+}
+
+// "https://github.com/drewbrend/gitplz.git" and "git@github.com:drewbrend/gitplz"
+// both become "drewbrend/gitplz"; local paths just keep their last component.
+fn path_from_url(url: &str) -> Option<PathBuf> {
+    let url = url.trim_end_matches('/');
+
+    let path = match url.find("://") {
+        Some(_) if url.starts_with("file://") => Path::new(url).file_name().and_then(|n| n.to_str()).unwrap_or(""),
+        Some(i) => {
+            let rest = &url[i + 3..];
+            rest.find('/').map_or("", |j| &rest[j + 1..])
+        }
+        None => {
+            match url.find(':') {
+                // scp-like, unless it's a Windows drive letter.
+                Some(i) if i > 1 && !url[..i].contains('/') => &url[i + 1..],
+                _ => Path::new(url).file_name().and_then(|n| n.to_str()).unwrap_or(""),
+            }
+        }
+    };
+
+    let path = path.trim_end_matches(".git").trim_start_matches('/');
+
+    match path.is_empty() || path.split('/').any(|c| c == "..") {
+        true => None,
+        false => Some(PathBuf::from(path)),
+    }
+}
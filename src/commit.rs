@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed, BrightYellow};
+use threadpool::ThreadPool;
+
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct CommitOptions {
+    pub message: String,
+    pub dry_run: bool,
+}
+
+// Untracked files are never added, so new files need staging first. Each
+// commit is authored by whoever that repo's config names.
+pub fn process_commit(repos: GitRepositories, pool: &ThreadPool, options: &CommitOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let result = repo.commit_tracked(&options.message, options.dry_run);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut committed = 0;
+
+    for (path, result) in &results {
+        let message = match *result {
+            Ok(None) => continue,
+            Ok(Some(n)) => {
+                committed += 1;
+
+                let files = match n {
+                    1 => String::from("1 file"),
+                    n => format!("{} files", n),
+                };
+
+                match options.dry_run {
+                    true => styled(BrightYellow, format!("would commit {}", files)),
+                    false => styled(BrightGreen, format!("committed {}", files)),
+                }
+            }
+            Err(_) => styled(BrightRed, "could not commit"),
+        };
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    match (committed, options.dry_run) {
+        (0, _) => println!("Nothing to commit"),
+        (n, true) => println!("Would commit in {} repositories", n),
+        (n, false) => println!("Committed in {} repositories", n),
+    }
+}
@@ -0,0 +1,514 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::io::{self, Write};
+use std::sync::mpsc::channel;
+
+use clap::Shell;
+use threadpool::ThreadPool;
+
+use util::{GitRepositories, Manifest};
+
+use cli::{self, Param, ParamKind};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+// clap 2 has no generator for nushell, so its script is built here.
+pub const NUSHELL: &str = "nushell";
+
+// Set to "intersection" to only complete branches present in every repo.
+const BRANCH_COMPLETION_ENV: &str = "GIT_PLZ_BRANCH_COMPLETION";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BranchSet {
+    Union,
+    Intersection,
+}
+
+impl BranchSet {
+    pub fn from_env() -> Self {
+        match env::var(BRANCH_COMPLETION_ENV) {
+            Ok(ref v) if v.eq_ignore_ascii_case("intersection") => BranchSet::Intersection,
+            _ => BranchSet::Union,
+        }
+    }
+}
+
+// Options whose values are looked up at completion time by calling back into
+// one of the hidden helper subcommands.
+struct DynamicOption {
+    long: &'static str,
+    short: &'static str,
+    helper: &'static str,
+    function: &'static str,
+}
+
+const DYNAMIC_OPTIONS: &[DynamicOption] = &[DynamicOption {
+                                                 long: cli::REPO,
+                                                 short: cli::REPO_SHORT,
+                                                 helper: cli::CMD_COMPLETE_REPOS,
+                                                 function: "repos",
+                                             }];
+
+// Positional values completed the same way, keyed by the subcommand they belong to.
+struct DynamicPositional {
+    subcommand: &'static str,
+    arg: &'static str,
+    helper: &'static str,
+    function: &'static str,
+}
+
+const DYNAMIC_POSITIONALS: &[DynamicPositional] = &[DynamicPositional {
+                                                         subcommand: cli::CMD_CHECKOUT,
+                                                         arg: cli::BRANCH,
+                                                         helper: cli::CMD_COMPLETE_BRANCHES,
+                                                         function: "branches",
+                                                     }];
+
+pub fn generate(shell: &str) {
+    let script = match shell {
+        NUSHELL => nushell(),
+        _ => {
+            let shell = shell.parse::<Shell>().expect("Only known shells get past the argument parser");
+            let mut buf = Vec::new();
+
+            cli::build_visible_cli().gen_completions_to(cli::BIN_NAME, shell, &mut buf);
+
+            let script = String::from_utf8(buf).expect("Completion script was not valid UTF-8");
+
+            match shell {
+                Shell::Bash => bash(&script),
+                Shell::Zsh => zsh(&script),
+                Shell::Fish => fish(script),
+                Shell::PowerShell => powershell(&script),
+                Shell::Elvish => elvish(&script),
+            }
+        }
+    };
+
+    io::stdout()
+        .write_all(script.as_bytes())
+        .expect("Could not write completion script");
+}
+
+pub fn print_repos(manifest: &Manifest) {
+    for path in manifest.entries() {
+        println!("{}", path.display());
+    }
+}
+
+pub fn print_branches(repos: GitRepositories, pool: &ThreadPool, set: BranchSet) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || if let Ok(names) = repo.branch_names() {
+                         let names = names.into_iter().collect::<BTreeSet<_>>();
+                         tx.send(names).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let mut branches: Option<BTreeSet<String>> = None;
+
+    for names in rx {
+        branches = Some(match branches {
+                            None => names,
+                            Some(b) => {
+                                match set {
+                                    BranchSet::Union => b.union(&names).cloned().collect(),
+                                    BranchSet::Intersection => b.intersection(&names).cloned().collect(),
+                                }
+                            }
+                        });
+    }
+
+    for name in branches.unwrap_or_default() {
+        println!("{}", name);
+    }
+}
+
+fn bash(script: &str) -> String {
+    let mut out = String::new();
+    let mut pending: Option<&DynamicOption> = None;
+
+    // clap mangles the dash in the bin name when labelling subcommand cases,
+    // so they never match the "git-plz__<subcommand>" it builds up in $cmd.
+    let mangled = format!("{}__", cli::BIN_NAME.replace("-", "__"));
+    let label = format!("{}__", cli::BIN_NAME);
+
+    // clap falls back to file completion for every option value; swap that
+    // line out for the ones we can answer ourselves.
+    for line in script.lines() {
+        let trimmed = line.trim();
+
+        if let Some(option) = pending.take() {
+            if trimmed.starts_with("COMPREPLY=(") {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                out.push_str(&format!("{}COMPREPLY=($(compgen -W \"$({} {} 2>/dev/null)\" -- \"${{cur}}\"))\n",
+                                      indent,
+                                      cli::BIN_NAME,
+                                      option.helper));
+                continue;
+            }
+        }
+
+        pending = DYNAMIC_OPTIONS
+            .iter()
+            .find(|o| trimmed == format!("--{})", o.long) || trimmed == format!("-{})", o.short));
+
+        if trimmed.starts_with(&mangled) && trimmed.ends_with(')') {
+            out.push_str(&line.replacen(&mangled, &label, 1));
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+
+    // Positionals get no per-argument case in clap's script, so wrap the
+    // generated function and answer them before handing over.
+    let value_options = DYNAMIC_OPTIONS
+        .iter()
+        .map(|o| format!("--{}|-{}", o.long, o.short))
+        .collect::<Vec<_>>()
+        .join("|");
+
+    let cases = DYNAMIC_POSITIONALS
+        .iter()
+        .map(|p| {
+                 format!("                        {subcommand})
+                            COMPREPLY=($(compgen -W \"$({bin} {helper} 2>/dev/null)\" -- \"${{cur}}\"))
+                            return 0
+                            ;;
+",
+                         subcommand = p.subcommand,
+                         bin = cli::BIN_NAME,
+                         helper = p.helper)
+             })
+        .collect::<String>();
+
+    out.push_str(&format!("
+_{bin}_dynamic() {{
+    local i cur prev
+    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+
+    if [[ ${{cur}} != -* ]]; then
+        case \"${{prev}}\" in
+            {value_options})
+                ;;
+            *)
+                for i in \"${{COMP_WORDS[@]:1:COMP_CWORD-1}}\"; do
+                    case \"${{i}}\" in
+{cases}                    esac
+                done
+                ;;
+        esac
+    fi
+
+    _{bin} \"$@\"
+}}
+
+complete -F _{bin}_dynamic -o bashdefault -o default {bin}
+",
+                          bin = cli::BIN_NAME,
+                          value_options = value_options,
+                          cases = cases));
+
+    out
+}
+
+fn zsh(script: &str) -> String {
+    let mut out = String::new();
+
+    for line in script.lines() {
+        let option = DYNAMIC_OPTIONS.iter().find(|o| {
+            line.contains(&format!("--{}=[", o.long)) || line.contains(&format!("-{}+[", o.short))
+        });
+
+        let positional = DYNAMIC_POSITIONALS.iter().find(|p| {
+            line.contains(&format!("':{} -- ", p.arg)) || line.contains(&format!("'::{} -- ", p.arg))
+        });
+
+        match (option, positional) {
+            (Some(o), _) if line.ends_with("]' \\") => {
+                let head = &line[..line.len() - "]' \\".len()];
+                out.push_str(&format!("{}]: :_{}_{}' \\\n", head, cli::BIN_NAME, o.function));
+            }
+            (_, Some(p)) if line.ends_with(":_files' \\") => {
+                let head = &line[..line.len() - "_files' \\".len()];
+                out.push_str(&format!("{}_{}_{}' \\\n", head, cli::BIN_NAME, p.function));
+            }
+            _ => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+
+    // The helpers have to exist before the script's trailing call into the
+    // generated completion function.
+    let call = format!("_{} \"$@\"", cli::BIN_NAME);
+    let helpers = DYNAMIC_OPTIONS
+        .iter()
+        .map(|o| (o.function, o.helper))
+        .chain(DYNAMIC_POSITIONALS.iter().map(|p| (p.function, p.helper)))
+        .map(|(function, helper)| {
+                 format!("_{bin}_{function}() {{
+    local -a values
+    values=(${{(f)\"$({bin} {helper} 2>/dev/null)\"}})
+    compadd -a values
+}}
+
+",
+                         bin = cli::BIN_NAME,
+                         function = function,
+                         helper = helper)
+             })
+        .collect::<String>();
+
+    match out.rfind(&call) {
+        Some(i) => out.insert_str(i, &helpers),
+        None => out.push_str(&helpers),
+    }
+
+    out
+}
+
+fn fish(mut script: String) -> String {
+    // fish merges completions for the same option, so appending is enough.
+    for o in DYNAMIC_OPTIONS {
+        script.push_str(&format!("complete -c {bin} -s {short} -l {long} -r -f -a \"({bin} {helper} 2>/dev/null)\"\n",
+                                 bin = cli::BIN_NAME,
+                                 short = o.short,
+                                 long = o.long,
+                                 helper = o.helper));
+    }
+
+    for p in DYNAMIC_POSITIONALS {
+        script.push_str(&format!("complete -c {bin} -n \"__fish_seen_subcommand_from {subcommand}\" -f -a \"({bin} {helper} 2>/dev/null)\"\n",
+                                 bin = cli::BIN_NAME,
+                                 subcommand = p.subcommand,
+                                 helper = p.helper));
+    }
+
+    script
+}
+
+// clap's script only ever offers option and subcommand names, so answer
+// dynamic values first and only fall through to it otherwise.
+fn powershell(script: &str) -> String {
+    const SWITCH: &str = "    $completions = @(switch ($command) {";
+
+    let options = DYNAMIC_OPTIONS
+        .iter()
+        .map(|o| {
+                 format!("        {{ $_ -in '--{long}', '-{short}' }} {{ '{helper}' }}\n",
+                         long = o.long,
+                         short = o.short,
+                         helper = o.helper)
+             })
+        .collect::<String>();
+
+    let positionals = DYNAMIC_POSITIONALS
+        .iter()
+        .map(|p| {
+                 format!("            {{ $_ -like '{bin};{subcommand}*' }} {{ '{helper}' }}\n",
+                         bin = cli::BIN_NAME,
+                         subcommand = p.subcommand,
+                         helper = p.helper)
+             })
+        .collect::<String>();
+
+    let dynamic = format!("    $previous = $commandElements[$commandElements.Count - $(if ($wordToComplete) {{ 2 }} else {{ 1 }})].ToString()
+    $helper = switch ($previous) {{
+{options}    }}
+    if (-not $helper -and -not $wordToComplete.StartsWith('-')) {{
+        $helper = switch ($command) {{
+{positionals}        }}
+    }}
+    if ($helper) {{
+        return @(& '{bin}' $helper 2>$null) |
+            Where-Object {{ $_ -like \"$wordToComplete*\" }} |
+            ForEach-Object {{ [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }}
+    }}
+
+",
+                          options = options,
+                          positionals = positionals,
+                          bin = cli::BIN_NAME);
+
+    match script.find(SWITCH) {
+        Some(i) => {
+            let mut out = script.to_string();
+            out.insert_str(i, &dynamic);
+            out
+        }
+        None => script.to_string(),
+    }
+}
+
+// Same idea as PowerShell: a check ahead of clap's per-command table.
+fn elvish(script: &str) -> String {
+    const TABLE: &str = "    completions = [";
+
+    let options = DYNAMIC_OPTIONS.iter().map(|o| {
+        format!("    if (or (eq $words[-2] '--{long}') (eq $words[-2] '-{short}')) {{
+        e:{bin} {helper} 2>/dev/null | from-lines
+        return
+    }}
+",
+                long = o.long,
+                short = o.short,
+                bin = cli::BIN_NAME,
+                helper = o.helper)
+    });
+
+    let positionals = DYNAMIC_POSITIONALS.iter().map(|p| {
+        format!("    if (and (eq $command '{bin};{subcommand}') (not (has-prefix $words[-1] '-'))) {{
+        e:{bin} {helper} 2>/dev/null | from-lines
+        return
+    }}
+",
+                bin = cli::BIN_NAME,
+                subcommand = p.subcommand,
+                helper = p.helper)
+    });
+
+    let dynamic = options.chain(positionals).collect::<String>();
+
+    match script.find(TABLE) {
+        Some(i) => {
+            let mut out = script.to_string();
+            out.insert_str(i, &dynamic);
+            out
+        }
+        None => script.to_string(),
+    }
+}
+
+// An `extern` per command, with custom completers for the dynamic values and
+// for options that only take certain values.
+fn nushell() -> String {
+    let mut completers = String::new();
+    let mut externs = String::new();
+
+    let root = Some(&[][..]);
+
+    for path in root.into_iter().chain(cli::COMMANDS.iter().map(|c| c.0)) {
+        nushell_extern(path, &mut completers, &mut externs);
+    }
+
+    let helpers = DYNAMIC_OPTIONS
+        .iter()
+        .map(|o| (o.function, o.helper))
+        .chain(DYNAMIC_POSITIONALS.iter().map(|p| (p.function, p.helper)))
+        .map(|(function, helper)| {
+                 format!("  def \"nu-complete {bin} {function}\" [] {{
+    ^{bin} {helper} | complete | get stdout | lines
+  }}
+
+",
+                         bin = cli::BIN_NAME,
+                         function = function,
+                         helper = helper)
+             })
+        .collect::<String>();
+
+    format!("module completions {{
+
+{helpers}{completers}{externs}}}
+
+export use completions *
+",
+            helpers = helpers,
+            completers = completers,
+            externs = externs)
+}
+
+fn nushell_extern(path: &[&str], completers: &mut String, externs: &mut String) {
+    let command = Some(cli::BIN_NAME).into_iter().chain(path.iter().cloned()).collect::<Vec<_>>().join(" ");
+
+    // The root's arguments are global, so every command takes them too.
+    let globals = cli::PARAMS.iter().filter(|p| p.command.is_empty() && !path.is_empty());
+    let args = cli::PARAMS.iter().filter(|p| p.command == path).chain(globals).collect::<Vec<_>>();
+    let of_kind = |kind| args.iter().filter(move |p| p.kind == kind);
+
+    let mut params = Vec::new();
+
+    for p in of_kind(ParamKind::Positional) {
+        let dynamic = DYNAMIC_POSITIONALS
+            .iter()
+            .find(|d| path == [d.subcommand] && d.arg == p.name)
+            .map(|d| format!("nu-complete {} {}", cli::BIN_NAME, d.function));
+
+        let completer = dynamic.or_else(|| values_completer(&command, p, completers));
+
+        let param = match (p.multiple, p.required) {
+            (true, _) => format!("...{}", p.name),
+            (false, true) => p.name.to_string(),
+            (false, false) => format!("{}?", p.name),
+        };
+
+        params.push((format!("{}: string{}", param, completer_suffix(completer)), p.help));
+    }
+
+    for p in of_kind(ParamKind::Value) {
+        let dynamic = DYNAMIC_OPTIONS
+            .iter()
+            .find(|d| d.long == p.name)
+            .map(|d| format!("nu-complete {} {}", cli::BIN_NAME, d.function));
+
+        let completer = dynamic.or_else(|| values_completer(&command, p, completers));
+
+        params.push((format!("--{}{}: string{}", p.name, short_flag(p.short), completer_suffix(completer)), p.help));
+    }
+
+    for p in of_kind(ParamKind::Flag) {
+        params.push((format!("--{}{}", p.name, short_flag(p.short)), p.help));
+    }
+
+    // clap adds these itself once parsing starts.
+    params.push((String::from("--help(-h)"), "Prints help information"));
+
+    externs.push_str(&format!("  export extern \"{}\" [\n", command));
+
+    for (param, help) in params {
+        externs.push_str(&format!("    {}  # {}\n", param, help));
+    }
+
+    externs.push_str("  ]\n\n");
+}
+
+// For arguments that only take certain values.
+fn values_completer(command: &str, param: &Param, completers: &mut String) -> Option<String> {
+    match param.values.is_empty() {
+        true => None,
+        false => Some(nushell_completer(command, param.name, param.values, completers)),
+    }
+}
+
+// Defines a completer listing the values and returns its name.
+fn nushell_completer(command: &str, arg: &str, values: &[&str], completers: &mut String) -> String {
+    let name = format!("nu-complete {} {}", command, arg);
+    let list = values.iter().map(|v| format!("\"{}\"", v)).collect::<Vec<_>>().join(" ");
+
+    completers.push_str(&format!("  def \"{}\" [] {{\n    [{}]\n  }}\n\n", name, list));
+
+    name
+}
+
+fn completer_suffix(completer: Option<String>) -> String {
+    match completer {
+        Some(c) => format!("@\"{}\"", c),
+        None => String::new(),
+    }
+}
+
+fn short_flag(short: Option<&str>) -> String {
+    match short {
+        Some(s) => format!("(-{})", s),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use styled::{self, plain, styled};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub enum ConfigOption {
+    Get(String),
+    Set(String, String),
+}
+
+pub fn process_config(repos: GitRepositories, pool: &ThreadPool, option: &ConfigOption) {
+    match *option {
+        ConfigOption::Get(ref key) => get(repos, pool, key),
+        ConfigOption::Set(ref key, ref value) => set(repos, pool, key, value),
+    }
+}
+
+// Shows each repo's effective value, then how many repos share each one.
+fn get(repos: GitRepositories, pool: &ThreadPool, key: &str) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let key = key.to_string();
+
+        pool.execute(move || {
+                         tx.send((repo.path().to_path_buf(), repo.get_config(&key))).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut counts = BTreeMap::new();
+
+    for (path, result) in &results {
+        let (label, value) = match *result {
+            Ok(Some((ref value, true))) => (vec![plain(value.as_str())], value.as_str()),
+            Ok(Some((ref value, false))) => {
+                (vec![plain(format!("{} ", value)), styled(BrightBlack, "(inherited)")], value.as_str())
+            }
+            Ok(None) => (vec![styled(BrightBlack, "(unset)")], "(unset)"),
+            Err(_) => {
+                println!("{}  {}", path.display(), BrightRed.paint("could not read config"));
+                continue;
+            }
+        };
+
+        styled::print_repo_line(path, &label);
+        *counts.entry(value).or_insert(0) += 1;
+    }
+
+    let summary = counts
+        .iter()
+        .map(|(value, n)| format!("{} {}", n, value))
+        .collect::<Vec<_>>();
+
+    match summary.is_empty() {
+        true => println!("No repositories"),
+        false => println!("{}", summary.join(", ")),
+    }
+}
+
+// Written to each repo's own config, so global settings are left alone.
+fn set(repos: GitRepositories, pool: &ThreadPool, key: &str, value: &str) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let key = key.to_string();
+        let value = value.to_string();
+
+        pool.execute(move || {
+                         tx.send((repo.path().to_path_buf(), repo.set_config(&key, &value))).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, result) in &results {
+        match *result {
+            Ok(_) => println!("{}  {}", path.display(), BrightGreen.paint(format!("{} = {}", key, value))),
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not write config")),
+        }
+    }
+
+    let written = results.values().filter(|r| r.is_ok()).count();
+    println!("Set {} in {} of {} repositories", key, written, results.len());
+}
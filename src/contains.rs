@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitBranch;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+pub fn process_contains(repos: GitRepositories, pool: &ThreadPool, rev: &str) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let rev = rev.to_string();
+
+        pool.execute(move || {
+                         let result = repo.branches_containing(&rev);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut found = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(Some(ref branches)) => {
+                found += 1;
+
+                match branches.is_empty() {
+                    true => println!("{}  {}", path.display(), BrightBlack.paint("on no branch")),
+                    false => println!("{}", path.display()),
+                }
+
+                for (name, branch_type) in branches {
+                    let kind = match *branch_type {
+                        GitBranch::Local => BrightGreen.paint("   local"),
+                        GitBranch::Remote => BrightCyan.paint("  remote"),
+                    };
+
+                    println!("  {} {}", kind, name);
+                }
+            }
+            Ok(None) => println!("{}  {}", path.display(), BrightRed.paint("missing")),
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not read branches")),
+        }
+    }
+
+    println!("{} of {} repositories have {}", found, results.len(), rev);
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightCyan, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone, Default)]
+pub struct ContributorsOptions {
+    pub since: Option<i64>,
+    pub by_repo: bool,
+}
+
+// Keyed by lowercased email so the same person committing under slightly
+// different names still counts once.
+#[derive(Default)]
+struct Tally {
+    authors: BTreeMap<String, (String, usize)>,
+}
+
+impl Tally {
+    fn add(&mut self, name: &str, email: &str, count: usize) {
+        let entry = self.authors
+            .entry(email.to_lowercase())
+            .or_insert_with(|| (name.to_string(), 0));
+
+        entry.1 += count;
+    }
+
+    fn merge(&mut self, other: &Tally) {
+        for (email, &(ref name, count)) in &other.authors {
+            self.add(name, email, count);
+        }
+    }
+
+    fn print(&self, indent: &str) {
+        let mut authors = self.authors.iter().collect::<Vec<_>>();
+        authors.sort_by(|a, b| (b.1).1.cmp(&(a.1).1).then_with(|| a.0.cmp(b.0)));
+
+        for (email, &(ref name, count)) in authors {
+            println!("{}{:>6}  {} <{}>", indent, BrightYellow.paint(count), name, email);
+        }
+    }
+}
+
+pub fn process_contributors(repos: GitRepositories, pool: &ThreadPool, options: &ContributorsOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let since = options.since;
+
+        pool.execute(move || {
+            let mut tally = Tally::default();
+
+            // Newest commits come first, so the first name seen for an
+            // author is the one they use today.
+            for commit in repo.commits(None, since).unwrap_or_default() {
+                tally.add(commit.author(), commit.email(), 1);
+            }
+
+            tx.send((repo.path().to_path_buf(), tally)).expect(THREAD_SIGNAL);
+        });
+    }
+
+    drop(tx);
+
+    let per_repo = rx.iter()
+        .filter(|(_, t)| !t.authors.is_empty())
+        .collect::<BTreeMap<PathBuf, Tally>>();
+
+    if options.by_repo {
+        for (path, tally) in &per_repo {
+            println!("{}", BrightCyan.paint(path.display()));
+            tally.print("  ");
+        }
+
+        return;
+    }
+
+    let mut total = Tally::default();
+
+    for tally in per_repo.values() {
+        total.merge(tally);
+    }
+
+    total.print("");
+}
@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::BrightRed;
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use diffstat::{format_counts, format_stats};
+use styled::{self, plain};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+pub fn process_diff(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let stats = repo.workdir_diff_stats();
+                         tx.send((repo.path().to_path_buf(), stats)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut total = (0, 0, 0);
+    let mut dirty = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref stats) if stats.is_empty() => (),
+            Ok(ref stats) => {
+                styled::print_repo_line(path, &format_stats(stats));
+
+                dirty += 1;
+                total.0 += stats.files();
+                total.1 += stats.insertions();
+                total.2 += stats.deletions();
+            }
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not diff")),
+        }
+    }
+
+    match dirty {
+        0 => println!("No changes against HEAD"),
+        n => {
+            let mut line = vec![plain(format!("{} {} changed: ",
+                                              n,
+                                              match n {
+                                                  1 => "repository",
+                                                  _ => "repositories",
+                                              }))];
+            line.extend(format_counts(total.0, total.1, total.2));
+            styled::print_line(&line);
+        }
+    }
+}
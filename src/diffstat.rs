@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitDiffStats;
+use styled::{self, plain, styled, Styled};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone, Default)]
+pub struct DiffstatOptions {
+    // Compare against this instead of each branch's upstream.
+    pub base: Option<String>,
+}
+
+pub fn process_diffstat(repos: GitRepositories, pool: &ThreadPool, options: &DiffstatOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let base = options.base.clone();
+
+        pool.execute(move || {
+                         let stats = repo.diff_stats(base.as_deref());
+                         tx.send((repo.path().to_path_buf(), stats)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut total = (0, 0, 0);
+    let mut shipping = 0;
+    let mut unknown = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(Some(ref stats)) if stats.is_empty() => (),
+            Ok(Some(ref stats)) => {
+                styled::print_repo_line(path, &format_stats(stats));
+
+                shipping += 1;
+                total.0 += stats.files();
+                total.1 += stats.insertions();
+                total.2 += stats.deletions();
+            }
+            Ok(None) => unknown += 1,
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not diff")),
+        }
+    }
+
+    let against = match options.base {
+        Some(ref base) => base.as_str(),
+        None => "upstream",
+    };
+
+    match shipping {
+        0 => println!("Nothing ahead of {}", against),
+        n => {
+            let mut line = vec![plain(format!("{} {} ahead of {}: ",
+                                              n,
+                                              match n {
+                                                  1 => "repository",
+                                                  _ => "repositories",
+                                              },
+                                              against))];
+            line.extend(format_counts(total.0, total.1, total.2));
+            styled::print_line(&line);
+        }
+    }
+
+    if unknown > 0 {
+        println!("{}", BrightBlack.paint(format!("{} with nothing to compare against", unknown)));
+    }
+}
+
+pub fn format_stats(stats: &GitDiffStats) -> Vec<Styled> {
+    format_counts(stats.files(), stats.insertions(), stats.deletions())
+}
+
+// e.g. "3 files, +120 -14"
+pub fn format_counts(files: usize, insertions: usize, deletions: usize) -> Vec<Styled> {
+    let files = match files {
+        1 => String::from("1 file"),
+        n => format!("{} files", n),
+    };
+
+    vec![plain(format!("{}, ", files)),
+         styled(BrightGreen, format!("+{}", insertions)),
+         plain(" "),
+         styled(BrightRed, format!("-{}", deletions))]
+}
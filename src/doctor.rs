@@ -0,0 +1,210 @@
+use std::env;
+use std::fs::{self, File};
+use std::path::Path;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+
+use gitlib::{self, GitLibrary};
+use util::{Manifest, ManifestError};
+
+use cli;
+
+// In the order curl, and so the git binary, looks them up.
+const PROXY_VARS: &[&str] = &["https_proxy", "HTTPS_PROXY", "http_proxy", "HTTP_PROXY", "all_proxy", "ALL_PROXY"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Level {
+    Ok,
+    Warn,
+    Fail,
+}
+
+struct Finding {
+    level: Level,
+    message: String,
+    hint: Option<String>,
+}
+
+impl Finding {
+    fn ok<S: Into<String>>(message: S) -> Self {
+        Self {
+            level: Level::Ok,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn warn<S: Into<String>, T: Into<String>>(message: S, hint: T) -> Self {
+        Self {
+            level: Level::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail<S: Into<String>, T: Into<String>>(message: S, hint: T) -> Self {
+        Self {
+            level: Level::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+// `app_root` is None when the platform's app data directory couldn't be located.
+pub fn process_doctor(app_root: Option<&Path>, manifest_path: Option<&Path>) {
+    let library = GitLibrary::linked();
+
+    let mut findings = Vec::new();
+    findings.extend(check_libgit2(&library));
+    findings.push(check_git());
+    findings.extend(check_credentials(&library));
+    findings.push(check_app_dir(app_root));
+    findings.extend(manifest_path.map(check_manifest));
+    findings.push(check_proxy());
+
+    for finding in &findings {
+        print_finding(finding);
+    }
+
+    let problems = findings.iter().filter(|f| f.level != Level::Ok).count();
+
+    match problems {
+        0 => println!("{}", BrightGreen.paint("No problems found")),
+        1 => println!("{}", BrightYellow.paint("1 problem found")),
+        n => println!("{}", BrightYellow.paint(format!("{} problems found", n))),
+    }
+}
+
+fn print_finding(finding: &Finding) {
+    let (colour, label) = match finding.level {
+        Level::Ok => (BrightGreen, "ok  "),
+        Level::Warn => (BrightYellow, "warn"),
+        Level::Fail => (BrightRed, "fail"),
+    };
+
+    println!("{}  {}", colour.paint(label), finding.message);
+
+    if let Some(ref hint) = finding.hint {
+        println!("      {}", BrightBlack.paint(hint));
+    }
+}
+
+fn check_libgit2(library: &GitLibrary) -> Vec<Finding> {
+    let mut findings = vec![Finding::ok(format!("libgit2 {}", library.version()))];
+
+    if !library.https() {
+        findings.push(Finding::warn("libgit2 was built without HTTPS support",
+                                    "Fetching https:// remotes will fail; rebuild with git2's \"https\" feature"));
+    }
+
+    if !library.ssh() {
+        findings.push(Finding::warn("libgit2 was built without SSH support",
+                                    "Fetching ssh:// and git@ remotes will fail; rebuild with git2's \"ssh\" feature"));
+    }
+
+    if !library.threads() {
+        findings.push(Finding::warn("libgit2 was built without thread support",
+                                    "Repositories are processed in parallel; expect crashes under load"));
+    }
+
+    findings
+}
+
+fn check_git() -> Finding {
+    match gitlib::git_version() {
+        Ok(version) => Finding::ok(version),
+        Err(e) => {
+            Finding::fail(format!("git is not usable: {}", e),
+                          "gc, reachability, sizes and some audits run the git binary; install it or add it to PATH")
+        }
+    }
+}
+
+// Fetch only ever offers agent keys for SSH and the credential helper for
+// HTTPS, so anything else configured won't be consulted.
+fn check_credentials(library: &GitLibrary) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if library.https() {
+        findings.push(match gitlib::global_config("credential.helper") {
+                          Some(helper) => Finding::ok(format!("credential helper: {}", helper)),
+                          None => {
+                              Finding::warn("No credential helper configured",
+                                            "HTTPS remotes that need a login will fail; set credential.helper")
+                          }
+                      });
+    }
+
+    if library.ssh() && cfg!(unix) {
+        findings.push(match env::var_os("SSH_AUTH_SOCK") {
+                          Some(_) => Finding::ok("ssh-agent is reachable"),
+                          None => {
+                              Finding::warn("SSH_AUTH_SOCK is not set",
+                                            "SSH remotes authenticate through ssh-agent only; start one and add your key")
+                          }
+                      });
+    }
+
+    findings
+}
+
+fn check_app_dir(app_root: Option<&Path>) -> Finding {
+    let root = match app_root {
+        Some(r) => r,
+        None => {
+            return Finding::fail("Could not locate the app data directory",
+                                 "The manifest can't be saved; check that HOME (or APPDATA) is set")
+        }
+    };
+
+    // Creating a file is the only reliable permission check across platforms.
+    let probe = root.join(".doctor");
+    let writable = fs::create_dir_all(root).and_then(|_| File::create(&probe)).is_ok();
+    let _ = fs::remove_file(&probe);
+
+    match writable {
+        true => Finding::ok(format!("app data directory {} is writable", root.display())),
+        false => {
+            Finding::fail(format!("app data directory {} is not writable", root.display()),
+                          "Manifest updates will fail; fix the directory's permissions")
+        }
+    }
+}
+
+fn check_manifest(path: &Path) -> Finding {
+    match Manifest::validate(path) {
+        Ok(count) => Finding::ok(format!("manifest {} lists {} repositories", path.display(), count)),
+        Err(ManifestError::PathNotFound) => {
+            Finding::ok("no manifest; repositories are found by searching the working directory")
+        }
+        Err(ManifestError::Invalid) => {
+            Finding::fail(format!("manifest {} is not valid", path.display()),
+                          format!("It is being ignored; run `{} manifest clean` then `{} manifest update`",
+                                  cli::APP_NAME,
+                                  cli::APP_NAME))
+        }
+        Err(_) => {
+            Finding::fail(format!("manifest {} can't be read", path.display()),
+                          "It is being ignored; check the file's permissions")
+        }
+    }
+}
+
+// libgit2 only uses a proxy when asked to per fetch, and fetch doesn't ask.
+fn check_proxy() -> Finding {
+    let proxy = PROXY_VARS
+        .iter()
+        .filter_map(|v| env::var(v).ok().map(|value| format!("{}={}", v, value)))
+        .chain(gitlib::global_config("http.proxy").map(|value| format!("http.proxy={}", value)))
+        .next();
+
+    match proxy {
+        Some(proxy) => {
+            Finding::warn(format!("proxy configured ({})", proxy),
+                          "Fetches go direct and will fail if the proxy is required; the git binary does honour it")
+        }
+        None => Finding::ok("no proxy configured"),
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+
+use term_painter::Color::{BrightCyan, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct ExecOptions {
+    pub command: Vec<String>,
+}
+
+enum Event {
+    Line(PathBuf, String),
+    // The exit code, None when killed by a signal, or why it couldn't start.
+    Exited(PathBuf, Result<Option<i32>, String>),
+}
+
+// Lines from different repos interleave as they arrive, so each carries its
+// repo's path.
+pub fn process_exec(repos: GitRepositories, pool: &ThreadPool, options: &ExecOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let path = repo.path().to_path_buf();
+        let command = options.command.clone();
+
+        pool.execute(move || {
+                         let result = run(&path, &command, &tx);
+                         tx.send(Event::Exited(path, result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let mut results = BTreeMap::new();
+
+    for event in rx.iter() {
+        match event {
+            Event::Line(path, line) => println!("{}  {}", BrightCyan.paint(path.display()), line),
+            Event::Exited(path, result) => {
+                results.insert(path, result);
+            }
+        }
+    }
+
+    let mut failed = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(Some(0)) => (),
+            Ok(Some(code)) => println!("{}  {}", path.display(), BrightRed.paint(format!("exited with {}", code))),
+            Ok(None) => println!("{}  {}", path.display(), BrightRed.paint("killed by a signal")),
+            Err(ref e) => println!("{}  {}", path.display(), BrightRed.paint(format!("could not run: {}", e))),
+        }
+
+        if *result != Ok(Some(0)) {
+            failed += 1;
+        }
+    }
+
+    match failed {
+        0 => println!("{}", BrightGreen.paint(format!("Succeeded in all {} repositories", results.len()))),
+        n => println!("{}", BrightRed.paint(format!("Failed in {} of {} repositories", n, results.len()))),
+    }
+}
+
+fn run(path: &Path, command: &[String], tx: &Sender<Event>) -> Result<Option<i32>, String> {
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .current_dir(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Reading both pipes from this thread could deadlock once the other fills.
+    let stderr = child.stderr.take().map(|err| {
+                                              let tx = tx.clone();
+                                              let path = path.to_path_buf();
+                                              thread::spawn(move || forward(err, &path, &tx))
+                                          });
+
+    if let Some(out) = child.stdout.take() {
+        forward(out, path, tx);
+    }
+
+    if let Some(handle) = stderr {
+        let _ = handle.join();
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    Ok(status.code())
+}
+
+fn forward<R: Read>(pipe: R, path: &Path, tx: &Sender<Event>) {
+    // Not every tool writes UTF-8, and one bad byte shouldn't drop the rest.
+    for line in BufReader::new(pipe).split(b'\n') {
+        match line {
+            Ok(line) => {
+                let line = String::from_utf8_lossy(&line).trim_end_matches('\r').to_string();
+                tx.send(Event::Line(path.to_path_buf(), line)).expect(THREAD_SIGNAL);
+            }
+            Err(_) => break,
+        }
+    }
+}
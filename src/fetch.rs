@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::Prune;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    pub prune: Prune,
+}
+
+// Fetches every remote of every repo; nothing in the working trees changes.
+pub fn process_fetch(repos: GitRepositories, pool: &ThreadPool, options: &FetchOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let prune = options.prune;
+
+        pool.execute(move || {
+                         let result = repo.fetch_remotes(prune);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    let mut fetched = 0;
+
+    for (path, result) in &results {
+        let remotes = match *result {
+            Ok(ref r) => r,
+            Err(_) => {
+                println!("{}  {}", path.display(), BrightRed.paint("could not fetch"));
+                continue;
+            }
+        };
+
+        let failed = remotes.iter().filter(|r| !r.1).map(|r| r.0.as_str()).collect::<Vec<_>>();
+
+        match (remotes.is_empty(), failed.is_empty()) {
+            (true, _) => println!("{}  {}", path.display(), BrightBlack.paint("no remotes")),
+            (false, true) => {
+                fetched += 1;
+                println!("{}  {}", path.display(), BrightGreen.paint("fetched"));
+            }
+            (false, false) => {
+                println!("{}  {}",
+                         path.display(),
+                         BrightRed.paint(format!("could not fetch {}", failed.join(", "))))
+            }
+        }
+    }
+
+    println!("{} of {} repositories fetched", fetched, results.len());
+}
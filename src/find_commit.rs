@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitCommit;
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone, Default)]
+pub struct FindCommitOptions {
+    pub pattern: String,
+    pub ignore_case: bool,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub range: Option<String>,
+}
+
+impl FindCommitOptions {
+    // A plain substring match against the whole message, not just the subject.
+    fn matches(&self, commit: &GitCommit) -> bool {
+        if let Some(until) = self.until {
+            if commit.time() > until {
+                return false;
+            }
+        }
+
+        match self.ignore_case {
+            true => commit.message().to_lowercase().contains(&self.pattern.to_lowercase()),
+            false => commit.message().contains(&self.pattern),
+        }
+    }
+}
+
+pub fn process_find_commit(repos: GitRepositories, pool: &ThreadPool, options: &FindCommitOptions) {
+    let (tx, rx) = channel();
+    let options = Arc::new(options.clone());
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+            // Repos missing the range's refs simply have nothing to match.
+            let found = repo.commits(options.range.as_deref(), options.since)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|c| options.matches(c))
+                .collect::<Vec<_>>();
+
+            tx.send((repo.path().to_path_buf(), found)).expect(THREAD_SIGNAL);
+        });
+    }
+
+    drop(tx);
+
+    let found = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut count = 0;
+
+    for (path, commits) in &found {
+        for commit in commits {
+            println!("{}  {}  {}  {}",
+                     BrightCyan.paint(path.display()),
+                     BrightYellow.paint(commit.short_id()),
+                     BrightBlack.paint(util::format_date(commit.time(), commit.offset_minutes())),
+                     commit.summary());
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        println!("No matching commits");
+    }
+}
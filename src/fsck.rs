@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use styled::{self, plain, styled};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+pub fn process_fsck(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let fsck = repo.fsck();
+                         tx.send((repo.path().to_path_buf(), fsck)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut corrupt = Vec::new();
+    let mut dangling = 0;
+
+    for (path, result) in &results {
+        let fsck = match *result {
+            Ok(ref f) => f,
+            Err(_) => {
+                println!("{}  {}", path.display(), BrightRed.paint("could not run git fsck"));
+                corrupt.push(path);
+                continue;
+            }
+        };
+
+        let counts = fsck.dangling()
+            .iter()
+            .map(|(kind, count)| format!("{} {}", count, kind))
+            .collect::<Vec<_>>();
+
+        let problems = styled(BrightRed, format!("{} problems", fsck.problems().len()));
+        let dangling_objects = styled(BrightYellow, format!("dangling: {}", counts.join(", ")));
+
+        let message = match (fsck.is_healthy(), counts.is_empty()) {
+            (true, true) => vec![styled(BrightGreen, "ok")],
+            (true, false) => vec![dangling_objects],
+            (false, true) => vec![problems],
+            (false, false) => vec![problems, plain(", "), dangling_objects],
+        };
+
+        styled::print_repo_line(path, &message);
+
+        for problem in fsck.problems() {
+            println!("    {}", BrightRed.paint(problem));
+        }
+
+        if fsck.dangling_count() > 0 {
+            dangling += 1;
+        }
+
+        if !fsck.is_healthy() {
+            corrupt.push(path);
+        }
+    }
+
+    println!("Checked {} repositories: {} damaged, {} with dangling objects",
+             results.len(),
+             corrupt.len(),
+             dangling);
+
+    // Dangling objects go away on their own with gc; damage doesn't.
+    if !corrupt.is_empty() {
+        println!();
+        println!("{}", BrightYellow.paint("Needs attention:"));
+
+        for path in corrupt {
+            println!("  {}", path.display());
+        }
+    }
+}
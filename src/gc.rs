@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone, Default)]
+pub struct GcOptions {
+    pub aggressive: bool,
+}
+
+struct GcResult {
+    path: PathBuf,
+    before: u64,
+    after: Option<u64>,
+}
+
+pub fn process_gc(repos: GitRepositories, pool: &ThreadPool, options: &GcOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let aggressive = options.aggressive;
+
+        pool.execute(move || {
+            let before = util::dir_size(repo.git_dir());
+            let after = repo.gc(aggressive).ok().map(|_| util::dir_size(repo.git_dir()));
+
+            let result = GcResult {
+                path: repo.path().to_path_buf(),
+                before: before,
+                after: after,
+            };
+
+            tx.send(result).expect(THREAD_SIGNAL);
+        });
+    }
+
+    drop(tx);
+
+    let mut reclaimed = 0;
+
+    for result in rx {
+        let after = match result.after {
+            Some(a) => a,
+            None => {
+                println!("{}  {}", result.path.display(), BrightRed.paint("gc failed"));
+                continue;
+            }
+        };
+
+        // A repack can occasionally grow the directory a little.
+        let saved = result.before.saturating_sub(after);
+        reclaimed += saved;
+
+        println!("{}  {} -> {}  {}",
+                 result.path.display(),
+                 util::format_bytes(result.before),
+                 util::format_bytes(after),
+                 BrightGreen.paint(format!("{} reclaimed", util::format_bytes(saved))));
+    }
+
+    println!("{}", BrightGreen.paint(format!("Total reclaimed: {}", util::format_bytes(reclaimed))));
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::BrightRed;
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    pub pattern: String,
+    pub ignore_case: bool,
+}
+
+// Paths are printed as repo/relative/path so they can be opened directly.
+pub fn process_grep(repos: GitRepositories, pool: &ThreadPool, options: &GrepOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let matches = repo.grep(&options.pattern, options.ignore_case);
+                         tx.send((repo.path().to_path_buf(), matches)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut found = 0;
+    let mut repos = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref matches) => {
+                for m in matches {
+                    println!("{}:{}: {}", path.join(m.path()).display(), m.line(), m.text());
+                }
+
+                if !matches.is_empty() {
+                    found += matches.len();
+                    repos += 1;
+                }
+            }
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not search")),
+        }
+    }
+
+    match found {
+        0 => println!("No matches"),
+        1 => println!("1 match"),
+        n => println!("{} matches in {} repositories", n, repos),
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitRepo;
+use styled::{self, styled};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstallMode {
+    Copy,
+    Symlink,
+    // Point core.hooksPath at the shared directory instead of touching .git/hooks.
+    HooksPath,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstallOptions {
+    pub dir: PathBuf,
+    pub mode: InstallMode,
+    // Replace hooks that differ from the shared ones.
+    pub force: bool,
+}
+
+#[derive(Default)]
+struct Installed {
+    installed: Vec<String>,
+    unchanged: usize,
+    // Already there with different contents, and not forced.
+    kept: Vec<String>,
+}
+
+pub fn process_install(repos: GitRepositories, pool: &ThreadPool, options: &InstallOptions) {
+    // Symlinks and core.hooksPath are both read from inside each repo.
+    let dir = match fs::canonicalize(&options.dir) {
+        Ok(d) if d.is_dir() => d,
+        _ => {
+            println!("{}", BrightRed.paint(format!("{} is not a directory", options.dir.display())));
+            return;
+        }
+    };
+
+    let hooks = match hook_names(&dir) {
+        Ok(h) => h,
+        Err(e) => {
+            println!("{}", BrightRed.paint(format!("Could not read {}: {}", dir.display(), e)));
+            return;
+        }
+    };
+
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let dir = dir.clone();
+        let hooks = hooks.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let result = install(&repo, &dir, &hooks, &options);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut updated = 0;
+
+    for (path, result) in &results {
+        let message = match *result {
+            Ok(ref i) if i.installed.is_empty() && i.kept.is_empty() => vec![styled(BrightBlack, "up to date")],
+            Ok(ref i) => {
+                let mut parts = Vec::new();
+
+                if !i.installed.is_empty() {
+                    updated += 1;
+                    parts.push(vec![styled(BrightGreen, format!("installed {}", i.installed.join(", ")))]);
+                }
+
+                if !i.kept.is_empty() {
+                    parts.push(vec![styled(BrightYellow,
+                                           format!("kept different {}; use --force to replace", i.kept.join(", ")))]);
+                }
+
+                styled::join(parts, "  ")
+            }
+            Err(ref e) => vec![styled(BrightRed, e.as_str())],
+        };
+
+        styled::print_repo_line(path, &message);
+    }
+
+    println!("Updated hooks in {} of {} repositories", updated, results.len());
+}
+
+fn install(repo: &GitRepo, dir: &Path, hooks: &[String], options: &InstallOptions) -> Result<Installed, String> {
+    let (hooks_dir, custom) = repo.hooks_dir();
+
+    if options.mode == InstallMode::HooksPath {
+        let mut result = Installed::default();
+
+        match custom && hooks_dir == dir {
+            true => result.unchanged = hooks.len(),
+            false => {
+                repo.set_config("core.hooksPath", &dir.to_string_lossy())
+                    .map_err(|_| String::from("could not set core.hooksPath"))?;
+                result.installed.push(String::from("core.hooksPath"));
+            }
+        }
+
+        return Ok(result);
+    }
+
+    // Whatever that points at is probably shared, and not ours to write to.
+    if custom {
+        return Err(format!("core.hooksPath is set to {}; skipped", hooks_dir.display()));
+    }
+
+    fs::create_dir_all(&hooks_dir).map_err(|e| format!("could not create {}: {}", hooks_dir.display(), e))?;
+
+    let mut result = Installed::default();
+
+    for name in hooks {
+        let source = dir.join(name);
+        let target = hooks_dir.join(name);
+
+        if is_same(&source, &target, options.mode) {
+            result.unchanged += 1;
+            continue;
+        }
+
+        if fs::symlink_metadata(&target).is_ok() {
+            if !options.force {
+                result.kept.push(name.clone());
+                continue;
+            }
+
+            fs::remove_file(&target).map_err(|e| format!("could not replace {}: {}", name, e))?;
+        }
+
+        match options.mode {
+            InstallMode::Symlink => symlink(&source, &target),
+            _ => fs::copy(&source, &target).map(|_| ()),
+        }.map_err(|e| format!("could not install {}: {}", name, e))?;
+
+        result.installed.push(name.clone());
+    }
+
+    Ok(result)
+}
+
+// Every regular file in the directory; git ignores the .sample ones anyway.
+fn hook_names(dir: &Path) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_file() {
+            if let Some(name) = entry.file_name().to_str() {
+                names.push(name.to_string());
+            }
+        }
+    }
+
+    names.sort();
+    Ok(names)
+}
+
+fn is_same(source: &Path, target: &Path, mode: InstallMode) -> bool {
+    match mode {
+        InstallMode::Symlink => fs::read_link(target).map(|t| t == source).unwrap_or(false),
+        _ => {
+            match (fs::read(source), fs::read(target)) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, target: &Path) -> io::Result<()> {
+    ::std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(windows)]
+fn symlink(source: &Path, target: &Path) -> io::Result<()> {
+    ::std::os::windows::fs::symlink_file(source, target)
+}
@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+
+use gitlib::{self, GitRepo};
+use util::Manifest;
+
+use clone;
+
+// What git itself falls back to when init.defaultBranch isn't set.
+const DEFAULT_BRANCH: &str = "master";
+
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    pub name: PathBuf,
+    pub branch: Option<String>,
+    pub template: Option<PathBuf>,
+}
+
+pub fn process_init(manifest: &mut Manifest, root: &Path, options: &InitOptions) {
+    let path = root.join(&options.name);
+
+    if path.exists() {
+        println!("{}", BrightRed.paint(format!("{} already exists", path.display())));
+        return;
+    }
+
+    let branch = options
+        .branch
+        .clone()
+        .or_else(|| gitlib::global_config("init.defaultBranch"))
+        .unwrap_or_else(|| String::from(DEFAULT_BRANCH));
+
+    let repo = match GitRepo::init(&path, &branch, options.template.as_deref()) {
+        Ok(r) => r,
+        Err(_) => {
+            println!("{}", BrightRed.paint(format!("Could not create a repository at {}", path.display())));
+            return;
+        }
+    };
+
+    clone::register(manifest, &repo);
+
+    println!("{}", BrightGreen.paint(format!("Initialized {} on {}", repo.path().display(), branch)));
+}
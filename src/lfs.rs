@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitRepo;
+use styled::{self, plain, styled};
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+const LFS_POINTER: &str = "version https://git-lfs.github.com/spec/v1";
+
+// Pointer files are tiny; anything bigger is real content.
+const LFS_POINTER_MAX: u64 = 1024;
+
+#[derive(Debug, Clone)]
+pub enum LfsOption {
+    Status,
+    Pull,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LfsState {
+    // Working tree files that are still pointers.
+    pub pointers: usize,
+    // Of those, how many have no object in the local LFS store either.
+    pub missing: usize,
+    pub size: u64,
+}
+
+impl LfsState {
+    fn not_checked_out(&self) -> usize {
+        self.pointers - self.missing
+    }
+}
+
+pub fn process_lfs(repos: GitRepositories, pool: &ThreadPool, option: &LfsOption) {
+    match *option {
+        LfsOption::Status => status(repos, pool),
+        LfsOption::Pull => pull(repos, pool),
+    }
+}
+
+// Reads LFS's own layout directly rather than needing git-lfs installed:
+// a pointer left in the working tree means the smudge never ran. None for
+// repos that don't use LFS at all.
+pub fn scan(repo: &GitRepo) -> Option<LfsState> {
+    let tracked = repo.tracked_paths().unwrap_or_default();
+    let objects = repo.git_dir().join("lfs").join("objects");
+
+    let uses_lfs = tracked
+        .iter()
+        .filter(|p| p.file_name().is_some_and(|n| n == ".gitattributes"))
+        .filter_map(|p| fs::read_to_string(repo.path().join(p)).ok())
+        .any(|a| a.contains("filter=lfs"));
+
+    if !uses_lfs && !objects.is_dir() {
+        return None;
+    }
+
+    let mut pointers = 0;
+    let mut missing = 0;
+
+    for path in &tracked {
+        let oid = match lfs_pointer_oid(&repo.path().join(path)) {
+            Some(o) => o,
+            None => continue,
+        };
+
+        pointers += 1;
+
+        // The oid comes from a working tree file, so it's only trusted to
+        // slice once it's known to be plain hex.
+        let valid = oid.len() == 64 && oid.bytes().all(|b| b.is_ascii_hexdigit());
+
+        if !valid || !objects.join(&oid[..2]).join(&oid[2..4]).join(&oid).is_file() {
+            missing += 1;
+        }
+    }
+
+    Some(LfsState {
+             pointers: pointers,
+             missing: missing,
+             size: util::dir_size(&objects),
+         })
+}
+
+fn status(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let state = scan(&repo).map(|s| (repo.path().to_path_buf(), s));
+                         tx.send(state).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().flatten().collect::<BTreeMap<_, _>>();
+    let mut incomplete = 0;
+
+    for (path, state) in &results {
+        let mut parts = Vec::new();
+
+        if state.missing > 0 {
+            parts.push(vec![styled(BrightRed, format!("{} not fetched", state.missing))]);
+        }
+
+        if state.not_checked_out() > 0 {
+            parts.push(vec![styled(BrightYellow, format!("{} not checked out", state.not_checked_out()))]);
+        }
+
+        if parts.is_empty() {
+            parts.push(vec![styled(BrightGreen, "complete")]);
+        } else {
+            incomplete += 1;
+        }
+
+        let mut line = styled::join(parts, ", ");
+        line.push(plain("  "));
+        line.push(styled(BrightBlack, util::format_bytes(state.size)));
+        styled::print_repo_line(path, &line);
+    }
+
+    match results.len() {
+        0 => println!("No repositories use LFS"),
+        n => println!("{} of {} repositories using LFS are missing content", incomplete, n),
+    }
+}
+
+// Only repos with pointers in the working tree are pulled; the rest already
+// have everything their checkout needs.
+fn pull(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let result = match scan(&repo) {
+                             Some(ref state) if state.pointers > 0 => {
+                                 let pulled = repo.lfs_pull().ok().and_then(|_| scan(&repo));
+                                 Some((repo.path().to_path_buf(), Some((*state, pulled))))
+                             }
+                             Some(_) => Some((repo.path().to_path_buf(), None)),
+                             None => None,
+                         };
+
+                         tx.send(result).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().flatten().collect::<BTreeMap<_, _>>();
+    let mut failed = 0;
+
+    for (path, result) in &results {
+        let message = match *result {
+            None => styled(BrightBlack, "nothing to pull"),
+            Some((_, None)) => {
+                failed += 1;
+                styled(BrightRed, "git lfs pull failed; is git-lfs installed?")
+            }
+            Some((before, Some(after))) if after.pointers == 0 => {
+                styled(BrightGreen, format!("pulled {} files", before.pointers))
+            }
+            Some((before, Some(after))) => {
+                failed += 1;
+                styled(BrightYellow,
+                       format!("pulled {} files, {} still pointers",
+                               before.pointers.saturating_sub(after.pointers),
+                               after.pointers))
+            }
+        };
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    match failed {
+        0 => println!("{}", BrightGreen.paint(format!("{} repositories using LFS are complete", results.len()))),
+        n => {
+            println!("{}",
+                     BrightYellow.paint(format!("{} of {} repositories still missing LFS content", n, results.len())))
+        }
+    }
+}
+
+// The sha256 of the object a working tree file points at, if it's still an
+// unsmudged pointer.
+fn lfs_pointer_oid(file: &Path) -> Option<String> {
+    if fs::metadata(file).ok()?.len() > LFS_POINTER_MAX {
+        return None;
+    }
+
+    let text = fs::read_to_string(file).ok()?;
+
+    if !text.starts_with(LFS_POINTER) {
+        return None;
+    }
+
+    text.lines()
+        .filter_map(|l| l.strip_prefix("oid sha256:"))
+        .map(|o| o.trim().to_string())
+        .next()
+}
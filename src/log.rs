@@ -0,0 +1,53 @@
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct LogOptions {
+    pub since: i64,
+}
+
+// Every repo's commits since the date, on the current branch, as one stream
+// with the newest first.
+pub fn process_log(repos: GitRepositories, pool: &ThreadPool, options: &LogOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let since = options.since;
+
+        pool.execute(move || {
+                         let commits = repo.commits(None, Some(since)).unwrap_or_default();
+                         tx.send((repo.path().to_path_buf(), commits)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let mut log = rx.iter()
+        .flat_map(|(path, commits)| commits.into_iter().map(move |c| (path.clone(), c)))
+        .collect::<Vec<_>>();
+
+    // Ties go to path order so reruns print the same thing.
+    log.sort_by(|a, b| b.1.time().cmp(&a.1.time()).then_with(|| a.0.cmp(&b.0)));
+
+    for (path, commit) in &log {
+        println!("{}  {}  {}  {}: {}",
+                 BrightBlack.paint(util::format_time(commit.time(), commit.offset_minutes())),
+                 BrightCyan.paint(path.display()),
+                 BrightYellow.paint(commit.short_id()),
+                 commit.author(),
+                 commit.summary());
+    }
+
+    match log.len() {
+        0 => println!("No commits since {}", util::format_date(options.since, 0)),
+        n => println!("{} commits since {}", n, util::format_date(options.since, 0)),
+    }
+}
@@ -2,101 +2,833 @@ extern crate app_dirs;
 #[macro_use]
 extern crate clap;
 //extern crate indicatif;
+extern crate notify;
 extern crate num_cpus;
+extern crate ratatui;
+#[macro_use]
+extern crate serde_json;
 extern crate term_painter;
 extern crate threadpool;
 
 extern crate gitlib;
 extern crate util;
 
+use std::collections::hash_map::DefaultHasher;
 use std::env;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::process;
 use std::sync::mpsc::{channel, Receiver};
 
 use app_dirs::{AppInfo, AppDataType};
 //use indicatif::{ProgressBar, ProgressStyle};
-use term_painter::Color::{BrightCyan, BrightYellow};
+use term_painter::Color::{BrightCyan, BrightRed, BrightYellow};
 use term_painter::ToStyle;
 use threadpool::ThreadPool;
 
-use gitlib::{GitError, GitRepo};
-use util::{GitRepositories, Manifest};
+use gitlib::{GitBranch, GitError, GitRepo, MergeMode, Prune, PullMode};
+use util::{GitRepositories, Manifest, ManifestError, ManifestFormat, WORKSPACE_MANIFEST};
+
+use approve::{Answer, Approval, Prompt};
+use audit::{AuditOption, IdentityOptions};
+use branch::{BranchOption, CreateOptions, DeleteOptions};
+use config::ConfigOption;
+use remote::{RemoteOption, SetUrlOptions};
+use sparse::{SparseOption, SparseSetOptions};
+use stash::StashOption;
 
+mod add;
+mod apply;
+mod approve;
+mod archive;
+mod audit;
+mod bigfiles;
+mod branch;
+mod bundle;
+mod cherry_pick;
+mod clean;
 mod cli;
+mod clone;
+mod commit;
+mod completions;
+mod config;
+mod contains;
+mod contributors;
+mod diff;
+mod diffstat;
+mod doctor;
+mod exec;
+mod fetch;
+mod find_commit;
+mod fsck;
+mod gc;
+mod grep;
+mod hooks;
+mod init;
+mod lfs;
+mod log;
+mod man;
+mod merge_upstream;
+mod mv;
+mod prune;
+mod pull;
+mod push;
+mod rebase;
+mod remote;
+mod restore;
+mod rm;
+mod self_update;
+mod sizes;
+mod sparse;
+mod stash;
+mod standup;
+mod stats;
 mod status;
+mod styled;
+mod submodule;
+mod tag;
+mod tui;
+mod unshallow;
+mod verify;
+mod version;
 
 const THREAD_SIGNAL: &str = "Could not signal main thread";
+const MANIFEST_FILE: &str = "manifest.json";
+// Named profiles each get their own manifest in here.
+const MANIFEST_DIR: &str = "manifests";
+const STATUS_CACHE_DIR: &str = "status-cache";
 
 #[derive(Debug, Clone)]
 enum RunOption {
-    Checkout(String),
+    Add(add::AddOptions),
+    Apply(apply::ApplyOptions),
+    Archive(archive::ArchiveOptions),
+    Audit(AuditOption),
+    Bigfiles(bigfiles::BigfilesOptions),
+    Branch(BranchOption),
+    // None checks out each repo's own default branch.
+    Checkout(Option<String>, Prompt),
+    CherryPick(cherry_pick::CherryPickOptions),
+    Clean(clean::CleanOptions),
+    Bundle(bundle::BundleOptions),
+    Clone(clone::CloneOptions),
+    CloneBundles(PathBuf),
+    Commit(commit::CommitOptions),
+    CompleteBranches(completions::BranchSet),
+    Config(ConfigOption),
+    Contains(String),
+    Contributors(contributors::ContributorsOptions),
+    Diff,
+    Diffstat(diffstat::DiffstatOptions),
+    Exec(exec::ExecOptions),
+    Fetch(fetch::FetchOptions),
+    FindCommit(find_commit::FindCommitOptions),
+    Fsck,
+    Gc(gc::GcOptions),
+    Grep(grep::GrepOptions),
+    HooksInstall(hooks::InstallOptions),
+    Init(init::InitOptions),
+    Lfs(lfs::LfsOption),
+    Log(log::LogOptions),
     Manifest(ManifestOption),
-    Reset,
-    Status,
+    MergeUpstream(merge_upstream::MergeUpstreamOptions),
+    Mv(mv::MvOptions),
+    Prune,
+    Pull(pull::PullOptions),
+    Push,
+    Rebase,
+    Remote(RemoteOption),
+    Reset(Prompt),
+    Rm(rm::RmOptions),
+    Sizes(sizes::SizesOptions),
+    Sparse(SparseOption),
+    Stash(StashOption),
+    Standup(standup::StandupOptions),
+    Stats(stats::StatsOptions),
+    Status(status::StatusOptions),
+    SubmoduleUpdate(submodule::SubmoduleUpdateOptions),
+    Tag(tag::TagOptions),
+    Tui,
+    Unshallow,
 }
 
 #[derive(Debug, Clone)]
 enum ManifestOption {
+    Add(PathBuf),
     Clean,
+    Export(PathBuf, ManifestFormat),
+    // Adds the glob, or removes it if asked; lists them without one.
+    Ignore(Option<String>, bool),
+    Import(PathBuf, restore::RestoreOptions),
+    List,
+    Merge(PathBuf),
     Preview,
+    // Only reports the stale entries when true.
+    Prune(bool),
+    Remove(PathBuf),
+    Restore(restore::RestoreOptions),
+    // Tags the entry, or untags it if asked; shows its groups without a name.
+    Tag(PathBuf, Option<String>, bool),
     Update,
+    Verify,
 }
 
 fn main() {
-    let working_dir = env::current_dir().expect("Could not get working directory");
     let matches = cli::build_cli().get_matches();
+    let profile = profile(&matches);
+    let roots = roots(&matches, profile.as_deref());
 
     let option = match matches.subcommand_name() {
+        Some(cli::CMD_ADD) => {
+            let matches = matches.subcommand_matches(cli::CMD_ADD).unwrap();
+            let pathspecs = matches.values_of(cli::PATHSPEC).unwrap().map(String::from).collect();
+
+            RunOption::Add(add::AddOptions { pathspecs: pathspecs })
+        }
+        Some(cli::CMD_APPLY) => {
+            let matches = matches.subcommand_matches(cli::CMD_APPLY).unwrap();
+
+            RunOption::Apply(apply::ApplyOptions {
+                                 patch: value_t!(matches, cli::PATCH, PathBuf).unwrap(),
+                                 dry_run: matches.is_present(cli::DRY_RUN),
+                             })
+        }
+        Some(cli::CMD_ARCHIVE) => {
+            let matches = matches.subcommand_matches(cli::CMD_ARCHIVE).unwrap();
+
+            RunOption::Archive(archive::ArchiveOptions {
+                                   output: value_t!(matches, cli::OUTPUT, PathBuf).unwrap(),
+                                   format: value_t!(matches, cli::FORMAT, String).unwrap(),
+                               })
+        }
+        Some(cli::CMD_AUDIT) => {
+            let matches = matches.subcommand_matches(cli::CMD_AUDIT).unwrap();
+
+            match matches.subcommand_name() {
+                Some(cli::CMD_CONFLICTS) => RunOption::Audit(AuditOption::Conflicts),
+                Some(cli::CMD_DETACHED) => RunOption::Audit(AuditOption::Detached),
+                Some(cli::CMD_DIVERGED) => RunOption::Audit(AuditOption::Diverged),
+                Some(cli::CMD_DUPLICATES) => RunOption::Audit(AuditOption::Duplicates),
+                Some(cli::CMD_HOOKS) => {
+                    let matches = matches.subcommand_matches(cli::CMD_HOOKS).unwrap();
+                    let required = values_t!(matches, cli::REQUIRE, String).unwrap();
+
+                    RunOption::Audit(AuditOption::Hooks(required))
+                }
+                Some(cli::CMD_IDENTITY) => {
+                    let matches = matches.subcommand_matches(cli::CMD_IDENTITY).unwrap();
+
+                    RunOption::Audit(AuditOption::Identity(IdentityOptions {
+                                                               expect: matches.value_of(cli::EXPECT).map(String::from),
+                                                               remote: matches.value_of(cli::REMOTE).map(String::from),
+                                                           }))
+                }
+                Some(cli::CMD_LFS) => RunOption::Audit(AuditOption::Lfs),
+                Some(cli::CMD_NO_REMOTE) => RunOption::Audit(AuditOption::NoRemote),
+                Some(cli::CMD_REACHABILITY) => RunOption::Audit(AuditOption::Reachability),
+                Some(cli::CMD_STALE) => RunOption::Audit(AuditOption::Stale),
+                Some(cli::CMD_UNPUSHED) => RunOption::Audit(AuditOption::Unpushed),
+                _ => unreachable!("audit requires a subcommand"),
+            }
+        }
+        Some(cli::CMD_BIGFILES) => {
+            let matches = matches.subcommand_matches(cli::CMD_BIGFILES).unwrap();
+            RunOption::Bigfiles(bigfiles::BigfilesOptions { min_size: value_t!(matches, cli::MIN_SIZE, u64).unwrap() })
+        }
+        Some(cli::CMD_BRANCH) => {
+            let matches = matches.subcommand_matches(cli::CMD_BRANCH).unwrap();
+
+            match matches.subcommand() {
+                (cli::CMD_CREATE, Some(matches)) => {
+                    RunOption::Branch(BranchOption::Create(CreateOptions {
+                                                               name: value_t!(matches, cli::BRANCH, String).unwrap(),
+                                                               from: matches.value_of(cli::FROM).map(String::from),
+                                                               checkout: matches.is_present(cli::CHECKOUT),
+                                                           }))
+                }
+                (cli::CMD_DELETE, Some(matches)) => {
+                    RunOption::Branch(BranchOption::Delete(DeleteOptions {
+                                                               name: value_t!(matches, cli::BRANCH, String).unwrap(),
+                                                               force: matches.is_present(cli::FORCE),
+                                                           }))
+                }
+                _ => RunOption::Branch(BranchOption::List),
+            }
+        }
         Some(cli::CMD_CHECKOUT) => {
             let branch_match = matches.subcommand_matches(cli::CMD_CHECKOUT).unwrap();
-            let branch = value_t!(branch_match, cli::BRANCH, String).unwrap();
-            RunOption::Checkout(branch)
+            let branch = branch_match.value_of(cli::BRANCH).map(String::from);
+            RunOption::Checkout(branch, prompt(branch_match))
+        }
+        Some(cli::CMD_CHERRY_PICK) => {
+            let matches = matches.subcommand_matches(cli::CMD_CHERRY_PICK).unwrap();
+            let target = value_t!(matches, cli::COMMIT, String).unwrap();
+
+            RunOption::CherryPick(cherry_pick::CherryPickOptions { target: target })
+        }
+        Some(cli::CMD_CLEAN) => {
+            let matches = matches.subcommand_matches(cli::CMD_CLEAN).unwrap();
+
+            RunOption::Clean(clean::CleanOptions {
+                                 dry_run: matches.is_present(cli::DRY_RUN),
+                                 ignored: matches.is_present(cli::IGNORED),
+                                 prompt: prompt(matches),
+                             })
+        }
+        Some(cli::CMD_BUNDLE) => {
+            let matches = matches.subcommand_matches(cli::CMD_BUNDLE).unwrap();
+            RunOption::Bundle(bundle::BundleOptions { output: value_t!(matches, cli::OUTPUT, PathBuf).unwrap() })
+        }
+        Some(cli::CMD_CLONE) => {
+            let matches = matches.subcommand_matches(cli::CMD_CLONE).unwrap();
+
+            match matches.value_of(cli::FROM_BUNDLES) {
+                Some(dir) => RunOption::CloneBundles(PathBuf::from(dir)),
+                None => {
+                    RunOption::Clone(clone::CloneOptions {
+                                         url: value_t!(matches, cli::URL, String).unwrap(),
+                                         path: matches.value_of(cli::PATH).map(PathBuf::from),
+                                         template: matches.value_of(cli::TEMPLATE).map(PathBuf::from),
+                                         email: matches.value_of(cli::EMAIL).map(String::from),
+                                         depth: matches.value_of(cli::DEPTH).and_then(|d| d.parse().ok()),
+                                     })
+                }
+            }
+        }
+        Some(cli::CMD_COMMIT) => {
+            let matches = matches.subcommand_matches(cli::CMD_COMMIT).unwrap();
+
+            RunOption::Commit(commit::CommitOptions {
+                                  message: value_t!(matches, cli::MESSAGE, String).unwrap(),
+                                  dry_run: matches.is_present(cli::DRY_RUN),
+                              })
+        }
+        Some(cli::CMD_CONFIG) => {
+            let matches = matches.subcommand_matches(cli::CMD_CONFIG).unwrap();
+
+            let (command, matches) = matches.subcommand();
+            let matches = matches.expect("config requires a subcommand");
+            let key = value_t!(matches, cli::KEY, String).unwrap();
+
+            match command {
+                cli::CMD_SET => {
+                    let value = value_t!(matches, cli::VALUE, String).unwrap();
+                    RunOption::Config(ConfigOption::Set(key, value))
+                }
+                _ => RunOption::Config(ConfigOption::Get(key)),
+            }
+        }
+        Some(cli::CMD_CONTAINS) => {
+            let matches = matches.subcommand_matches(cli::CMD_CONTAINS).unwrap();
+            RunOption::Contains(value_t!(matches, cli::REV, String).unwrap())
+        }
+        Some(cli::CMD_CONTRIBUTORS) => {
+            let matches = matches.subcommand_matches(cli::CMD_CONTRIBUTORS).unwrap();
+
+            RunOption::Contributors(contributors::ContributorsOptions {
+                                        since: matches.value_of(cli::SINCE).and_then(util::parse_date),
+                                        by_repo: matches.is_present(cli::BY_REPO),
+                                    })
+        }
+        Some(cli::CMD_DIFF) => RunOption::Diff,
+        Some(cli::CMD_DIFFSTAT) => {
+            let matches = matches.subcommand_matches(cli::CMD_DIFFSTAT).unwrap();
+            RunOption::Diffstat(diffstat::DiffstatOptions { base: matches.value_of(cli::BASE).map(String::from) })
+        }
+        Some(cli::CMD_DOCTOR) => {
+            let app_root = app_root();
+            let manifest_path = match (profile.as_deref(), workspace_manifest()) {
+                (None, Some(path)) => Some(path),
+                (profile, _) => app_root.as_ref().map(|r| manifest_path_in(r, profile)),
+            };
+            doctor::process_doctor(app_root.as_deref(), manifest_path.as_deref());
+
+            return;
+        }
+        Some(cli::CMD_EXEC) => {
+            let matches = matches.subcommand_matches(cli::CMD_EXEC).unwrap();
+            let command = matches.values_of(cli::COMMAND).unwrap().map(String::from).collect();
+
+            RunOption::Exec(exec::ExecOptions { command: command })
+        }
+        Some(cli::CMD_FETCH) => {
+            let matches = matches.subcommand_matches(cli::CMD_FETCH).unwrap();
+
+            let prune = match (matches.is_present(cli::PRUNE), matches.is_present(cli::PRUNE_TAGS)) {
+                (_, true) => Prune::BranchesAndTags,
+                (true, false) => Prune::Branches,
+                (false, false) => Prune::Nothing,
+            };
+
+            RunOption::Fetch(fetch::FetchOptions { prune: prune })
+        }
+        Some(cli::CMD_FIND_COMMIT) => {
+            let matches = matches.subcommand_matches(cli::CMD_FIND_COMMIT).unwrap();
+
+            RunOption::FindCommit(find_commit::FindCommitOptions {
+                                      pattern: value_t!(matches, cli::GREP, String).unwrap(),
+                                      ignore_case: matches.is_present(cli::IGNORE_CASE),
+                                      since: matches.value_of(cli::SINCE).and_then(util::parse_date),
+                                      until: matches.value_of(cli::UNTIL).and_then(util::parse_date),
+                                      range: matches.value_of(cli::RANGE).map(String::from),
+                                  })
+        }
+        Some(cli::CMD_FSCK) => RunOption::Fsck,
+        Some(cli::CMD_GC) => {
+            let matches = matches.subcommand_matches(cli::CMD_GC).unwrap();
+            RunOption::Gc(gc::GcOptions { aggressive: matches.is_present(cli::AGGRESSIVE) })
+        }
+        Some(cli::CMD_GREP) => {
+            let matches = matches.subcommand_matches(cli::CMD_GREP).unwrap();
+
+            RunOption::Grep(grep::GrepOptions {
+                                pattern: value_t!(matches, cli::PATTERN, String).unwrap(),
+                                ignore_case: matches.is_present(cli::IGNORE_CASE),
+                            })
+        }
+        Some(cli::CMD_HOOKS) => {
+            let matches = matches.subcommand_matches(cli::CMD_HOOKS).unwrap();
+            let matches = matches.subcommand_matches(cli::CMD_INSTALL).unwrap();
+
+            let mode = match (matches.is_present(cli::SYMLINK), matches.is_present(cli::HOOKS_PATH)) {
+                (true, _) => hooks::InstallMode::Symlink,
+                (_, true) => hooks::InstallMode::HooksPath,
+                _ => hooks::InstallMode::Copy,
+            };
+
+            RunOption::HooksInstall(hooks::InstallOptions {
+                                        dir: value_t!(matches, cli::DIR, PathBuf).unwrap(),
+                                        mode: mode,
+                                        force: matches.is_present(cli::FORCE),
+                                    })
+        }
+        Some(cli::CMD_INIT) => {
+            let matches = matches.subcommand_matches(cli::CMD_INIT).unwrap();
+
+            RunOption::Init(init::InitOptions {
+                                name: value_t!(matches, cli::NAME, PathBuf).unwrap(),
+                                branch: matches.value_of(cli::INITIAL_BRANCH).map(String::from),
+                                template: matches.value_of(cli::TEMPLATE).map(PathBuf::from),
+                            })
+        }
+        Some(cli::CMD_LFS) => {
+            let matches = matches.subcommand_matches(cli::CMD_LFS).unwrap();
+
+            match matches.subcommand_name() {
+                Some(cli::CMD_PULL) => RunOption::Lfs(lfs::LfsOption::Pull),
+                _ => RunOption::Lfs(lfs::LfsOption::Status),
+            }
+        }
+        Some(cli::CMD_LOG) => {
+            let matches = matches.subcommand_matches(cli::CMD_LOG).unwrap();
+            RunOption::Log(log::LogOptions { since: matches.value_of(cli::SINCE).and_then(util::parse_date).unwrap() })
         }
         Some(cli::CMD_MANIFEST) => {
             let matches = matches.subcommand_matches(cli::CMD_MANIFEST).unwrap();
 
             match matches.subcommand_name() {
+                Some(cli::CMD_ADD) => {
+                    let matches = matches.subcommand_matches(cli::CMD_ADD).unwrap();
+                    RunOption::Manifest(ManifestOption::Add(PathBuf::from(matches.value_of(cli::PATH).unwrap())))
+                }
                 Some(cli::CMD_CLEAN) => RunOption::Manifest(ManifestOption::Clean),
+                Some(cli::CMD_EXPORT) => {
+                    let matches = matches.subcommand_matches(cli::CMD_EXPORT).unwrap();
+                    let file = PathBuf::from(matches.value_of(cli::FILE).unwrap());
+
+                    let format = matches.value_of(cli::FORMAT)
+                        .and_then(ManifestFormat::from_name)
+                        .unwrap_or(ManifestFormat::from_path(&file));
+                    RunOption::Manifest(ManifestOption::Export(file, format))
+                }
+                Some(cli::CMD_IGNORE) => {
+                    let matches = matches.subcommand_matches(cli::CMD_IGNORE).unwrap();
+
+                    let glob = matches.value_of(cli::GLOB).map(String::from);
+                    RunOption::Manifest(ManifestOption::Ignore(glob, matches.is_present(cli::REMOVE)))
+                }
+                Some(cli::CMD_IMPORT) => {
+                    let matches = matches.subcommand_matches(cli::CMD_IMPORT).unwrap();
+
+                    let file = PathBuf::from(matches.value_of(cli::FILE).unwrap());
+                    let depth = matches.value_of(cli::DEPTH).and_then(|d| d.parse().ok());
+                    RunOption::Manifest(ManifestOption::Import(file, restore::RestoreOptions { depth: depth }))
+                }
+                Some(cli::CMD_LIST) => RunOption::Manifest(ManifestOption::List),
+                Some(cli::CMD_MERGE) => {
+                    let matches = matches.subcommand_matches(cli::CMD_MERGE).unwrap();
+                    RunOption::Manifest(ManifestOption::Merge(PathBuf::from(matches.value_of(cli::FILE).unwrap())))
+                }
+                Some(cli::CMD_PRUNE) => {
+                    let matches = matches.subcommand_matches(cli::CMD_PRUNE).unwrap();
+                    RunOption::Manifest(ManifestOption::Prune(matches.is_present(cli::DRY_RUN)))
+                }
+                Some(cli::CMD_REMOVE) => {
+                    let matches = matches.subcommand_matches(cli::CMD_REMOVE).unwrap();
+                    RunOption::Manifest(ManifestOption::Remove(PathBuf::from(matches.value_of(cli::PATH).unwrap())))
+                }
+                Some(cli::CMD_RESTORE) => {
+                    let matches = matches.subcommand_matches(cli::CMD_RESTORE).unwrap();
+
+                    let depth = matches.value_of(cli::DEPTH).and_then(|d| d.parse().ok());
+
+                    RunOption::Manifest(ManifestOption::Restore(restore::RestoreOptions { depth: depth }))
+                }
+                Some(cli::CMD_TAG) => {
+                    let matches = matches.subcommand_matches(cli::CMD_TAG).unwrap();
+
+                    let path = PathBuf::from(matches.value_of(cli::PATH).unwrap());
+                    let group = matches.value_of(cli::NAME).map(String::from);
+                    RunOption::Manifest(ManifestOption::Tag(path, group, matches.is_present(cli::REMOVE)))
+                }
                 Some(cli::CMD_UPDATE) => RunOption::Manifest(ManifestOption::Update),
+                Some(cli::CMD_VERIFY) => RunOption::Manifest(ManifestOption::Verify),
                 _ => RunOption::Manifest(ManifestOption::Preview),
             }
         }
+        Some(cli::CMD_MERGE_UPSTREAM) => {
+            let matches = matches.subcommand_matches(cli::CMD_MERGE_UPSTREAM).unwrap();
+
+            let mode = match matches.is_present(cli::NO_FF) {
+                true => MergeMode::NoFastForward,
+                false => MergeMode::FastForwardOnly,
+            };
+
+            RunOption::MergeUpstream(merge_upstream::MergeUpstreamOptions { mode: mode })
+        }
+        Some(cli::CMD_MV) => {
+            let matches = matches.subcommand_matches(cli::CMD_MV).unwrap();
+
+            RunOption::Mv(mv::MvOptions {
+                              from: value_t!(matches, cli::FROM, PathBuf).unwrap(),
+                              to: value_t!(matches, cli::TO, PathBuf).unwrap(),
+                          })
+        }
+        Some(cli::CMD_PRUNE) => RunOption::Prune,
+        Some(cli::CMD_PULL) => {
+            let matches = matches.subcommand_matches(cli::CMD_PULL).unwrap();
+
+            let mode = match matches.is_present(cli::REBASE) {
+                true => PullMode::Rebase { autostash: matches.is_present(cli::AUTOSTASH) },
+                false => PullMode::FastForward,
+            };
+
+            RunOption::Pull(pull::PullOptions { mode: mode })
+        }
+        Some(cli::CMD_PUSH) => RunOption::Push,
+        Some(cli::CMD_REBASE) => RunOption::Rebase,
+        Some(cli::CMD_REMOTE) => {
+            let matches = matches.subcommand_matches(cli::CMD_REMOTE).unwrap();
+
+            match matches.subcommand_matches(cli::CMD_SET_URL) {
+                Some(matches) => {
+                    let rewrite = matches.values_of(cli::REWRITE).unwrap().collect::<Vec<_>>();
+
+                    RunOption::Remote(RemoteOption::SetUrl(SetUrlOptions {
+                                                               from: rewrite[0].to_string(),
+                                                               to: rewrite[1].to_string(),
+                                                               dry_run: matches.is_present(cli::DRY_RUN),
+                                                           }))
+                }
+                None => RunOption::Remote(RemoteOption::List),
+            }
+        }
         Some(cli::CMD_COMPLETIONS) => {
             if let Some(ref matches) = matches.subcommand_matches(cli::CMD_COMPLETIONS) {
-                let shell = value_t!(matches, cli::SHELL, clap::Shell).unwrap();
-                cli::build_cli().gen_completions_to(cli::APP_NAME, shell, &mut std::io::stdout());
+                let shell = value_t!(matches, cli::SHELL, String).unwrap();
+                completions::generate(&shell);
+            }
+
+            return;
+        }
+        Some(cli::CMD_MAN) => {
+            if let Some(ref matches) = matches.subcommand_matches(cli::CMD_MAN) {
+                let commands = matches.values_of(cli::COMMAND).map(|v| v.collect()).unwrap_or_else(Vec::new);
+                man::generate(&commands, matches.value_of(cli::OUT_DIR).map(Path::new));
+            }
+
+            return;
+        }
+        Some(cli::CMD_COMPLETE_BRANCHES) => {
+            RunOption::CompleteBranches(completions::BranchSet::from_env())
+        }
+        Some(cli::CMD_COMPLETE_REPOS) => {
+            let manifest_path = build_manifest_path(profile.as_deref());
+            let manifest = Manifest::open(&manifest_path, &roots[0]);
+            completions::print_repos(&manifest);
+
+            return;
+        }
+        Some(cli::CMD_RESET) => {
+            let matches = matches.subcommand_matches(cli::CMD_RESET).unwrap();
+            RunOption::Reset(prompt(matches))
+        }
+        Some(cli::CMD_RM) => {
+            let matches = matches.subcommand_matches(cli::CMD_RM).unwrap();
+
+            RunOption::Rm(rm::RmOptions {
+                              path: value_t!(matches, cli::PATH, PathBuf).unwrap(),
+                              force: matches.is_present(cli::FORCE),
+                          })
+        }
+        Some(cli::CMD_SELF_UPDATE) => {
+            let matches = matches.subcommand_matches(cli::CMD_SELF_UPDATE).unwrap();
+            self_update::process_self_update(&self_update::SelfUpdateOptions {
+                check: matches.is_present(cli::CHECK),
+            });
+
+            return;
+        }
+        Some(cli::CMD_SIZES) => {
+            let matches = matches.subcommand_matches(cli::CMD_SIZES).unwrap();
+
+            RunOption::Sizes(sizes::SizesOptions {
+                                 sort: value_t!(matches, cli::SORT, sizes::SizeSort).unwrap(),
+                                 blobs: value_t!(matches, cli::BLOBS, usize).unwrap(),
+                             })
+        }
+        Some(cli::CMD_SUBMODULE) => {
+            let matches = matches.subcommand_matches(cli::CMD_SUBMODULE).unwrap();
+            let matches = matches.subcommand_matches(cli::CMD_UPDATE).unwrap();
+
+            let recursive = matches.is_present(cli::RECURSIVE);
+
+            RunOption::SubmoduleUpdate(submodule::SubmoduleUpdateOptions { recursive: recursive })
+        }
+        Some(cli::CMD_TAG) => {
+            let matches = matches.subcommand_matches(cli::CMD_TAG).unwrap();
+
+            RunOption::Tag(tag::TagOptions {
+                               name: value_t!(matches, cli::NAME, String).unwrap(),
+                               message: matches.value_of(cli::MESSAGE).map(String::from),
+                           })
+        }
+        Some(cli::CMD_TUI) => RunOption::Tui,
+        Some(cli::CMD_UNSHALLOW) => RunOption::Unshallow,
+        Some(cli::CMD_STASH) => {
+            let matches = matches.subcommand_matches(cli::CMD_STASH).unwrap();
+
+            match matches.subcommand_name() {
+                Some(cli::CMD_LIST) => RunOption::Stash(StashOption::List),
+                Some(cli::CMD_POP) => RunOption::Stash(StashOption::Pop),
+                _ => RunOption::Stash(StashOption::Save),
+            }
+        }
+        Some(cli::CMD_SPARSE) => {
+            let matches = matches.subcommand_matches(cli::CMD_SPARSE).unwrap();
+
+            match matches.subcommand() {
+                (cli::CMD_SET, Some(matches)) => {
+                    let patterns = value_t!(matches, cli::PATTERNS, PathBuf).unwrap();
+
+                    RunOption::Sparse(SparseOption::Set(SparseSetOptions {
+                                                            patterns: patterns,
+                                                            cone: matches.is_present(cli::CONE),
+                                                        }))
+                }
+                (_, matches) => {
+                    let expected = matches.and_then(|m| m.value_of(cli::PATTERNS)).map(PathBuf::from);
+                    RunOption::Sparse(SparseOption::Status(expected))
+                }
             }
+        }
+        Some(cli::CMD_STANDUP) => {
+            let matches = matches.subcommand_matches(cli::CMD_STANDUP).unwrap();
+
+            RunOption::Standup(standup::StandupOptions {
+                                   since: matches.value_of(cli::SINCE).and_then(util::parse_date).unwrap(),
+                                   author: value_t!(matches, cli::AUTHOR, String).unwrap(),
+                               })
+        }
+        Some(cli::CMD_STATS) => {
+            let matches = matches.subcommand_matches(cli::CMD_STATS).unwrap();
+
+            RunOption::Stats(stats::StatsOptions {
+                                 since: matches.value_of(cli::SINCE).and_then(util::parse_date).unwrap(),
+                                 json: matches.is_present(cli::JSON),
+                             })
+        }
+        Some(cli::CMD_STATUS) => {
+            let filter = repo_filter(&matches);
+            let grouped = !groups(&matches).is_empty();
+            let matches = matches.subcommand_matches(cli::CMD_STATUS).unwrap();
+            let last_commit = matches.is_present(cli::LAST_COMMIT);
+            let stale = matches.value_of(cli::STALE).and_then(|d| d.parse().ok());
+            let off_default = matches.is_present(cli::OFF_DEFAULT);
+
+            RunOption::Status(status::StatusOptions {
+                                  last_commit: last_commit,
+                                  stale: stale,
+                                  off_default: off_default,
+                                  short: matches.is_present(cli::SHORT),
+                                  watch: matches.is_present(cli::WATCH),
+                                  cached: matches.is_present(cli::CACHED),
+                                  refresh: matches.is_present(cli::REFRESH),
+                                  // A filtered view would hide repos from the next --cached run.
+                                  cache: match stale.is_some() || off_default || grouped {
+                                      true => None,
+                                      false => status_cache_path(&roots, &filter, last_commit),
+                                  },
+                              })
+        }
+        Some(cli::CMD_VERSION) => {
+            let matches = matches.subcommand_matches(cli::CMD_VERSION).unwrap();
+            version::print_version(matches.is_present(cli::VERBOSE));
 
             return;
         }
-        Some(cli::CMD_RESET) => RunOption::Reset,
 
         // By default, just show status.
-        _ => RunOption::Status,
+        _ => {
+            RunOption::Status(status::StatusOptions {
+                                  cache: match groups(&matches).is_empty() {
+                                      true => status_cache_path(&roots, &repo_filter(&matches), false),
+                                      false => None,
+                                  },
+                                  ..Default::default()
+                              })
+        }
     };
 
-    process(option, &working_dir);
+    process(option, &roots, repo_filter(&matches), &groups(&matches), profile.as_deref());
+}
+
+// Relative roots are taken from the current directory. Without any, that's
+// the only root.
+fn roots(matches: &clap::ArgMatches, profile: Option<&str>) -> Vec<PathBuf> {
+    let current_dir = env::current_dir().expect("Could not get working directory");
+
+    // Given after the subcommand, they only land in the subcommand's matches.
+    let roots = Some(matches)
+        .into_iter()
+        .chain(matches.subcommand().1)
+        .filter_map(|m| m.values_of(cli::ROOT))
+        .flat_map(|v| v.map(|r| current_dir.join(r)))
+        .collect::<Vec<_>>();
+
+    // A profile or workspace manifest brings its own root, once it has one.
+    match (roots.is_empty(), profile.is_some() || workspace_manifest().is_some()) {
+        (true, true) => {
+            let manifest_path = build_manifest_path(profile);
+            vec![Manifest::open(&manifest_path, &current_dir).root().to_path_buf()]
+        }
+        (true, false) => vec![current_dir],
+        (false, _) => roots,
+    }
 }
 
-fn process(option: RunOption, path: &Path) {
-    let manifest_path = build_manifest_path();
-    let mut manifest = Manifest::open(&manifest_path, &path);
+fn profile(matches: &clap::ArgMatches) -> Option<String> {
+    // Global args given after the subcommand only land in its matches.
+    Some(matches)
+        .into_iter()
+        .chain(matches.subcommand().1)
+        .filter_map(|m| m.value_of(cli::PROFILE))
+        .map(String::from)
+        .next_back()
+}
+
+fn repo_filter(matches: &clap::ArgMatches) -> Vec<PathBuf> {
+    // Global args given after the subcommand only land in its matches.
+    let sub_matches = matches.subcommand().1;
+
+    Some(matches)
+        .into_iter()
+        .chain(sub_matches)
+        .filter_map(|m| m.values_of(cli::REPO))
+        .flat_map(|v| v.map(PathBuf::from))
+        .collect()
+}
+
+fn groups(matches: &clap::ArgMatches) -> Vec<String> {
+    // Global args given after the subcommand only land in its matches.
+    Some(matches)
+        .into_iter()
+        .chain(matches.subcommand().1)
+        .filter_map(|m| m.values_of(cli::GROUP))
+        .flat_map(|v| v.map(String::from))
+        .collect()
+}
+
+fn prompt(matches: &clap::ArgMatches) -> Prompt {
+    match matches.is_present(cli::INTERACTIVE) {
+        true => Prompt::PerRepo,
+        false => Prompt::Never,
+    }
+}
+
+fn process(option: RunOption, roots: &[PathBuf], repo_filter: Vec<PathBuf>, groups: &[String], profile: Option<&str>) {
+    // A manifest only ever covers one root.
+    let path = &roots[0];
+    let manifest_path = build_manifest_path(profile);
+    let mut manifest = Manifest::open(&manifest_path, path);
 
     if let RunOption::Manifest(ref m) = option {
         match *m {
+            ManifestOption::Add(ref entry) => manifest_add(&mut manifest, entry),
             ManifestOption::Clean => manifest_clean(&manifest_path),
-            ManifestOption::Preview => manifest_preview(path),
+            ManifestOption::Export(ref file, format) => manifest_export(&manifest, file, format),
+            ManifestOption::Import(ref file, ref opts) => manifest_import(path, &mut manifest, file, opts),
+            ManifestOption::List => manifest_list(profile),
+            ManifestOption::Merge(ref file) => {
+                let missing = manifest_merge(&mut manifest, file) &&
+                              manifest.entries().iter().any(|e| !manifest.root().join(e).exists());
+
+                if missing {
+                    let hint = format!("Some entries aren't on disk; run `{} manifest restore` to clone them",
+                                       cli::APP_NAME);
+                    println!("{}", BrightYellow.paint(hint));
+                }
+            }
+            ManifestOption::Ignore(ref glob, remove) => manifest_ignore(&mut manifest, glob.as_deref(), remove),
+            ManifestOption::Preview => manifest_preview(path, &manifest),
+            ManifestOption::Prune(dry_run) => manifest_prune(&mut manifest, dry_run),
+            ManifestOption::Remove(ref entry) => manifest_remove(&mut manifest, entry),
+            ManifestOption::Restore(ref opts) => {
+                restore::process_restore(&manifest, &ThreadPool::new(num_cpus::get()), opts)
+            }
+            ManifestOption::Tag(ref entry, ref group, remove) => {
+                manifest_tag(&mut manifest, entry, group.as_deref(), remove)
+            }
             ManifestOption::Update => manifest_update(path, &mut manifest),
+            ManifestOption::Verify => {
+                if !verify::process_verify(&manifest_path, path) {
+                    process::exit(1);
+                }
+            }
         }
 
         return;
     }
 
-    let repos = match manifest.is_empty() {
-        true => GitRepositories::new(path),
-        false => GitRepositories::from_manifest(&manifest),
-    };
+    match option {
+        RunOption::Clone(ref opts) => return clone::process_clone(&mut manifest, path, opts),
+        RunOption::CloneBundles(ref dir) => return bundle::process_clone_bundles(&mut manifest, path, dir),
+        RunOption::Init(ref opts) => return init::process_init(&mut manifest, path, opts),
+        RunOption::Mv(ref opts) => return mv::process_mv(&mut manifest, path, opts),
+        RunOption::Rm(ref opts) => return rm::process_rm(&mut manifest, opts),
+        _ => (),
+    }
+
+    // Groups only exist in the manifest, so an unknown one would otherwise
+    // quietly match nothing.
+    if let Some(group) = groups.iter().find(|g| manifest.group_members(g).is_empty()) {
+        println!("{}", BrightRed.paint(format!("No repositories in the manifest are tagged {}", group)));
+        return;
+    }
+
+    // A manifest for some other workspace says nothing about this one.
+    let repos = roots
+        .iter()
+        .map(|root| match (manifest.is_empty(), manifest.path_in_manifest(root)) {
+                 (_, false) => GitRepositories::new(root),
+                 (true, true) => GitRepositories::new(root).exclude(manifest.excludes()),
+                 (false, true) => GitRepositories::from_manifest(&manifest),
+             })
+        .reduce(GitRepositories::chain)
+        .expect("There is always at least one root")
+        .in_groups(&manifest, groups)
+        .only(repo_filter);
 
     let pool = {
         let thread_count = num_cpus::get();
@@ -104,56 +836,346 @@ fn process(option: RunOption, path: &Path) {
     };
 
     match option {
-        RunOption::Reset => {
+        RunOption::Add(ref opts) => add::process_add(repos, &pool, opts),
+        RunOption::Apply(ref opts) => apply::process_apply(repos, &pool, opts),
+        RunOption::Archive(ref opts) => archive::process_archive(repos, &pool, roots, opts),
+        RunOption::Audit(ref audit) => audit::process_audit(repos, &pool, audit),
+        RunOption::Bigfiles(ref opts) => bigfiles::process_bigfiles(repos, &pool, opts),
+        RunOption::Branch(ref option) => branch::process_branch(repos, &pool, option),
+        RunOption::Bundle(ref opts) => bundle::process_bundle(repos, &pool, roots, opts),
+        RunOption::Checkout(ref branch, prompt) => checkout_repos(repos, branch.as_deref(), prompt),
+        RunOption::CherryPick(ref opts) => cherry_pick::process_cherry_pick(repos, &pool, opts),
+        RunOption::Clean(ref opts) => clean::process_clean(repos, &pool, opts),
+        RunOption::Commit(ref opts) => commit::process_commit(repos, &pool, opts),
+        RunOption::CompleteBranches(set) => completions::print_branches(repos, &pool, set),
+        RunOption::Config(ref option) => config::process_config(repos, &pool, option),
+        RunOption::Contains(ref rev) => contains::process_contains(repos, &pool, rev),
+        RunOption::Contributors(ref opts) => contributors::process_contributors(repos, &pool, opts),
+        RunOption::Diff => diff::process_diff(repos, &pool),
+        RunOption::Diffstat(ref opts) => diffstat::process_diffstat(repos, &pool, opts),
+        RunOption::Exec(ref opts) => exec::process_exec(repos, &pool, opts),
+        RunOption::Fetch(ref opts) => fetch::process_fetch(repos, &pool, opts),
+        RunOption::FindCommit(ref opts) => find_commit::process_find_commit(repos, &pool, opts),
+        RunOption::Fsck => fsck::process_fsck(repos, &pool),
+        RunOption::Gc(ref opts) => gc::process_gc(repos, &pool, opts),
+        RunOption::Grep(ref opts) => grep::process_grep(repos, &pool, opts),
+        RunOption::HooksInstall(ref opts) => hooks::process_install(repos, &pool, opts),
+        RunOption::Lfs(ref option) => lfs::process_lfs(repos, &pool, option),
+        RunOption::Log(ref opts) => log::process_log(repos, &pool, opts),
+        RunOption::MergeUpstream(ref opts) => merge_upstream::process_merge_upstream(repos, &pool, opts),
+        RunOption::Prune => prune::process_prune(repos, &pool),
+        RunOption::Pull(ref opts) => pull::process_pull(repos, &pool, opts),
+        RunOption::Push => push::process_push(repos, &pool),
+        RunOption::Rebase => rebase::process_rebase(repos, &pool),
+        RunOption::Remote(ref option) => remote::process_remote(repos, &pool, option),
+        RunOption::Reset(Prompt::Never) => {
             let rx = reset(repos, &pool);
 
             while let Ok((path, head)) = rx.recv() {
-                let branch = BrightCyan.paint(head);
-                let l_brace = BrightYellow.paint("[");
-                let r_brace = BrightYellow.paint("]");
-
-                println!("  {}{}{}  {}", l_brace, branch, r_brace, path.display());
+                print_reset(&path, &head);
             }
         }
-        RunOption::Status => status::process_status(repos, &pool),
+        RunOption::Reset(Prompt::PerRepo) => reset_interactive(repos),
+        RunOption::Sizes(ref opts) => sizes::process_sizes(repos, &pool, opts),
+        RunOption::Sparse(ref option) => sparse::process_sparse(repos, &pool, option),
+        RunOption::Stash(option) => stash::process_stash(repos, &pool, option),
+        RunOption::Standup(ref opts) => standup::process_standup(repos, &pool, opts),
+        RunOption::Stats(ref opts) => stats::process_stats(repos, &pool, opts),
+        RunOption::Status(ref opts) => status::process_status(repos, &pool, opts),
+        RunOption::SubmoduleUpdate(ref opts) => submodule::process_submodule_update(repos, &pool, opts),
+        RunOption::Tag(ref opts) => tag::process_tag(repos, &pool, opts),
+        RunOption::Tui => tui::process_tui(repos, &pool, roots),
+        RunOption::Unshallow => unshallow::process_unshallow(repos, &pool),
         _ => panic!("Unhandled run option"),
     }
 }
 
-fn build_manifest_path() -> PathBuf {
+fn app_root() -> Option<PathBuf> {
     const APP_INFO: AppInfo = AppInfo {
         name: "git-plz",
         author: "devnought",
     };
 
-    let root = app_dirs::get_app_root(AppDataType::UserCache, &APP_INFO)
-        .expect("Could not locate app settings directory");
-    let mut path = PathBuf::from(root);
-    path.push("manifest.json");
+    app_dirs::get_app_root(AppDataType::UserCache, &APP_INFO).ok()
+}
+
+// One cache per set of repositories looked at, so a run over some other
+// workspace doesn't replace this one's.
+fn status_cache_path(roots: &[PathBuf], repo_filter: &[PathBuf], last_commit: bool) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    (roots, repo_filter, last_commit).hash(&mut hasher);
 
-    path
+    app_root().map(|r| r.join(STATUS_CACHE_DIR).join(format!("{:016x}.json", hasher.finish())))
+}
+
+// Without a profile, a manifest kept in the workspace wins over the one in
+// the app data directory.
+fn build_manifest_path(profile: Option<&str>) -> PathBuf {
+    if let (None, Some(path)) = (profile, workspace_manifest()) {
+        return path;
+    }
+
+    let app_root = app_root().expect("Could not locate app settings directory");
+    manifest_path_in(&app_root, profile)
+}
+
+// The nearest one in the working directory or above it.
+fn workspace_manifest() -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok()?;
+
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(WORKSPACE_MANIFEST))
+        .find(|path| path.is_file())
+}
+
+// Without a profile it's the manifest there's always been, so existing
+// setups carry on as the default.
+fn manifest_path_in(app_root: &Path, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => app_root.join(MANIFEST_DIR).join(format!("{}.json", name)),
+        None => app_root.join(MANIFEST_FILE),
+    }
 }
 
 fn manifest_update<P>(path: P, manifest: &mut Manifest)
     where P: AsRef<Path>
 {
-    let repos = GitRepositories::new(path);
+    let repos = GitRepositories::new(path).exclude(manifest.excludes());
 
     manifest.add_repositories(repos);
 
     println!("{:#?}", &manifest);
 }
 
-fn manifest_preview<P>(path: P)
+fn manifest_preview<P>(path: P, manifest: &Manifest)
     where P: AsRef<Path>
 {
-    let repos = GitRepositories::new(path);
+    let repos = GitRepositories::new(path).exclude(manifest.excludes());
 
     for repo in repos {
         println!("{}", repo.path().display());
     }
 }
 
+// Entries are relative to the manifest's root, which is the working
+// directory until the manifest has one of its own.
+fn manifest_add(manifest: &mut Manifest, entry: &Path) {
+    let path = manifest.root().join(entry);
+
+    let repo = match GitRepo::new(&path) {
+        Ok(r) => r,
+        Err(_) => {
+            println!("{}", BrightRed.paint(format!("{} is not a repository", path.display())));
+            return;
+        }
+    };
+
+    if manifest.entries().contains(entry) {
+        println!("{}", BrightYellow.paint(format!("{} is already in the manifest", entry.display())));
+        return;
+    }
+
+    manifest.add_repository(&repo);
+    println!("{}", BrightCyan.paint(format!("Added {}", entry.display())));
+}
+
+fn manifest_remove(manifest: &mut Manifest, entry: &Path) {
+    let path = manifest.root().join(entry);
+
+    match manifest.remove_repository(&path) {
+        true => println!("{}", BrightCyan.paint(format!("Removed {}", entry.display()))),
+        false => println!("{}", BrightYellow.paint(format!("{} wasn't in the manifest", entry.display()))),
+    }
+}
+
+fn manifest_export(manifest: &Manifest, file: &Path, format: ManifestFormat) {
+    if manifest.is_empty() {
+        println!("{}", BrightRed.paint("The manifest is empty; nothing to export"));
+        return;
+    }
+
+    match manifest.export(file, format) {
+        Ok(_) => println!("{}", BrightCyan.paint(format!("Exported {} entries to {}", manifest.len(), file.display()))),
+        Err(_) => println!("{}", BrightRed.paint(format!("Could not write {}", file.display()))),
+    }
+}
+
+// Restores afterwards, so importing is all a teammate needs on a fresh root.
+fn manifest_import(root: &Path, manifest: &mut Manifest, file: &Path, options: &restore::RestoreOptions) {
+    if !manifest.is_empty() && manifest.root() != root {
+        println!("{}",
+                 BrightRed.paint(format!("The manifest is for {}; use --{} to import into another",
+                                         manifest.root().display(),
+                                         cli::PROFILE)));
+        return;
+    }
+
+    if manifest_merge(manifest, file) {
+        restore::process_restore(manifest, &ThreadPool::new(num_cpus::get()), options);
+    }
+}
+
+// False if the other manifest couldn't be used at all.
+fn manifest_merge(manifest: &mut Manifest, file: &Path) -> bool {
+    let merge = match manifest.merge(file) {
+        Ok(m) => m,
+        Err(e) => {
+            let problem = match e {
+                ManifestError::PathNotFound => format!("{} does not exist", file.display()),
+                ManifestError::Invalid => format!("{} is not a manifest", file.display()),
+                ManifestError::OutsideRoot => {
+                    format!("{} has entries outside the root, e.g. absolute or with ..; nothing was added",
+                            file.display())
+                }
+                _ => format!("Could not read {}", file.display()),
+            };
+
+            println!("{}", BrightRed.paint(problem));
+            return false;
+        }
+    };
+
+    for conflict in &merge.conflicts {
+        println!("{}  {}",
+                 conflict.entry.display(),
+                 BrightYellow.paint(format!("origin {} kept over {}", conflict.ours, conflict.theirs)));
+    }
+
+    println!("{}", BrightCyan.paint(format!("Added {} new entries", merge.added)));
+
+    if !merge.conflicts.is_empty() {
+        println!("{}", BrightYellow.paint(format!("{} entries have a different origin there", merge.conflicts.len())));
+    }
+
+    true
+}
+
+fn manifest_prune(manifest: &mut Manifest, dry_run: bool) {
+    let stale = match dry_run {
+        true => manifest.stale_entries(),
+        false => manifest.prune(),
+    };
+
+    for entry in &stale {
+        println!("{}  {}", entry.display(), BrightYellow.paint("no longer a repository"));
+    }
+
+    match (stale.len(), dry_run) {
+        (0, _) => println!("{}", BrightCyan.paint("Every entry is still a repository")),
+        (n, true) => println!("{}", BrightCyan.paint(format!("Would drop {} entries", n))),
+        (n, false) => println!("{}", BrightCyan.paint(format!("Dropped {} entries", n))),
+    }
+}
+
+fn manifest_ignore(manifest: &mut Manifest, glob: Option<&str>, remove: bool) {
+    let glob = match glob {
+        Some(g) => g,
+        None => {
+            match manifest.excludes().is_empty() {
+                true => println!("No excludes"),
+                false => manifest.excludes().iter().for_each(|x| println!("{}", x)),
+            }
+
+            return;
+        }
+    };
+
+    let changed = match remove {
+        true => manifest.remove_exclude(glob),
+        false => manifest.add_exclude(glob),
+    };
+
+    match (remove, changed) {
+        (false, true) => println!("{}", BrightCyan.paint(format!("Excluding {}", glob))),
+        (false, false) => println!("{}", BrightYellow.paint(format!("{} is already excluded", glob))),
+        (true, true) => println!("{}", BrightCyan.paint(format!("No longer excluding {}", glob))),
+        (true, false) => println!("{}", BrightYellow.paint(format!("{} wasn't excluded", glob))),
+    }
+}
+
+fn manifest_tag(manifest: &mut Manifest, entry: &Path, group: Option<&str>, remove: bool) {
+    if !manifest.entries().contains(entry) {
+        println!("{}", BrightRed.paint(format!("{} is not in the manifest", entry.display())));
+        return;
+    }
+
+    let group = match group {
+        Some(g) => g,
+        None => {
+            match manifest.groups(entry) {
+                Some(groups) => groups.iter().for_each(|g| println!("{}", g)),
+                None => println!("No groups"),
+            }
+
+            return;
+        }
+    };
+
+    let changed = match remove {
+        true => manifest.remove_from_group(entry, group),
+        false => manifest.add_to_group(entry, group),
+    };
+
+    let entry = entry.display();
+
+    match (remove, changed) {
+        (false, true) => println!("{}", BrightCyan.paint(format!("Tagged {} {}", entry, group))),
+        (false, false) => println!("{}", BrightYellow.paint(format!("{} is already tagged {}", entry, group))),
+        (true, true) => println!("{}", BrightCyan.paint(format!("Untagged {} {}", entry, group))),
+        (true, false) => println!("{}", BrightYellow.paint(format!("{} wasn't tagged {}", entry, group))),
+    }
+}
+
+fn manifest_list(current: Option<&str>) {
+    let app_root = app_root().expect("Could not locate app settings directory");
+
+    let mut profiles = fs::read_dir(app_root.join(MANIFEST_DIR))
+        .map(|entries| {
+                 entries
+                     .filter_map(|e| e.ok())
+                     .map(|e| e.path())
+                     .filter(|p| p.extension().is_some_and(|e| e == "json"))
+                     .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
+                     .collect::<Vec<_>>()
+             })
+        .unwrap_or_default();
+    profiles.sort();
+
+    // A workspace manifest is what's used in place of the default.
+    let workspace = workspace_manifest();
+
+    if let Some(ref path) = workspace {
+        list_manifest(current.is_none(), "(workspace)", path);
+    }
+
+    for profile in Some(None).into_iter().chain(profiles.iter().map(|p| Some(p.as_str()))) {
+        let current = profile == current && (profile.is_some() || workspace.is_none());
+        list_manifest(current, profile.unwrap_or("(default)"), &manifest_path_in(&app_root, profile));
+    }
+}
+
+fn list_manifest(current: bool, name: &str, path: &Path) {
+    let marker = match current {
+        true => "*",
+        false => " ",
+    };
+
+    match Manifest::validate(path) {
+        Ok(count) => {
+            let manifest = Manifest::open(&path, &path);
+
+            println!("{} {}  {}  {}",
+                     marker,
+                     BrightCyan.paint(name),
+                     manifest.root().display(),
+                     BrightYellow.paint(format!("{} repositories", count)))
+        }
+        Err(ManifestError::PathNotFound) => println!("{} {}  no manifest", marker, BrightCyan.paint(name)),
+        Err(_) => println!("{} {}  unreadable manifest", marker, BrightCyan.paint(name)),
+    }
+}
+
 fn manifest_clean<P>(manifest_path: P)
     where P: AsRef<Path>
 {
@@ -166,14 +1188,62 @@ fn manifest_clean<P>(manifest_path: P)
 }
 
 fn checkout(repo: &GitRepo, branch: &str) -> Result<(), GitError> {
-    repo.checkout(branch)?;
+    let kind = match repo.checkout(branch)? {
+        GitBranch::Local => "[Local]",
+        GitBranch::Remote => "[Remote]",
+    };
 
     println!("{}", repo.path().display());
-    println!("    {}", BrightCyan.paint(branch));
+    println!("    {} {}", BrightCyan.paint(branch), kind);
 
     Ok(())
 }
 
+fn checkout_repos(repos: GitRepositories, branch: Option<&str>, prompt: Prompt) {
+    let mut approval = Approval::new(prompt);
+
+    for repo in repos {
+        let branch = match branch.map(String::from).or_else(|| repo.default_branch()) {
+            Some(b) => b,
+            None => {
+                println!("{}  {}", repo.path().display(), BrightYellow.paint("no default branch"));
+                continue;
+            }
+        };
+
+        if prompt == Prompt::PerRepo {
+            // Repos without the branch have nothing to approve.
+            let changes = match repo.checkout_preview(&branch) {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+
+            println!("{}", repo.path().display());
+
+            for path in &changes {
+                println!("    {}", path.display());
+            }
+
+            if changes.is_empty() {
+                println!("    No file changes");
+            }
+        }
+
+        match approval.ask(&format!("Checkout {}", branch)) {
+            Answer::Yes => (),
+            Answer::No => continue,
+            Answer::Quit => return,
+        }
+
+        // Repos without the branch are left alone, but a path Windows can't
+        // write needs the user to act.
+        if let Err(GitError::PathTooLong(path)) = checkout(&repo, &branch) {
+            let message = format!("{} is too long; set core.longpaths", path.display());
+            println!("{}  {}", repo.path().display(), BrightRed.paint(message));
+        }
+    }
+}
+
 fn reset(repos: GitRepositories, pool: &ThreadPool) -> Receiver<(PathBuf, String)> {
     let (tx, rx) = channel();
 
@@ -181,29 +1251,61 @@ fn reset(repos: GitRepositories, pool: &ThreadPool) -> Receiver<(PathBuf, String
         let tx = tx.clone();
 
         pool.execute(move || {
-            match repo.statuses() {
-                Ok(s) => {
-                    if s.len() == 0 {
-                        return;
-                    }
-                }
-                _ => (),
+            if let Some(head) = reset_repo(&repo) {
+                let tuple = (repo.path().to_path_buf(), head);
+                tx.send(tuple).expect(THREAD_SIGNAL);
             }
+        });
+    }
 
-            match repo.remove_untracked() {
-                Err(_) => return,
-                _ => (),
-            }
+    rx
+}
 
-            let head = match repo.reset() {
-                Ok(r) => r,
-                _ => return,
-            };
+fn reset_interactive(repos: GitRepositories) {
+    let mut approval = Approval::new(Prompt::PerRepo);
 
-            let tuple = (repo.path().to_path_buf(), head.name().to_string());
-            tx.send(tuple).expect(THREAD_SIGNAL);
-        });
+    for repo in repos {
+        // Stashes survive a reset, so only the working tree matters here.
+        let status = match status::read_status(&repo) {
+            Some(s) => s,
+            None => continue,
+        };
+
+        if status.list.is_empty() {
+            continue;
+        }
+
+        status::print_status(repo.path(), &status);
+
+        match approval.ask("Reset") {
+            Answer::Yes => (),
+            Answer::No => continue,
+            Answer::Quit => return,
+        }
+
+        if let Some(head) = reset_repo(&repo) {
+            print_reset(repo.path(), &head);
+        }
     }
+}
 
-    rx
+fn reset_repo(repo: &GitRepo) -> Option<String> {
+    if let Ok(s) = repo.statuses() {
+        if s.len() == 0 {
+            return None;
+        }
+    }
+
+    repo.remove_untracked().ok()?;
+
+    let head = repo.reset().ok()?;
+    Some(head.name().to_string())
+}
+
+fn print_reset(path: &Path, head: &str) {
+    let branch = BrightCyan.paint(head);
+    let l_brace = BrightYellow.paint("[");
+    let r_brace = BrightYellow.paint("]");
+
+    println!("  {}{}{}  {}", l_brace, branch, r_brace, path.display());
 }
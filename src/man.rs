@@ -0,0 +1,186 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use clap::ErrorKind;
+
+use cli;
+
+const SECTION: &str = "1";
+const TERM_WIDTH: usize = 80;
+
+// A page per command, named the way man expects: "git-plz-manifest-clean".
+struct Page {
+    path: Vec<String>,
+    about: Option<String>,
+    children: Vec<String>,
+}
+
+impl Page {
+    fn name(&self) -> String {
+        page_name(&self.path)
+    }
+
+    fn parent(&self) -> Option<String> {
+        match self.path.len() {
+            0 => None,
+            n => Some(page_name(&self.path[..n - 1])),
+        }
+    }
+}
+
+pub fn generate(commands: &[&str], out_dir: Option<&Path>) {
+    let pages = pages();
+
+    match out_dir {
+        Some(dir) => {
+            fs::create_dir_all(dir).expect("Could not create man page directory");
+
+            for page in &pages {
+                let file = dir.join(format!("{}.{}", page.name(), SECTION));
+                fs::write(&file, render(page)).expect("Could not write man page");
+                println!("{}", file.display());
+            }
+        }
+        None => {
+            let page = pages.iter().find(|p| p.path.iter().map(String::as_str).eq(commands.iter().cloned()));
+
+            match page {
+                Some(page) => {
+                    io::stdout()
+                        .write_all(render(page).as_bytes())
+                        .expect("Could not write man page")
+                }
+                None => {
+                    clap::Error::with_description(&format!("No such command: {}", commands.join(" ")),
+                                                  ErrorKind::InvalidSubcommand)
+                            .exit()
+                }
+            }
+        }
+    }
+}
+
+// The root page, then one per command in the order cli.rs defines them.
+fn pages() -> Vec<Page> {
+    let root = Some((&[][..], cli::ABOUT));
+
+    root.into_iter()
+        .chain(cli::COMMANDS.iter().cloned())
+        .map(|(path, about)| {
+                 Page {
+                     path: path.iter().map(|c| c.to_string()).collect(),
+                     about: Some(about.to_string()),
+                     children: children(path),
+                 }
+             })
+        .collect()
+}
+
+fn children(parent: &[&str]) -> Vec<String> {
+    cli::COMMANDS
+        .iter()
+        .filter(|c| c.0.len() == parent.len() + 1 && c.0.starts_with(parent))
+        .map(|c| c.0[parent.len()].to_string())
+        .collect()
+}
+
+fn page_name(path: &[String]) -> String {
+    let mut name = String::from(cli::BIN_NAME);
+
+    for command in path {
+        name.push('-');
+        name.push_str(command);
+    }
+
+    name
+}
+
+fn help(page: &Page) -> String {
+    let args = Some(cli::BIN_NAME)
+        .into_iter()
+        .chain(page.path.iter().map(String::as_str))
+        .chain(Some("--help"));
+
+    match cli::build_visible_cli()
+              .set_term_width(TERM_WIDTH)
+              .get_matches_from_safe(args) {
+        Err(ref e) if e.kind == ErrorKind::HelpDisplayed => e.message.clone(),
+        Err(e) => e.exit(),
+        Ok(_) => unreachable!("--help always short-circuits"),
+    }
+}
+
+fn render(page: &Page) -> String {
+    let name = page.name();
+    let mut out = String::new();
+
+    out.push_str(&format!(".TH \"{}\" \"{}\" \"\" \"{} {}\"\n",
+                          name.to_uppercase(),
+                          SECTION,
+                          cli::BIN_NAME,
+                          cli::VERSION));
+    out.push_str(".SH NAME\n");
+    out.push_str(&format!("{} \\- {}\n", escape(&name), escape(page.about.as_ref().map_or("", String::as_str))));
+
+    // Everything before USAGE is the name/version/about banner, which the
+    // header above already covers.
+    let help = help(page);
+    let mut in_section = false;
+
+    for line in help.lines().skip_while(|l| *l != "USAGE:") {
+        if is_heading(line) {
+            if in_section {
+                out.push_str(".fi\n");
+            }
+
+            let heading = match line.trim_end_matches(':') {
+                "USAGE" => "SYNOPSIS",
+                heading => heading,
+            };
+
+            out.push_str(&format!(".SH {}\n.nf\n", heading));
+            in_section = true;
+        } else if !line.is_empty() {
+            out.push_str(&escape_line(line));
+            out.push('\n');
+        }
+    }
+
+    if in_section {
+        out.push_str(".fi\n");
+    }
+
+    let see_also = page.parent()
+        .into_iter()
+        .chain(page.children.iter().map(|c| format!("{}-{}", name, c)))
+        .map(|p| format!("\\fB{}\\fR({})", escape(&p), SECTION))
+        .collect::<Vec<_>>();
+
+    if !see_also.is_empty() {
+        out.push_str(".SH \"SEE ALSO\"\n");
+        out.push_str(&see_also.join(", "));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn is_heading(line: &str) -> bool {
+    line.ends_with(':') && !line.starts_with(' ') &&
+    line.chars().all(|c| c.is_ascii_uppercase() || c == ' ' || c == ':')
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+// Lines starting with a dot or quote would otherwise be read as requests.
+fn escape_line(line: &str) -> String {
+    let line = escape(line);
+
+    match line.starts_with('.') || line.starts_with('\'') {
+        true => format!("\\&{}", line),
+        false => line,
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::{GitHead, MergeMode, MergeResult};
+use pull::commits;
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct MergeUpstreamOptions {
+    pub mode: MergeMode,
+}
+
+// Unlike pull this doesn't fetch, so it merges whatever was fetched last.
+pub fn process_merge_upstream(repos: GitRepositories, pool: &ThreadPool, options: &MergeUpstreamOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let mode = options.mode;
+
+        pool.execute(move || {
+                         let result = repo.merge_upstream(mode);
+                         tx.send((repo.path().to_path_buf(), (repo.head_state(), result))).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, (head, result)) in &results {
+        let message = match *result {
+            Ok(MergeResult::UpToDate) => styled(BrightBlack, "up to date"),
+            Ok(MergeResult::FastForwarded(n)) => styled(BrightGreen, format!("fast-forwarded {}", commits(n))),
+            Ok(MergeResult::Merged(n)) => styled(BrightCyan, format!("merged {}", commits(n))),
+            Ok(MergeResult::Diverged(ahead, behind)) => {
+                styled(BrightYellow, format!("diverged (⇡{} ⇣{}); skipped, try --no-ff", ahead, behind))
+            }
+            Ok(MergeResult::Conflicted) => styled(BrightRed, "merge conflicted; aborted"),
+            Ok(MergeResult::NoUpstream) => styled(BrightBlack, "no upstream"),
+            Err(_) => styled(BrightRed, "could not merge"),
+        };
+
+        let branch = match *head {
+            GitHead::Branch(ref name) => name.clone(),
+            GitHead::Detached(ref id) => format!("detached @ {}", id),
+            GitHead::Unborn => String::from("unborn"),
+        };
+
+        println!("  {}{}{}  {}  {}",
+                 BrightYellow.paint("["),
+                 BrightCyan.paint(branch),
+                 BrightYellow.paint("]"),
+                 path.display(),
+                 message.paint());
+    }
+
+    let updated = results
+        .values()
+        .filter(|(_, r)| matches!(*r, Ok(MergeResult::FastForwarded(_)) | Ok(MergeResult::Merged(_))))
+        .count();
+
+    let conflicted = results
+        .values()
+        .filter(|(_, r)| matches!(*r, Ok(MergeResult::Conflicted)))
+        .count();
+
+    match conflicted {
+        0 => println!("{} of {} repositories updated", updated, results.len()),
+        n => println!("{} of {} repositories updated; {} need merging by hand", updated, results.len(), n),
+    }
+}
@@ -0,0 +1,54 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed};
+use term_painter::ToStyle;
+
+use gitlib::GitRepo;
+use util::Manifest;
+
+#[derive(Debug, Clone)]
+pub struct MvOptions {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+pub fn process_mv(manifest: &mut Manifest, root: &Path, options: &MvOptions) {
+    let from = root.join(&options.from);
+    let to = root.join(&options.to);
+
+    if GitRepo::new(&from).is_err() {
+        println!("{}", BrightRed.paint(format!("{} is not a repository", from.display())));
+        return;
+    }
+
+    if to.exists() {
+        println!("{}", BrightRed.paint(format!("{} already exists", to.display())));
+        return;
+    }
+
+    // A plain rename can't cross filesystems; rather than copy a repo
+    // half-way, leave that to the user.
+    let moved = to.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::rename(&from, &to));
+
+    if let Err(e) = moved {
+        println!("{}", BrightRed.paint(format!("Could not move {}: {}", from.display(), e)));
+        return;
+    }
+
+    let repo = match GitRepo::new(&to) {
+        Ok(r) => r,
+        Err(_) => {
+            println!("{}", BrightRed.paint(format!("Moved, but {} no longer opens as a repository", to.display())));
+            return;
+        }
+    };
+
+    if !manifest.move_repository(&from, &repo) {
+        println!("{}", BrightBlack.paint("Not in the manifest; nothing to update"));
+    }
+
+    println!("{}", BrightGreen.paint(format!("Moved {} to {}", from.display(), to.display())));
+}
@@ -0,0 +1,51 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+// Only repos that lost something, or couldn't be fetched, are listed.
+pub fn process_prune(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let result = repo.prune();
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut pruned = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref branches) if branches.is_empty() => (),
+            Ok(ref branches) => {
+                println!("{}", path.display());
+
+                for branch in branches {
+                    println!("  {} {}", BrightYellow.paint("pruned"), branch);
+                }
+
+                pruned += branches.len();
+            }
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not fetch")),
+        }
+    }
+
+    match pruned {
+        0 => println!("No stale remote-tracking branches"),
+        1 => println!("Pruned 1 remote-tracking branch"),
+        n => println!("Pruned {} remote-tracking branches", n),
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::{GitHead, PullMode, PullResult};
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct PullOptions {
+    pub mode: PullMode,
+}
+
+pub fn process_pull(repos: GitRepositories, pool: &ThreadPool, options: &PullOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let mode = options.mode;
+
+        pool.execute(move || {
+                         let result = repo.pull(mode);
+                         tx.send((repo.path().to_path_buf(), (repo.head_state(), result))).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, (head, result)) in &results {
+        let message = match *result {
+            Ok(PullResult::UpToDate) => styled(BrightBlack, "up to date"),
+            Ok(PullResult::FastForwarded(n)) => styled(BrightGreen, format!("fast-forwarded {}", commits(n))),
+            Ok(PullResult::Rebased(n)) => styled(BrightCyan, format!("rebased {}", commits(n))),
+            Ok(PullResult::AutostashConflicted(n)) => {
+                styled(BrightYellow,
+                       format!("rebased {}; stashed changes conflicted and are still stashed", commits(n)))
+            }
+            Ok(PullResult::Diverged(ahead, behind)) => {
+                styled(BrightYellow, format!("diverged (⇡{} ⇣{}); skipped, try --rebase", ahead, behind))
+            }
+            Ok(PullResult::Conflicted) => styled(BrightRed, "rebase conflicted; aborted"),
+            Ok(PullResult::NoUpstream) => styled(BrightBlack, "no upstream"),
+            Err(_) => styled(BrightRed, "could not pull"),
+        };
+
+        // Laid out like reset's output, with the branch that was pulled.
+        let branch = match *head {
+            GitHead::Branch(ref name) => name.clone(),
+            GitHead::Detached(ref id) => format!("detached @ {}", id),
+            GitHead::Unborn => String::from("unborn"),
+        };
+
+        println!("  {}{}{}  {}  {}",
+                 BrightYellow.paint("["),
+                 BrightCyan.paint(branch),
+                 BrightYellow.paint("]"),
+                 path.display(),
+                 message.paint());
+    }
+
+    let updated = results
+        .values()
+        .map(|(_, r)| r)
+        .filter(|r| {
+                    matches!(**r,
+                             Ok(PullResult::FastForwarded(_)) |
+                             Ok(PullResult::Rebased(_)) |
+                             Ok(PullResult::AutostashConflicted(_)))
+                })
+        .count();
+
+    println!("{} of {} repositories updated", updated, results.len());
+}
+
+pub fn commits(n: usize) -> String {
+    match n {
+        1 => String::from("1 commit"),
+        n => format!("{} commits", n),
+    }
+}
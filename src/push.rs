@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use threadpool::ThreadPool;
+
+use gitlib::PushResult;
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+pub fn process_push(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let result = repo.push();
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let (mut pushed, mut skipped, mut rejected) = (0, 0, 0);
+
+    for (path, result) in &results {
+        let message = match *result {
+            Ok(PushResult::Pushed(n)) => {
+                pushed += 1;
+                styled(BrightGreen,
+                       match n {
+                           1 => String::from("pushed 1 commit"),
+                           n => format!("pushed {} commits", n),
+                       })
+            }
+            Ok(PushResult::UpToDate) => styled(BrightBlack, "up to date"),
+            Ok(PushResult::NoUpstream) => {
+                skipped += 1;
+                styled(BrightBlack, "no upstream; skipped")
+            }
+            Ok(PushResult::Behind(n)) => {
+                skipped += 1;
+                styled(BrightYellow, format!("⇣{} behind upstream; skipped, pull first", n))
+            }
+            Ok(PushResult::Rejected(ref reason)) => {
+                rejected += 1;
+                styled(BrightRed, format!("rejected: {}", reason))
+            }
+            Err(_) => {
+                rejected += 1;
+                styled(BrightRed, "could not push")
+            }
+        };
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    println!("{} pushed, {} skipped, {} rejected", pushed, skipped, rejected);
+}
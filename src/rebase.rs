@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::{GitHead, RebaseResult};
+use pull::commits;
+use styled::styled;
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+// Rebases onto whatever was fetched last, then lists every repo that was
+// skipped or stopped so they can be dealt with by hand.
+pub fn process_rebase(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let result = repo.rebase_upstream();
+                         tx.send((repo.path().to_path_buf(), (repo.head_state(), result))).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut attention = Vec::new();
+
+    for (path, (head, result)) in &results {
+        let message = match *result {
+            Ok(RebaseResult::UpToDate) => styled(BrightBlack, "up to date"),
+            Ok(RebaseResult::FastForwarded(n)) => styled(BrightGreen, format!("fast-forwarded {}", commits(n))),
+            Ok(RebaseResult::Rebased(n)) => styled(BrightCyan, format!("rebased {}", commits(n))),
+            Ok(RebaseResult::Dirty) => {
+                attention.push((path, "has local changes"));
+                styled(BrightYellow, "local changes; skipped")
+            }
+            Ok(RebaseResult::Conflicted) => {
+                attention.push((path, "conflicts with its upstream"));
+                styled(BrightRed, "rebase conflicted; aborted")
+            }
+            Ok(RebaseResult::NoUpstream) => styled(BrightBlack, "no upstream"),
+            Err(_) => {
+                attention.push((path, "could not be rebased"));
+                styled(BrightRed, "could not rebase")
+            }
+        };
+
+        let branch = match *head {
+            GitHead::Branch(ref name) => name.clone(),
+            GitHead::Detached(ref id) => format!("detached @ {}", id),
+            GitHead::Unborn => String::from("unborn"),
+        };
+
+        println!("  {}{}{}  {}  {}",
+                 BrightYellow.paint("["),
+                 BrightCyan.paint(branch),
+                 BrightYellow.paint("]"),
+                 path.display(),
+                 message.paint());
+    }
+
+    let updated = results
+        .values()
+        .filter(|(_, r)| matches!(*r, Ok(RebaseResult::FastForwarded(_)) | Ok(RebaseResult::Rebased(_))))
+        .count();
+
+    println!("{} of {} repositories updated", updated, results.len());
+
+    if !attention.is_empty() {
+        println!();
+        println!("{}", BrightYellow.paint("Needs attention:"));
+
+        for (path, reason) in attention {
+            println!("  {}  {}", path.display(), reason);
+        }
+    }
+}
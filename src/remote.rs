@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::{GitError, GitRepo};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub enum RemoteOption {
+    List,
+    SetUrl(SetUrlOptions),
+}
+
+// Rewrites URLs starting with `from` to start with `to` instead.
+#[derive(Debug, Clone)]
+pub struct SetUrlOptions {
+    pub from: String,
+    pub to: String,
+    pub dry_run: bool,
+}
+
+struct Row {
+    name: String,
+    url: String,
+    // Only set when pushes go somewhere other than `url`.
+    push_url: Option<String>,
+}
+
+struct Rewrite {
+    remote: String,
+    push: bool,
+    old: String,
+    new: String,
+}
+
+pub fn process_remote(repos: GitRepositories, pool: &ThreadPool, option: &RemoteOption) {
+    match *option {
+        RemoteOption::List => list(repos, pool),
+        RemoteOption::SetUrl(ref opts) => set_url(repos, pool, opts),
+    }
+}
+
+fn list(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let rows = rows(&repo);
+                         tx.send((repo.path().display().to_string(), rows)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    // Padding has to be worked out before painting, which adds escape codes.
+    let path_width = results.keys().map(|p| p.chars().count()).max().unwrap_or(0);
+    let name_width = results
+        .values()
+        .filter_map(|r| r.as_ref().ok())
+        .flat_map(|rows| rows.iter().map(|row| row.name.chars().count()))
+        .max()
+        .unwrap_or(0);
+
+    let mut remotes = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref rows) if rows.is_empty() => {
+                println!("{:<width$}  {}", path, BrightBlack.paint("no remotes"), width = path_width)
+            }
+            Ok(ref rows) => {
+                for row in rows {
+                    let name = format!("{:<width$}", row.name, width = name_width);
+                    print!("{:<width$}  {}  {}", path, BrightYellow.paint(name), row.url, width = path_width);
+
+                    match row.push_url {
+                        Some(ref push) => println!("  {}", BrightBlack.paint(format!("(push {})", push))),
+                        None => println!(),
+                    }
+                }
+
+                remotes += rows.len();
+            }
+            Err(_) => println!("{:<width$}  {}", path, BrightRed.paint("could not read remotes"), width = path_width),
+        }
+    }
+
+    println!("{} remotes across {} repositories", remotes, results.len());
+}
+
+fn set_url(repos: GitRepositories, pool: &ThreadPool, options: &SetUrlOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let result = rewrite(&repo, &options);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut rewritten = 0;
+    let mut repos = 0;
+
+    for (path, result) in &results {
+        match *result {
+            Ok(ref rewrites) if rewrites.is_empty() => (),
+            Ok(ref rewrites) => {
+                println!("{}", path.display());
+
+                for r in rewrites {
+                    let label = match r.push {
+                        true => format!("{} (push)", r.remote),
+                        false => r.remote.clone(),
+                    };
+
+                    println!("  {}  {} -> {}", BrightYellow.paint(label), r.old, BrightGreen.paint(&r.new));
+                }
+
+                rewritten += rewrites.len();
+                repos += 1;
+            }
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not update remotes")),
+        }
+    }
+
+    let urls = match rewritten {
+        1 => "URL",
+        _ => "URLs",
+    };
+
+    match (rewritten, options.dry_run) {
+        (0, _) => println!("No remote URLs start with {}", options.from),
+        (n, true) => println!("Would rewrite {} {} in {} repositories", n, urls, repos),
+        (n, false) => println!("Rewrote {} {} in {} repositories", n, urls, repos),
+    }
+}
+
+// Stops at the first URL that can't be written, so a repo may end up half done.
+fn rewrite(repo: &GitRepo, options: &SetUrlOptions) -> Result<Vec<Rewrite>, GitError> {
+    let mut rewrites = Vec::new();
+
+    for remote in repo.remotes()? {
+        let push_url = match remote.has_separate_push_url() {
+            true => remote.push_url(),
+            false => None,
+        };
+
+        let urls = remote.url().map(|u| (false, u)).into_iter().chain(push_url.map(|u| (true, u)));
+
+        for (push, url) in urls {
+            if !url.starts_with(&options.from) {
+                continue;
+            }
+
+            let new = format!("{}{}", options.to, &url[options.from.len()..]);
+
+            if !options.dry_run {
+                match push {
+                    true => repo.set_remote_push_url(remote.name(), &new)?,
+                    false => repo.set_remote_url(remote.name(), &new)?,
+                }
+            }
+
+            rewrites.push(Rewrite {
+                              remote: remote.name().to_string(),
+                              push: push,
+                              old: url.to_string(),
+                              new: new,
+                          });
+        }
+    }
+
+    Ok(rewrites)
+}
+
+fn rows(repo: &GitRepo) -> Result<Vec<Row>, GitError> {
+    let rows = repo.remotes()?
+        .iter()
+        .map(|r| {
+                 Row {
+                     name: r.name().to_string(),
+                     url: r.url().unwrap_or("").to_string(),
+                     push_url: match r.has_separate_push_url() {
+                         true => r.push_url().map(String::from),
+                         false => None,
+                     },
+                 }
+             })
+        .collect();
+
+    Ok(rows)
+}
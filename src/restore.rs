@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitRepo;
+use util::Manifest;
+
+use cli;
+use styled::styled;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    pub depth: Option<usize>,
+}
+
+enum Restore {
+    Present,
+    NoOrigin,
+    Cloned,
+    Failed,
+}
+
+// Clones every manifest entry that's missing from disk back into place from
+// its recorded origin, so a fresh machine ends up with the same workspace.
+pub fn process_restore(manifest: &Manifest, pool: &ThreadPool, options: &RestoreOptions) {
+    if manifest.is_empty() {
+        println!("{}", BrightRed.paint("The manifest is empty; nothing to restore"));
+        return;
+    }
+
+    let (tx, rx) = channel();
+    let mut results = BTreeMap::new();
+
+    for entry in manifest.entries() {
+        let path = manifest.root().join(entry);
+
+        if path.exists() {
+            results.insert(path, Restore::Present);
+            continue;
+        }
+
+        let url = match manifest.origin(entry) {
+            Some(u) => u.to_string(),
+            None => {
+                results.insert(path, Restore::NoOrigin);
+                continue;
+            }
+        };
+
+        let tx = tx.clone();
+        let depth = options.depth;
+
+        pool.execute(move || {
+                         let result = match GitRepo::clone_url(&url, &path, None, depth) {
+                             Ok(_) => Restore::Cloned,
+                             Err(_) => Restore::Failed,
+                         };
+
+                         tx.send((path, result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+    results.extend(rx.iter());
+
+    let mut cloned = 0;
+
+    for (path, result) in &results {
+        let message = match *result {
+            Restore::Present => styled(BrightBlack, "already present"),
+            Restore::NoOrigin => styled(BrightYellow, "no origin recorded"),
+            Restore::Cloned => {
+                cloned += 1;
+                styled(BrightGreen, "cloned")
+            }
+            Restore::Failed => styled(BrightRed, "clone failed"),
+        };
+
+        println!("{}  {}", path.display(), message.paint());
+    }
+
+    println!("Cloned {} of {} repositories", cloned, results.len());
+
+    if results.values().any(|r| matches!(*r, Restore::NoOrigin)) {
+        println!("{}",
+                 BrightBlack.paint(format!("Origins are recorded by `{} manifest update` wherever the repositories exist",
+                                           cli::APP_NAME)));
+    }
+
+    // Their entries stay listed, and every run skips them with a warning.
+    let missing = results.values().filter(|r| matches!(**r, Restore::NoOrigin | Restore::Failed)).count();
+
+    if missing > 0 {
+        println!("{}",
+                 BrightYellow.paint(format!("{} entries are still missing; `{} manifest prune` drops them",
+                                            missing,
+                                            cli::APP_NAME)));
+    }
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+
+use gitlib::GitRepo;
+use util::Manifest;
+
+#[derive(Debug, Clone)]
+pub struct RmOptions {
+    pub path: PathBuf,
+    pub force: bool,
+}
+
+// Relative paths are taken from the working directory, which isn't always
+// the root: a workspace manifest's root is wherever the file is.
+pub fn process_rm(manifest: &mut Manifest, options: &RmOptions) {
+    let path = env::current_dir().expect("Could not get working directory").join(&options.path);
+
+    let repo = match GitRepo::new(&path) {
+        Ok(r) => r,
+        Err(_) => {
+            println!("{}", BrightRed.paint(format!("{} is not a repository", path.display())));
+            return;
+        }
+    };
+
+    let mut reasons = unsaved_work(&repo);
+
+    // Status skips submodules and ignored files, so look inside any repos
+    // nested in this one separately.
+    for nested in nested_repos(&path) {
+        let relative = nested.strip_prefix(&path).unwrap_or(&nested).display().to_string();
+
+        match GitRepo::new(&nested) {
+            Ok(r) => reasons.extend(unsaved_work(&r).into_iter().map(|r| format!("{}: {}", relative, r))),
+            Err(_) => reasons.push(format!("{}: could not open nested repository", relative)),
+        }
+    }
+
+    for reason in &reasons {
+        println!("{}  {}", path.display(), BrightYellow.paint(reason));
+    }
+
+    if !reasons.is_empty() && !options.force {
+        println!("{}", BrightRed.paint("Refusing to remove a repository with work that exists nowhere else; use --force"));
+        return;
+    }
+
+    // Close the repo before its files go.
+    drop(repo);
+
+    if let Err(e) = fs::remove_dir_all(&path) {
+        println!("{}", BrightRed.paint(format!("Could not remove {}: {}", path.display(), e)));
+        return;
+    }
+
+    if !manifest.remove_repository(&path) {
+        println!("{}", BrightBlack.paint("Not in the manifest; nothing to update"));
+    }
+
+    println!("{}", BrightGreen.paint(format!("Removed {}", path.display())));
+}
+
+// Anything that would be lost for good with the directory. A repo that can't
+// be inspected counts too, since there's no telling what it holds.
+fn unsaved_work(repo: &GitRepo) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    match repo.statuses() {
+        Ok(ref s) if s.len() == 0 => (),
+        Ok(s) => reasons.push(format!("{} uncommitted changes", s.len())),
+        Err(_) => reasons.push(String::from("could not read status")),
+    }
+
+    match repo.stash_count() {
+        Ok(0) => (),
+        Ok(n) => reasons.push(format!("{} stashed", n)),
+        Err(_) => reasons.push(String::from("could not read stashes")),
+    }
+
+    match repo.unpushed() {
+        Ok(branches) => {
+            for (branch, commits) in branches {
+                reasons.push(format!("{}: {} unpushed commits", branch, commits.len()));
+            }
+        }
+        Err(_) => reasons.push(String::from("could not check for unpushed commits")),
+    }
+
+    reasons
+}
+
+// Submodules and any other repos inside `dir`, at any depth. Symlinks aren't
+// followed, since remove_dir_all doesn't follow them either.
+fn nested_repos(dir: &Path) -> Vec<PathBuf> {
+    let mut repos = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = match current.read_dir() {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            if entry.file_name() == ".git" || !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                continue;
+            }
+
+            let path = entry.path();
+
+            if path.join(".git").exists() {
+                repos.push(path.clone());
+            }
+
+            pending.push(path);
+        }
+    }
+
+    repos
+}
@@ -0,0 +1,226 @@
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde_json::{self, Value};
+use term_painter::Color::{BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+
+use util;
+
+use cli;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/drewbrend/gitplz/releases/latest";
+
+// Published alongside the binaries, one "<sha256>  <asset name>" per line.
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+#[derive(Debug, Clone, Default)]
+pub struct SelfUpdateOptions {
+    pub check: bool,
+}
+
+#[derive(Debug)]
+struct Release {
+    tag: String,
+    assets: Vec<(String, String)>,
+}
+
+impl Release {
+    fn asset_url(&self, name: &str) -> Option<&str> {
+        self.assets.iter().find(|a| a.0 == name).map(|a| a.1.as_str())
+    }
+}
+
+pub fn process_self_update(options: &SelfUpdateOptions) {
+    if let Err(e) = self_update(options) {
+        println!("{}", BrightRed.paint(format!("Self-update failed: {}", e)));
+    }
+}
+
+fn self_update(options: &SelfUpdateOptions) -> Result<(), String> {
+    let release = latest_release()?;
+
+    if !is_newer(&release.tag, cli::VERSION) {
+        println!("{} {} is up to date", cli::BIN_NAME, cli::VERSION);
+        return Ok(());
+    }
+
+    println!("{}", BrightYellow.paint(format!("{} -> {} available", cli::VERSION, release.tag)));
+
+    if options.check {
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let url = release.asset_url(&name).ok_or_else(|| format!("release {} has no build named {}", release.tag, name))?;
+    let sums_url = release.asset_url(CHECKSUMS_ASSET)
+        .ok_or_else(|| format!("release {} has no {}", release.tag, CHECKSUMS_ASSET))?;
+
+    let expected = expected_checksum(&download(sums_url)?, &name)
+        .ok_or_else(|| format!("{} has no entry for {}", CHECKSUMS_ASSET, name))?;
+
+    let exe = env::current_exe().map_err(|e| format!("could not locate the running executable: {}", e))?;
+    let staged = sibling(&exe, "new");
+
+    // Download next to the executable so the final rename stays on one filesystem.
+    download_to(url, &staged)?;
+
+    if let Err(e) = verify(&staged, &expected).and_then(|_| replace(&exe, &staged)) {
+        let _ = fs::remove_file(&staged);
+        return Err(e);
+    }
+
+    println!("{}", BrightGreen.paint(format!("Updated to {}", release.tag)));
+    Ok(())
+}
+
+fn latest_release() -> Result<Release, String> {
+    let body = download(LATEST_RELEASE_URL)?;
+    let json: Value = serde_json::from_slice(&body).map_err(|e| format!("unexpected release data: {}", e))?;
+
+    let tag = json["tag_name"].as_str().ok_or("release has no tag")?.to_string();
+    let assets = json["assets"]
+        .as_array()
+        .map(|assets| {
+                 assets
+                     .iter()
+                     .filter_map(|a| match (a["name"].as_str(), a["browser_download_url"].as_str()) {
+                                     (Some(name), Some(url)) => Some((name.to_string(), url.to_string())),
+                                     _ => None,
+                                 })
+                     .collect()
+             })
+        .unwrap_or_default();
+
+    Ok(Release {
+           tag: tag,
+           assets: assets,
+       })
+}
+
+// e.g. "git-plz-linux-x86_64" or "git-plz-windows-x86_64.exe"
+fn asset_name() -> String {
+    format!("{}-{}-{}{}",
+            cli::BIN_NAME,
+            env::consts::OS,
+            env::consts::ARCH,
+            env::consts::EXE_SUFFIX)
+}
+
+fn expected_checksum(sums: &[u8], name: &str) -> Option<String> {
+    String::from_utf8_lossy(sums)
+        .lines()
+        .filter_map(|line| {
+                        let mut parts = line.split_whitespace();
+                        match (parts.next(), parts.next()) {
+                            // sha256sum marks binary-mode entries with a leading '*'.
+                            (Some(sum), Some(file)) if file.trim_start_matches('*') == name => {
+                                Some(sum.to_lowercase())
+                            }
+                            _ => None,
+                        }
+                    })
+        .next()
+}
+
+fn verify(path: &Path, expected: &str) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("could not read download: {}", e))?;
+    let actual = util::sha256(file).map_err(|e| format!("could not read download: {}", e))?;
+
+    match actual == expected {
+        true => Ok(()),
+        false => Err(format!("checksum mismatch: expected {}, got {}", expected, actual)),
+    }
+}
+
+// Windows won't overwrite a running executable but will let it be renamed,
+// so move the old one aside first and only then clean it up.
+fn replace(exe: &Path, staged: &Path) -> Result<(), String> {
+    make_executable(staged)?;
+
+    let old = sibling(exe, "old");
+    let _ = fs::remove_file(&old);
+
+    fs::rename(exe, &old).map_err(|e| format!("could not move {} aside: {}", exe.display(), e))?;
+
+    if let Err(e) = fs::rename(staged, exe) {
+        let _ = fs::rename(&old, exe);
+        return Err(format!("could not install new binary: {}", e));
+    }
+
+    let _ = fs::remove_file(&old);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("could not mark {} executable: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn sibling(exe: &Path, suffix: &str) -> PathBuf {
+    let mut name = exe.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(format!(".{}", suffix));
+    exe.with_file_name(name)
+}
+
+// There's no HTTP client among our dependencies, so lean on curl the same way
+// gitlib leans on git for what libgit2 can't do.
+fn download(url: &str) -> Result<Vec<u8>, String> {
+    let output = curl().arg(url).output().map_err(|e| format!("could not run curl: {}", e))?;
+
+    match output.status.success() {
+        true => Ok(output.stdout),
+        false => Err(format!("could not fetch {}: {}", url, String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+fn download_to(url: &str, path: &Path) -> Result<(), String> {
+    let output = curl()
+        .arg("-o")
+        .arg(path)
+        .arg(url)
+        .output()
+        .map_err(|e| format!("could not run curl: {}", e))?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(format!("could not fetch {}: {}", url, String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+fn curl() -> Command {
+    let mut command = Command::new("curl");
+    command
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .args(["--user-agent", cli::BIN_NAME])
+        .stdin(Stdio::null());
+    command
+}
+
+// Compares dotted version numbers, ignoring a leading "v" on the tag.
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |v: &str| {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse::<u64>().unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+
+    let (mut latest, mut current) = (parse(tag), parse(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+
+    latest > current
+}
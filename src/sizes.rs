@@ -0,0 +1,107 @@
+use std::cmp::Reverse;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitBlob;
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+arg_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SizeSort {
+        Name,
+        Worktree,
+        Git,
+        Blob
+    }
+}
+
+// SizeSort's variants for the argument table, which needs them as a const;
+// arg_enum! only has them at runtime.
+pub const SORTS: &[&str] = &["Name", "Worktree", "Git", "Blob"];
+
+#[derive(Debug, Clone)]
+pub struct SizesOptions {
+    pub sort: SizeSort,
+    pub blobs: usize,
+}
+
+struct RepoSize {
+    path: PathBuf,
+    worktree: u64,
+    git: u64,
+    blobs: Vec<GitBlob>,
+}
+
+impl RepoSize {
+    fn largest_blob(&self) -> u64 {
+        self.blobs.first().map_or(0, GitBlob::size)
+    }
+}
+
+pub fn process_sizes(repos: GitRepositories, pool: &ThreadPool, options: &SizesOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let blobs = options.blobs;
+
+        pool.execute(move || {
+            let size = RepoSize {
+                path: repo.path().to_path_buf(),
+                worktree: util::worktree_size(repo.path()),
+                git: util::dir_size(repo.git_dir()),
+                blobs: match blobs {
+                    0 => Vec::new(),
+                    n => repo.largest_blobs(n).unwrap_or_default(),
+                },
+            };
+
+            tx.send(size).expect(THREAD_SIGNAL);
+        });
+    }
+
+    drop(tx);
+
+    let mut sizes = rx.iter().collect::<Vec<_>>();
+
+    // Everything but the name sorts biggest first.
+    match options.sort {
+        SizeSort::Name => sizes.sort_by(|a, b| a.path.cmp(&b.path)),
+        SizeSort::Worktree => sizes.sort_by_key(|s| Reverse(s.worktree)),
+        SizeSort::Git => sizes.sort_by_key(|s| Reverse(s.git)),
+        SizeSort::Blob => sizes.sort_by_key(|s| Reverse(s.largest_blob())),
+    }
+
+    for size in &sizes {
+        println!("{}  {} {}  {} {}",
+                 size.path.display(),
+                 BrightBlack.paint("worktree"),
+                 BrightCyan.paint(util::format_bytes(size.worktree)),
+                 BrightBlack.paint(".git"),
+                 BrightYellow.paint(util::format_bytes(size.git)));
+
+        for blob in &size.blobs {
+            let name = match blob.path() {
+                Some(p) => p.display().to_string(),
+                None => blob.id().to_string(),
+            };
+
+            println!("  {:>10}  {}", util::format_bytes(blob.size()), name);
+        }
+    }
+
+    let worktree = sizes.iter().map(|s| s.worktree).sum();
+    let git = sizes.iter().map(|s| s.git).sum();
+
+    println!("Total  {} {}  {} {}",
+             BrightBlack.paint("worktree"),
+             BrightCyan.paint(util::format_bytes(worktree)),
+             BrightBlack.paint(".git"),
+             BrightYellow.paint(util::format_bytes(git)));
+}
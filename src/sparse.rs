@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::{GitError, GitRepo, GitSparseCheckout};
+use styled::{self, plain, styled};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub enum SparseOption {
+    Set(SparseSetOptions),
+    // Optionally a patterns file each repo is expected to match.
+    Status(Option<PathBuf>),
+}
+
+#[derive(Debug, Clone)]
+pub struct SparseSetOptions {
+    pub patterns: PathBuf,
+    pub cone: bool,
+}
+
+struct SparseState {
+    sparse: Option<GitSparseCheckout>,
+    present: usize,
+    tracked: usize,
+}
+
+pub fn process_sparse(repos: GitRepositories, pool: &ThreadPool, option: &SparseOption) {
+    match *option {
+        SparseOption::Set(ref opts) => {
+            if let Some(patterns) = read_patterns(&opts.patterns) {
+                set(repos, pool, patterns, opts.cone);
+            }
+        }
+        SparseOption::Status(ref expected) => {
+            match *expected {
+                Some(ref path) => {
+                    if let Some(patterns) = read_patterns(path) {
+                        status(repos, pool, Some(patterns));
+                    }
+                }
+                None => status(repos, pool, None),
+            }
+        }
+    }
+}
+
+fn set(repos: GitRepositories, pool: &ThreadPool, patterns: Vec<String>, cone: bool) {
+    let (tx, rx) = channel();
+    let patterns = Arc::new(patterns);
+
+    for repo in repos {
+        let tx = tx.clone();
+        let patterns = patterns.clone();
+
+        pool.execute(move || {
+                         let result = repo.set_sparse_checkout(&patterns, cone).map(|_| checked_out(&repo));
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, result) in &results {
+        match *result {
+            Ok((present, tracked)) => {
+                println!("{}  {}",
+                         path.display(),
+                         BrightGreen.paint(format!("{} of {} files checked out", present, tracked)))
+            }
+            // Usually local changes to files the new patterns would remove.
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not set sparse checkout")),
+        }
+    }
+
+    let set = results.values().filter(|r| r.is_ok()).count();
+    println!("Set sparse checkout in {} of {} repositories", set, results.len());
+}
+
+fn status(repos: GitRepositories, pool: &ThreadPool, expected: Option<Vec<String>>) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         tx.send((repo.path().to_path_buf(), sparse_state(&repo))).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+    let mut sparse = 0;
+    let mut differs = 0;
+
+    for (path, result) in &results {
+        let state = match *result {
+            Ok(ref s) => s,
+            Err(_) => {
+                println!("{}  {}", path.display(), BrightRed.paint("could not read sparse checkout"));
+                continue;
+            }
+        };
+
+        let mut message = match state.sparse {
+            Some(ref s) => {
+                sparse += 1;
+                vec![plain(format!("{} {}, {} of {} files checked out",
+                                   s.patterns().len(),
+                                   if s.cone() { "directories" } else { "patterns" },
+                                   state.present,
+                                   state.tracked))]
+            }
+            None => vec![styled(BrightBlack, "full checkout")],
+        };
+
+        if let Some(ref expected) = expected {
+            let matches = state.sparse.as_ref().is_some_and(|s| s.patterns() == expected.as_slice());
+
+            match matches {
+                true => message.extend([plain("  "), styled(BrightGreen, "matches")]),
+                false => {
+                    differs += 1;
+                    message.extend([plain("  "), styled(BrightYellow, "differs")]);
+                }
+            }
+        }
+
+        styled::print_repo_line(path, &message);
+    }
+
+    println!("{} of {} repositories are sparse", sparse, results.len());
+
+    if expected.is_some() {
+        println!("{} don't match the expected patterns", differs);
+    }
+}
+
+fn sparse_state(repo: &GitRepo) -> Result<SparseState, GitError> {
+    let sparse = repo.sparse_checkout()?;
+    let (present, tracked) = checked_out(repo);
+
+    Ok(SparseState {
+           sparse: sparse,
+           present: present,
+           tracked: tracked,
+       })
+}
+
+// Files skipped by the patterns are still tracked, just absent on disk.
+fn checked_out(repo: &GitRepo) -> (usize, usize) {
+    let tracked = repo.tracked_paths().unwrap_or_default();
+    let present = tracked.iter().filter(|p| repo.path().join(p).exists()).count();
+
+    (present, tracked.len())
+}
+
+// One pattern per line, skipping blanks and # comments like git does.
+fn read_patterns(path: &Path) -> Option<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => {
+            Some(text.lines()
+                     .map(str::trim)
+                     .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                     .map(String::from)
+                     .collect())
+        }
+        Err(e) => {
+            println!("{}", BrightRed.paint(format!("Could not read {}: {}", path.display(), e)));
+            None
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightBlack, BrightCyan, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::GitCommit;
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+// Stands for whoever user.email says commits in each repo are made by.
+pub const ME: &str = "me";
+
+#[derive(Debug, Clone)]
+pub struct StandupOptions {
+    pub since: i64,
+    pub author: String,
+}
+
+impl StandupOptions {
+    // Anything but "me" is matched, ignoring case, against the author's name
+    // or email. `email` is this repo's user.email.
+    fn is_author(&self, email: Option<&str>, commit: &GitCommit) -> bool {
+        if self.author == ME {
+            return email.is_some_and(|e| commit.email().eq_ignore_ascii_case(e));
+        }
+
+        let author = self.author.to_lowercase();
+        commit.author().to_lowercase().contains(&author) || commit.email().to_lowercase().contains(&author)
+    }
+}
+
+pub fn process_standup(repos: GitRepositories, pool: &ThreadPool, options: &StandupOptions) {
+    let (tx, rx) = channel();
+    let options = Arc::new(options.clone());
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+            let (_, email) = repo.identity().unwrap_or((None, None));
+
+            let mut commits = repo.commits(None, Some(options.since))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|c| options.is_author(email.as_deref(), c))
+                .collect::<Vec<_>>();
+
+            // Read top to bottom, the day in the order it happened.
+            commits.reverse();
+
+            tx.send((repo.path().to_path_buf(), commits)).expect(THREAD_SIGNAL);
+        });
+    }
+
+    drop(tx);
+
+    let found = rx.iter()
+        .filter(|(_, commits)| !commits.is_empty())
+        .collect::<BTreeMap<_, _>>();
+
+    for (path, commits) in &found {
+        println!("{}", BrightCyan.paint(path.display()));
+
+        for commit in commits {
+            println!("  {}  {}  {}",
+                     BrightBlack.paint(util::format_time(commit.time(), commit.offset_minutes())),
+                     BrightYellow.paint(commit.short_id()),
+                     commit.summary());
+        }
+    }
+
+    match found.values().map(Vec::len).sum::<usize>() {
+        0 => println!("No commits since {}", util::format_date(options.since, 0)),
+        n => println!("{} commits across {} repositories", n, found.len()),
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use gitlib::{FileStatus, GitError, GitRepo, StashPop};
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+// Starts every stash message, so ours can be told apart from anyone else's.
+const STASH_PREFIX: &str = "gitplz stash";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StashOption {
+    Save,
+    Pop,
+    List,
+}
+
+pub fn process_stash(repos: GitRepositories, pool: &ThreadPool, option: StashOption) {
+    match option {
+        StashOption::Save => save(repos, pool),
+        StashOption::Pop => pop(repos, pool),
+        StashOption::List => list(repos, pool),
+    }
+}
+
+// Stashes tracked changes in every repo that has some. Untracked files are
+// left where they are, as with a plain `git stash`.
+fn save(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+    let message = format!("{} {}", STASH_PREFIX, util::format_time(util::now(), 0));
+
+    for mut repo in repos.filter(has_changes) {
+        let tx = tx.clone();
+        let message = message.clone();
+
+        pool.execute(move || {
+                         let result = repo.stash(&message);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, result) in &results {
+        match *result {
+            Ok(_) => println!("{}  {}", path.display(), BrightGreen.paint("stashed")),
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not stash")),
+        }
+    }
+
+    match results.len() {
+        0 => println!("Nothing to stash"),
+        n => {
+            println!("Stashed {} of {} repositories as \"{}\"",
+                     results.values().filter(|r| r.is_ok()).count(),
+                     n,
+                     message)
+        }
+    }
+}
+
+// Pops the most recent stash we made, leaving anyone else's alone.
+fn pop(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for mut repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+            let ours = match repo.stashes() {
+                Ok(stashes) => stashes.into_iter().find(|s| s.message().contains(STASH_PREFIX)),
+                Err(_) => None,
+            };
+
+            if let Some(stash) = ours {
+                let result = repo.stash_pop(stash.index());
+                tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+            }
+        });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, result) in &results {
+        match *result {
+            Ok(StashPop::Popped) => println!("{}  {}", path.display(), BrightGreen.paint("popped")),
+            Ok(StashPop::Conflicted) => {
+                println!("{}  {}",
+                         path.display(),
+                         BrightYellow.paint("conflicts with local changes; left in the stash"))
+            }
+            Err(GitError::PathTooLong(ref long)) => {
+                let message = format!("could not pop: {} is too long; set core.longpaths", long.display());
+                println!("{}  {}", path.display(), BrightRed.paint(message))
+            }
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not pop")),
+        }
+    }
+
+    match results.len() {
+        0 => println!("No stashes of ours to pop"),
+        n => {
+            let popped = results.values().filter(|r| matches!(**r, Ok(StashPop::Popped))).count();
+            println!("Popped {} of {} stashes", popped, n)
+        }
+    }
+}
+
+// Counts every stash, not just ours, since they all hold work.
+fn list(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for mut repo in repos {
+        let tx = tx.clone();
+
+        pool.execute(move || if let Ok(stashes) = repo.stashes() {
+                         if !stashes.is_empty() {
+                             let ours = stashes.iter().filter(|s| s.message().contains(STASH_PREFIX)).count();
+                             tx.send((repo.path().to_path_buf(), (stashes.len(), ours))).expect(THREAD_SIGNAL);
+                         }
+                     });
+    }
+
+    drop(tx);
+
+    let counts = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, &(total, ours)) in &counts {
+        let stashes = match (total, ours) {
+            (1, 0) => String::from("1 stash"),
+            (n, 0) => format!("{} stashes", n),
+            (1, _) => format!("1 stash (from {})", STASH_PREFIX),
+            (n, m) => format!("{} stashes ({} from {})", n, m, STASH_PREFIX),
+        };
+
+        println!("{}  {}", path.display(), BrightYellow.paint(stashes));
+    }
+
+    match counts.len() {
+        0 => println!("No stashes"),
+        n => println!("{} stashes across {} repositories", counts.values().map(|c| c.0).sum::<usize>(), n),
+    }
+}
+
+fn has_changes(repo: &GitRepo) -> bool {
+    match repo.statuses() {
+        Ok(statuses) => {
+            statuses
+                .iter()
+                .any(|e| !matches!(*e.status(), FileStatus::New | FileStatus::Ignored | FileStatus::Current))
+        }
+        Err(_) => false,
+    }
+}
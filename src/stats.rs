@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+
+use serde_json::{self, Value};
+use term_painter::Color::{BrightBlack, BrightCyan, BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::{self, GitRepositories};
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct StatsOptions {
+    pub since: i64,
+    pub json: bool,
+}
+
+struct RepoStats {
+    path: PathBuf,
+    commits: usize,
+    merges: usize,
+    insertions: usize,
+    deletions: usize,
+    branches: Vec<String>,
+    last_activity: Option<i64>,
+}
+
+impl RepoStats {
+    fn is_active(&self) -> bool {
+        self.commits > 0 || self.merges > 0
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "path": self.path.display().to_string(),
+            "commits": self.commits,
+            "merges": self.merges,
+            "insertions": self.insertions,
+            "deletions": self.deletions,
+            "active_branches": self.branches,
+            "last_activity": self.last_activity.map(|t| util::format_date(t, 0)),
+        })
+    }
+}
+
+pub fn process_stats(repos: GitRepositories, pool: &ThreadPool, options: &StatsOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let since = options.since;
+
+        pool.execute(move || {
+            let commits = repo.commits(None, Some(since)).unwrap_or_default();
+            let merges = commits.iter().filter(|c| c.is_merge()).count();
+
+            // Merges would count their branch's lines a second time.
+            let (insertions, deletions) = commits.iter()
+                .filter(|c| !c.is_merge())
+                .filter_map(|c| repo.commit_diff_stats(c.id()).ok())
+                .fold((0, 0), |(i, d), s| (i + s.insertions(), d + s.deletions()));
+
+            let mut branches = repo.active_branches(since).unwrap_or_default();
+            branches.sort();
+
+            let stats = RepoStats {
+                path: repo.path().to_path_buf(),
+                commits: commits.len() - merges,
+                merges: merges,
+                insertions: insertions,
+                deletions: deletions,
+                branches: branches,
+                last_activity: repo.last_activity().unwrap_or(None),
+            };
+
+            tx.send(stats).expect(THREAD_SIGNAL);
+        });
+    }
+
+    drop(tx);
+
+    let mut stats = rx.iter().collect::<Vec<_>>();
+    stats.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let commits = stats.iter().map(|s| s.commits).sum::<usize>();
+    let merges = stats.iter().map(|s| s.merges).sum::<usize>();
+    let insertions = stats.iter().map(|s| s.insertions).sum::<usize>();
+    let deletions = stats.iter().map(|s| s.deletions).sum::<usize>();
+    let branches = stats.iter().map(|s| s.branches.len()).sum::<usize>();
+    let active = stats.iter().filter(|s| s.is_active()).count();
+
+    if options.json {
+        let report = json!({
+            "since": util::format_date(options.since, 0),
+            "repositories": stats.iter().map(RepoStats::to_json).collect::<Vec<_>>(),
+            "totals": {
+                "repositories": stats.len(),
+                "active_repositories": active,
+                "commits": commits,
+                "merges": merges,
+                "insertions": insertions,
+                "deletions": deletions,
+                "active_branches": branches,
+            },
+        });
+
+        println!("{}", serde_json::to_string_pretty(&report).expect("Could not serialize stats"));
+        return;
+    }
+
+    // Quiet repos would only bury the ones worth reading about.
+    for s in stats.iter().filter(|s| s.is_active()) {
+        println!("{}  {} {}  {} {}  {} {}  {} {}  {}",
+                 s.path.display(),
+                 BrightGreen.paint(s.commits),
+                 BrightBlack.paint("commits"),
+                 BrightYellow.paint(s.merges),
+                 BrightBlack.paint("merges"),
+                 BrightGreen.paint(format!("+{}", s.insertions)),
+                 BrightRed.paint(format!("-{}", s.deletions)),
+                 BrightCyan.paint(s.branches.len()),
+                 BrightBlack.paint("active branches"),
+                 BrightBlack.paint(s.last_activity.map_or(String::new(), util::format_age)));
+    }
+
+    println!("Since {}: {} commits (+{} -{}), {} merges and {} active branches in {} of {} repositories",
+             util::format_date(options.since, 0),
+             commits,
+             insertions,
+             deletions,
+             merges,
+             branches,
+             active,
+             stats.len());
+}
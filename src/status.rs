@@ -1,19 +1,109 @@
-use std::collections::BTreeMap;
-use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
-use term_painter::Color::{BrightRed, BrightCyan, BrightGreen, BrightMagenta};
+use notify::{self, RecursiveMode, Watcher};
+use term_painter::Color::{BrightBlack, BrightRed, BrightCyan, BrightGreen, BrightMagenta, BrightYellow};
 use term_painter::ToStyle;
 use threadpool::ThreadPool;
 
-use gitlib::{FileStatus, GitStatusEntry};
-use util::GitRepositories;
+use gitlib::{FileStatus, GitCommit, GitHead, GitRepo, GitStatusEntry, GitSubmodule, RepoState, SubmoduleState};
+use util::{self, GitRepositories, StatusCache};
+
+use cli;
+use styled::{self, plain, styled, Styled};
 
 const THREAD_SIGNAL: &str = "Could not signal main thread";
 
+// Editors and builds touch files in bursts, so wait for things to settle
+// before re-reading the affected repos.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+const WATCH_MAX_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Default)]
+pub struct StatusOptions {
+    pub last_commit: bool,
+    // Only list repos whose uncommitted changes are more than this many days old.
+    pub stale: Option<u64>,
+    // Only list repos that aren't on their default branch, clean or not.
+    pub off_default: bool,
+    pub short: bool,
+    pub watch: bool,
+    pub cached: bool,
+    pub refresh: bool,
+    // Where the last run's summary lines are kept; None if there's nowhere to put them.
+    pub cache: Option<PathBuf>,
+}
+
+// Everything shown for a single repo.
+pub struct RepoStatus {
+    pub list: Vec<GitStatusEntry>,
+    pub head: GitHead,
+    pub state: RepoState,
+    pub stashes: usize,
+    pub ahead_behind: Option<(usize, usize)>,
+    // Only the submodules that need attention.
+    pub submodules: Vec<GitSubmodule>,
+    // Only read when asked for.
+    pub last_commit: Option<GitCommit>,
+    // When the longest-untouched changed file was last modified.
+    pub oldest_change: Option<i64>,
+    // Only looked up when filtering on it.
+    pub default_branch: Option<String>,
+}
+
+impl Default for RepoStatus {
+    fn default() -> Self {
+        Self {
+            list: Vec::new(),
+            head: GitHead::Unborn,
+            state: RepoState::Clean,
+            stashes: 0,
+            ahead_behind: None,
+            submodules: Vec::new(),
+            last_commit: None,
+            oldest_change: None,
+            default_branch: None,
+        }
+    }
+}
+
+impl RepoStatus {
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty() && !self.is_detached() && self.state == RepoState::Clean && self.stashes == 0 &&
+        !self.is_out_of_sync() && self.submodules.is_empty()
+    }
+
+    pub fn is_detached(&self) -> bool {
+        match self.head {
+            GitHead::Detached(_) => true,
+            _ => false,
+        }
+    }
+
+    // Detached counts as off; without a known default there's nothing to compare against.
+    pub fn is_off_default(&self) -> bool {
+        match (&self.head, &self.default_branch) {
+            (GitHead::Branch(name), Some(default)) => name != default,
+            (GitHead::Detached(_), Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_out_of_sync(&self) -> bool {
+        match self.ahead_behind {
+            Some((ahead, behind)) => ahead > 0 || behind > 0,
+            None => false,
+        }
+    }
+}
+
 struct StatusData {
     path: PathBuf,
-    list: Vec<GitStatusEntry>,
+    status: RepoStatus,
     index: usize,
 }
 
@@ -22,18 +112,30 @@ enum StatusResult {
     Data(StatusData),
 }
 
-pub fn process_status(repos: GitRepositories, pool: &ThreadPool) {
-    let rx = repo_status(repos, pool);
+pub fn process_status(repos: GitRepositories, pool: &ThreadPool, options: &StatusOptions) {
+    if options.watch {
+        watch_status(repos, pool, options);
+        return;
+    }
+
+    if options.cached {
+        print_cached(options);
+        return;
+    }
+
+    let rx = repo_status(repos, pool, options);
 
     let mut queue = BTreeMap::new();
     let mut next_index = 0;
+    let mut detached = 0;
+    let mut lines = BTreeMap::new();
 
     while let Ok(result) = rx.recv() {
         let data = match result {
             StatusResult::Data(d) => d,
             StatusResult::Empty(i) => {
                 if i == next_index {
-                    next_index = process_queue(&mut queue, next_index + 1);
+                    next_index = process_queue(&mut queue, next_index + 1, options);
                 } else {
                     queue.insert(i, None);
                 }
@@ -41,30 +143,104 @@ pub fn process_status(repos: GitRepositories, pool: &ThreadPool) {
             }
         };
 
+        if data.status.is_detached() {
+            detached += 1;
+        }
+
+        lines.insert(data.path.clone(), styled::plain_line(&summary_line(&data.path, &data.status)));
+
         if next_index != data.index {
-            queue.insert(data.index, Some((data.path, data.list)));
+            queue.insert(data.index, Some((data.path, data.status)));
             continue;
         }
 
-        print_status(data.path, data.list);
+        show(&data.path, &data.status, options);
 
         // If there are adjacent items in the queue, process them.
-        next_index = process_queue(&mut queue, next_index + 1);
+        next_index = process_queue(&mut queue, next_index + 1, options);
     }
 
     if !queue.is_empty() {
         panic!("Queue somehow has unprocessed items");
     }
+
+    print_summary_line(next_index, detached);
+
+    if let Some(ref path) = options.cache {
+        // Only a convenience for the next --cached run, so don't complain.
+        let _ = StatusCache::new(next_index, detached, lines).save(path);
+    }
+}
+
+fn print_cached(options: &StatusOptions) {
+    let cache = options.cache.as_ref().and_then(StatusCache::load);
+
+    if options.refresh {
+        refresh_in_background();
+    }
+
+    let cache = match cache {
+        Some(c) => c,
+        None => {
+            println!("{}", BrightRed.paint(format!("No cached status yet; run `{} status` first", cli::APP_NAME)));
+            return;
+        }
+    };
+
+    println!("{}", BrightBlack.paint(format!("Cached {}", util::format_age(cache.saved()))));
+
+    for line in cache.repos().values() {
+        println!("{}", line);
+    }
+
+    print_summary_line(cache.checked(), cache.detached());
 }
 
-fn process_queue(queue: &mut BTreeMap<usize, Option<(PathBuf, Vec<GitStatusEntry>)>>,
-                 index: usize)
+// Runs the same command again without --cached, detached from the terminal,
+// so the next --cached run picks up what it finds.
+fn refresh_in_background() {
+    let exe = match env::current_exe() {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    let cached = format!("--{}", cli::CACHED);
+    let refresh = format!("--{}", cli::REFRESH);
+    let args = env::args_os().skip(1).filter(|a| *a != *cached && *a != *refresh);
+
+    let spawned = Command::new(exe)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    if spawned.is_err() {
+        println!("{}", BrightRed.paint("Could not start a background refresh"));
+    }
+}
+
+fn print_summary_line(total: usize, detached: usize) {
+    let repos = match total {
+        1 => String::from("1 repository"),
+        n => format!("{} repositories", n),
+    };
+
+    match detached {
+        0 => println!("Checked {}", repos),
+        n => println!("Checked {}, {}", repos, BrightRed.paint(format!("{} on a detached HEAD", n))),
+    }
+}
+
+fn process_queue(queue: &mut BTreeMap<usize, Option<(PathBuf, RepoStatus)>>,
+                 index: usize,
+                 options: &StatusOptions)
                  -> usize {
     let mut next_index = index;
 
     while let Some(opt) = queue.remove(&next_index) {
-        if let Some((path, list)) = opt {
-            print_status(path, list);
+        if let Some((path, status)) = opt {
+            show(&path, &status, options);
         }
 
         next_index += 1;
@@ -73,30 +249,57 @@ fn process_queue(queue: &mut BTreeMap<usize, Option<(PathBuf, Vec<GitStatusEntry
     next_index
 }
 
-fn repo_status(repos: GitRepositories, pool: &ThreadPool) -> Receiver<StatusResult> {
+fn repo_status(repos: GitRepositories, pool: &ThreadPool, options: &StatusOptions) -> Receiver<StatusResult> {
     let (tx, rx) = channel();
+    let last_commit = options.last_commit;
+    let off_default = options.off_default;
+    let stale = options.stale.map(|days| util::now() - days as i64 * 24 * 60 * 60);
 
     for (index, repo) in repos.enumerate() {
         let tx = tx.clone();
 
         pool.execute(move || {
-            let statuses = match repo.statuses() {
-                Ok(s) => s,
-                Err(_) => {
+            let mut status = match read_status(&repo) {
+                Some(s) => s,
+                None => {
                     tx.send(StatusResult::Empty(index)).expect(THREAD_SIGNAL);
                     return;
                 }
             };
 
-            if statuses.len() == 0 {
+            if let Some(cutoff) = stale {
+                status.oldest_change = oldest_change(repo.path(), &status.list);
+
+                match status.oldest_change {
+                    Some(time) if time < cutoff => (),
+                    _ => {
+                        tx.send(StatusResult::Empty(index)).expect(THREAD_SIGNAL);
+                        return;
+                    }
+                }
+            }
+
+            if off_default {
+                status.default_branch = repo.default_branch();
+
+                if !status.is_off_default() {
+                    tx.send(StatusResult::Empty(index)).expect(THREAD_SIGNAL);
+                    return;
+                }
+            }
+
+            // Asking for commit info lists every repo, clean or not, so the
+            // stale ones stand out.
+            if last_commit {
+                status.last_commit = repo.last_commit().ok();
+            } else if !off_default && status.is_empty() {
                 tx.send(StatusResult::Empty(index)).expect(THREAD_SIGNAL);
                 return;
             }
 
-            let statuses_result = statuses.iter().collect::<Vec<_>>();
             let data = StatusData {
                 path: repo.path().to_path_buf(),
-                list: statuses_result,
+                status: status,
                 index: index,
             };
 
@@ -107,9 +310,331 @@ fn repo_status(repos: GitRepositories, pool: &ThreadPool) -> Receiver<StatusResu
     rx
 }
 
-fn print_status(path: PathBuf, list: Vec<GitStatusEntry>) {
-    println!("{}", path.display());
+fn watch_status(repos: GitRepositories, pool: &ThreadPool, options: &StatusOptions) {
+    let paths = repos.map(|x| x.path().to_path_buf()).collect::<BTreeSet<_>>();
+
+    // Events carry canonical paths, which a relative or symlinked root isn't.
+    let canonical = paths
+        .iter()
+        .map(|p| (p.canonicalize().unwrap_or_else(|_| p.clone()), p.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let watched = canonical.keys().cloned().collect::<BTreeSet<_>>();
+
+    let (tx, rx) = channel();
+
+    // Running out of inotify watches is common enough to just say so.
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(w) => w,
+        Err(e) => {
+            println!("{}", BrightRed.paint(format!("Could not start watching for changes: {}", e)));
+            return;
+        }
+    };
+
+    for path in &watched {
+        if let Err(e) = watcher.watch(path, RecursiveMode::Recursive) {
+            println!("Could not watch {}: {}", path.display(), e);
+        }
+    }
+
+    let mut cache = collect_status(paths.clone(), pool, options.last_commit);
+    print_watch(&cache, options);
+
+    while let Ok(event) = rx.recv() {
+        let mut changed = BTreeSet::new();
+        let mut pending = Some(event);
+
+        // Drain everything that arrives within the debounce window, but no
+        // longer than WATCH_MAX_DELAY, so a busy build still refreshes.
+        let deadline = Instant::now() + WATCH_MAX_DELAY;
+
+        while let Some(event) = pending.take() {
+            if let Ok(event) = event {
+                for p in &event.paths {
+                    if let Some(repo) = owning_repo(&watched, p) {
+                        changed.insert(canonical[repo].clone());
+                    }
+                }
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if remaining.is_zero() {
+                break;
+            }
+
+            match rx.recv_timeout(WATCH_DEBOUNCE.min(remaining)) {
+                Ok(e) => pending = Some(e),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        if changed.is_empty() {
+            continue;
+        }
+
+        cache.extend(collect_status(changed, pool, options.last_commit));
+        print_watch(&cache, options);
+    }
+}
+
+// Picks the innermost watched repo containing the path, skipping churn inside
+// .git that can't change what status reports.
+fn owning_repo<'a>(paths: &'a BTreeSet<PathBuf>, changed: &Path) -> Option<&'a Path> {
+    let repo = paths
+        .iter()
+        .filter(|x| changed.starts_with(x))
+        .max_by_key(|x| x.components().count())?;
+
+    let relative = changed.strip_prefix(repo).ok()?;
+    let mut components = relative.components();
+
+    if components.next() == Some(Component::Normal(".git".as_ref())) {
+        match components.next() {
+            Some(Component::Normal(name)) if name == "index" || name == "HEAD" => (),
+            _ => return None,
+        }
+    }
+
+    Some(repo)
+}
+
+fn collect_status(paths: BTreeSet<PathBuf>,
+                  pool: &ThreadPool,
+                  last_commit: bool)
+                  -> BTreeMap<PathBuf, RepoStatus> {
+    let (tx, rx) = channel();
+    let count = paths.len();
+
+    for path in paths {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+            let status = GitRepo::new(&path)
+                .ok()
+                .and_then(|repo| {
+                    let mut status = read_status(&repo)?;
+
+                    if last_commit {
+                        status.last_commit = repo.last_commit().ok();
+                    }
+
+                    Some(status)
+                })
+                .unwrap_or_default();
+
+            tx.send((path, status)).expect(THREAD_SIGNAL);
+        });
+    }
+
+    rx.iter().take(count).collect()
+}
+
+fn print_watch(cache: &BTreeMap<PathBuf, RepoStatus>, options: &StatusOptions) {
+    // Clear the screen and home the cursor so the view redraws in place.
+    print!("\x1B[2J\x1B[H");
+    println!("Watching {} repositories", cache.len());
+
+    for (path, status) in cache.iter().filter(|&(_, s)| options.last_commit || !s.is_empty()) {
+        show(path, status, options);
+    }
+}
+
+// None when the working tree can't be read at all.
+pub fn read_status(repo: &GitRepo) -> Option<RepoStatus> {
+    let list = repo.statuses().ok()?.iter().collect::<Vec<_>>();
+
+    Some(RepoStatus {
+             list: list,
+             head: repo.head_state(),
+             state: repo.state(),
+             stashes: repo.stash_count().unwrap_or(0),
+             ahead_behind: repo.ahead_behind().unwrap_or(None),
+             submodules: repo.submodules()
+                 .unwrap_or_default()
+                 .into_iter()
+                 .filter(|s| s.state() != SubmoduleState::Clean)
+                 .collect(),
+             last_commit: None,
+             oldest_change: None,
+             default_branch: None,
+         })
+}
+
+// File mtimes are the only record of when uncommitted work happened; deleted
+// files have none, so they don't count.
+fn oldest_change(path: &Path, list: &[GitStatusEntry]) -> Option<i64> {
+    list.iter()
+        .filter(|e| !matches!(*e.status(), FileStatus::Current | FileStatus::Ignored))
+        .filter_map(|e| path.join(e.path()).metadata().and_then(|m| m.modified()).ok())
+        .filter_map(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .min()
+}
+
+fn show(path: &Path, status: &RepoStatus, options: &StatusOptions) {
+    match options.short {
+        true => print_summary(path, status),
+        false => print_status(path, status),
+    }
+}
+
+pub fn print_status(path: &Path, status: &RepoStatus) {
+    print_summary(path, status);
+    print_entries(&status.list);
+    print_submodules(&status.submodules);
+}
+
+fn print_summary(path: &Path, status: &RepoStatus) {
+    styled::print_line(&summary_line(path, status));
+}
+
+// Just the repo's line: path, change counts and sync markers.
+fn summary_line(path: &Path, status: &RepoStatus) -> Vec<Styled> {
+    let mut markers = Vec::new();
+
+    match status.head {
+        GitHead::Branch(ref name) => markers.push(vec![styled(BrightCyan, name.as_str())]),
+        GitHead::Detached(ref id) => markers.push(vec![styled(BrightRed, format!("(detached @ {})", id))]),
+        GitHead::Unborn => (),
+    }
+
+    if let Some(ref default) = status.default_branch {
+        markers.push(vec![styled(BrightBlack, format!("(default {})", default))]);
+    }
+
+    // Half-finished operations come first; they block most everything else.
+    if let Some(operation) = operation(status.state) {
+        markers.push(vec![styled(BrightMagenta.bold(), operation)]);
+    }
+
+    if let Some(counts) = breakdown(&status.list) {
+        markers.push(counts);
+    }
+
+    if let Some((ahead, behind)) = status.ahead_behind {
+        if ahead > 0 {
+            markers.push(vec![styled(BrightGreen, format!("⇡{}", ahead))]);
+        }
+
+        if behind > 0 {
+            markers.push(vec![styled(BrightRed, format!("⇣{}", behind))]);
+        }
+    }
+
+    if let Some(submodules) = submodule_breakdown(&status.submodules) {
+        markers.push(vec![submodules]);
+    }
+
+    if let Some(time) = status.oldest_change {
+        markers.push(vec![styled(BrightYellow, format!("changes from {}", util::format_age(time)))]);
+    }
+
+    if status.stashes > 0 {
+        markers.push(vec![styled(BrightYellow, format!("{} stashed", status.stashes))]);
+    }
+
+    if let Some(ref commit) = status.last_commit {
+        markers.push(vec![styled(BrightYellow, commit.short_id()),
+                          plain(" "),
+                          styled(BrightBlack,
+                                 format!("{} ({})",
+                                         util::format_date(commit.time(), commit.offset_minutes()),
+                                         util::format_age(commit.time()))),
+                          plain(format!(" {}: {}", commit.author(), commit.summary()))]);
+    }
+
+    let mut line = vec![plain(path.display().to_string())];
+
+    if !markers.is_empty() {
+        line.push(plain("  "));
+        line.extend(styled::join(markers, " "));
+    }
+
+    line
+}
+
+fn operation(state: RepoState) -> Option<&'static str> {
+    match state {
+        RepoState::Clean => None,
+        RepoState::Merge => Some("MERGING"),
+        RepoState::Revert | RepoState::RevertSequence => Some("REVERTING"),
+        RepoState::CherryPick | RepoState::CherryPickSequence => Some("CHERRY-PICKING"),
+        RepoState::Bisect => Some("BISECTING"),
+        RepoState::Rebase | RepoState::RebaseInteractive | RepoState::RebaseMerge => Some("REBASING"),
+        RepoState::ApplyMailbox | RepoState::ApplyMailboxOrRebase => Some("APPLYING"),
+    }
+}
+
+// e.g. "2 staged, 5 modified, 12 untracked"
+fn breakdown(list: &[GitStatusEntry]) -> Option<Vec<Styled>> {
+    let (mut conflicted, mut staged, mut modified, mut untracked) = (0, 0, 0, 0);
+
+    for entry in list {
+        match *entry.status() {
+            FileStatus::Conflicted => conflicted += 1,
+            FileStatus::StagedNew |
+            FileStatus::StagedModified |
+            FileStatus::StagedDeleted |
+            FileStatus::StagedRenamed |
+            FileStatus::StagedTypechange => staged += 1,
+            FileStatus::Deleted |
+            FileStatus::Modified |
+            FileStatus::Renamed |
+            FileStatus::Typechange => modified += 1,
+            FileStatus::New => untracked += 1,
+            FileStatus::Current | FileStatus::Ignored | FileStatus::Unknown => (),
+        }
+    }
+
+    let counts = [(conflicted, "conflicted", BrightMagenta),
+                  (staged, "staged", BrightMagenta),
+                  (modified, "modified", BrightCyan),
+                  (untracked, "untracked", BrightGreen)]
+            .iter()
+            .filter(|&&(n, _, _)| n > 0)
+            .map(|&(n, label, colour)| vec![styled(colour, format!("{} {}", n, label))])
+            .collect::<Vec<_>>();
+
+    match counts.is_empty() {
+        true => None,
+        false => Some(styled::join(counts, ", ")),
+    }
+}
+
+fn submodule_breakdown(submodules: &[GitSubmodule]) -> Option<Styled> {
+    if submodules.is_empty() {
+        return None;
+    }
+
+    let count = |state| submodules.iter().filter(|s| s.state() == state).count();
+
+    let counts = [(count(SubmoduleState::Modified), "modified"),
+                  (count(SubmoduleState::OutOfSync), "out of sync"),
+                  (count(SubmoduleState::Uninitialized), "uninitialized")]
+            .iter()
+            .filter(|&&(n, _)| n > 0)
+            .map(|&(n, label)| format!("{} {}", n, label))
+            .collect::<Vec<_>>();
+
+    Some(styled(BrightRed, format!("submodules: {}", counts.join(", "))))
+}
+
+fn print_submodules(submodules: &[GitSubmodule]) {
+    for submodule in submodules {
+        let pre = match submodule.state() {
+            SubmoduleState::Clean => continue,
+            SubmoduleState::Modified => "  Submodule Dirty",
+            SubmoduleState::OutOfSync => "  Submodule Moved",
+            SubmoduleState::Uninitialized => " Submodule Uninit",
+        };
+
+        println!("  {} {}", BrightRed.paint(pre), submodule.path().display());
+    }
+}
 
+fn print_entries(list: &[GitStatusEntry]) {
     for entry in list {
         let (pre, colour) = match *entry.status() {
             FileStatus::Conflicted => ("       Conflicted", BrightMagenta),
@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use term_painter::Color::NotSet;
+use term_painter::{Painted, Style, ToStyle};
+
+// term-painter colours text by writing to the terminal while a Painted value
+// is being formatted, so only a Painted handed straight to print! comes out
+// coloured; format! into a String keeps just the text. Anything built up
+// before printing keeps its style alongside and is painted here instead.
+#[derive(Debug, Clone)]
+pub struct Styled {
+    pub style: Style,
+    pub text: String,
+}
+
+impl Styled {
+    // For handing straight to print!.
+    pub fn paint(&self) -> Painted<&str> {
+        self.style.paint(self.text.as_str())
+    }
+}
+
+pub fn styled<S: ToStyle, T: Into<String>>(style: S, text: T) -> Styled {
+    Styled {
+        style: style.to_style(),
+        text: text.into(),
+    }
+}
+
+pub fn plain<T: Into<String>>(text: T) -> Styled {
+    styled(NotSet, text)
+}
+
+// Like String::join, for parts that may each be several pieces.
+pub fn join(parts: Vec<Vec<Styled>>, separator: &str) -> Vec<Styled> {
+    let mut joined = Vec::new();
+
+    for part in parts {
+        if !joined.is_empty() {
+            joined.push(plain(separator));
+        }
+
+        joined.extend(part);
+    }
+
+    joined
+}
+
+pub fn print_line(pieces: &[Styled]) {
+    for piece in pieces {
+        match piece.style == Style::default() {
+            true => print!("{}", piece.text),
+            false => print!("{}", piece.paint()),
+        }
+    }
+
+    println!();
+}
+
+// "<path>  <pieces>", as most commands report on each repo.
+pub fn print_repo_line(path: &Path, pieces: &[Styled]) {
+    print!("{}  ", path.display());
+    print_line(pieces);
+}
+
+// The same line without colour, e.g. for saving.
+pub fn plain_line(pieces: &[Styled]) -> String {
+    pieces.iter().map(|p| p.text.as_str()).collect()
+}
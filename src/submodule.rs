@@ -0,0 +1,59 @@
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone, Default)]
+pub struct SubmoduleUpdateOptions {
+    pub recursive: bool,
+}
+
+// Repos are reported as they finish, since a checkout can take a while.
+pub fn process_submodule_update(repos: GitRepositories, pool: &ThreadPool, options: &SubmoduleUpdateOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let recursive = options.recursive;
+
+        pool.execute(move || {
+                         let result = repo.update_submodules(recursive);
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let mut updated = 0;
+    let mut failed = 0;
+
+    for (path, result) in rx {
+        match result {
+            Ok(None) => (),
+            Ok(Some(count)) => {
+                let submodules = match count {
+                    1 => String::from("1 submodule"),
+                    n => format!("{} submodules", n),
+                };
+
+                println!("{}  {}", path.display(), BrightGreen.paint(format!("updated {}", submodules)));
+                updated += 1;
+            }
+            Err(_) => {
+                println!("{}  {}", path.display(), BrightRed.paint("submodule update failed"));
+                failed += 1;
+            }
+        }
+    }
+
+    match (updated, failed) {
+        (0, 0) => println!("No repositories have submodules"),
+        (n, 0) => println!("Updated submodules in {} repositories", n),
+        (n, m) => println!("Updated submodules in {} repositories; {} failed", n, m),
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed, BrightYellow};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+#[derive(Debug, Clone)]
+pub struct TagOptions {
+    pub name: String,
+    // Makes it an annotated tag.
+    pub message: Option<String>,
+}
+
+// An existing tag is never moved, so a rerun after a partial failure only
+// fills in the gaps.
+pub fn process_tag(repos: GitRepositories, pool: &ThreadPool, options: &TagOptions) {
+    let (tx, rx) = channel();
+
+    for repo in repos {
+        let tx = tx.clone();
+        let options = options.clone();
+
+        pool.execute(move || {
+                         let result = repo.tag(&options.name, options.message.as_deref());
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, result) in &results {
+        match *result {
+            Ok(true) => println!("{}  {}", path.display(), BrightGreen.paint("tagged")),
+            Ok(false) => println!("{}  {}", path.display(), BrightYellow.paint("already has the tag; left alone")),
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not tag")),
+        }
+    }
+
+    let tagged = results.values().filter(|r| matches!(**r, Ok(true))).count();
+    println!("Tagged {} in {} of {} repositories", options.name, tagged, results.len());
+}
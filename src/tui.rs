@@ -0,0 +1,488 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+
+use ratatui::{DefaultTerminal, Frame};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Cell, Row, Table, TableState};
+use threadpool::ThreadPool;
+
+use gitlib::{GitRepo, Prune};
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+enum Dirty {
+    Loading,
+    Changes(usize),
+    Error,
+}
+
+#[derive(Debug, Clone)]
+struct RepoRow {
+    path: PathBuf,
+    branch: String,
+    dirty: Dirty,
+    message: String,
+}
+
+impl RepoRow {
+    fn loading(path: PathBuf) -> Self {
+        Self {
+            path,
+            branch: String::new(),
+            dirty: Dirty::Loading,
+            message: String::new(),
+        }
+    }
+}
+
+enum Update {
+    Status(RepoRow),
+    Message(PathBuf, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortColumn {
+    Path,
+    Branch,
+    Changes,
+}
+
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            SortColumn::Path => SortColumn::Branch,
+            SortColumn::Branch => SortColumn::Changes,
+            SortColumn::Changes => SortColumn::Path,
+        }
+    }
+}
+
+enum Input {
+    Normal,
+    Filter,
+    Checkout(String),
+}
+
+struct App<'a> {
+    roots: &'a [PathBuf],
+    pool: &'a ThreadPool,
+    rows: Vec<RepoRow>,
+    selected: BTreeSet<PathBuf>,
+    filter: String,
+    sort: SortColumn,
+    input: Input,
+    state: TableState,
+    tx: Sender<Update>,
+    rx: Receiver<Update>,
+    quit: bool,
+}
+
+pub fn process_tui(repos: GitRepositories, pool: &ThreadPool, roots: &[PathBuf]) {
+    // Discovery happens up front so the dashboard never has to redraw a
+    // reshuffling list while the tree is still being walked.
+    let paths = repos.map(|x| x.path().to_path_buf()).collect::<Vec<_>>();
+    let mut app = App::new(paths, pool, roots);
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    if let Err(e) = result {
+        println!("{:#?}", e);
+    }
+}
+
+impl<'a> App<'a> {
+    fn new(paths: Vec<PathBuf>, pool: &'a ThreadPool, roots: &'a [PathBuf]) -> Self {
+        let (tx, rx) = channel();
+
+        let mut app = Self {
+            roots,
+            pool,
+            rows: paths.into_iter().map(RepoRow::loading).collect(),
+            selected: BTreeSet::new(),
+            filter: String::new(),
+            sort: SortColumn::Path,
+            input: Input::Normal,
+            state: TableState::default(),
+            tx,
+            rx,
+            quit: false,
+        };
+
+        app.state.select(Some(0));
+        app.refresh(app.rows.iter().map(|x| x.path.clone()).collect());
+
+        app
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            while let Ok(update) = self.rx.try_recv() {
+                self.apply(update);
+            }
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(TICK)? {
+                continue;
+            }
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key, terminal)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::Status(row) => {
+                if let Some(r) = self.rows.iter_mut().find(|x| x.path == row.path) {
+                    let message = r.message.clone();
+                    *r = row;
+                    r.message = message;
+                }
+            }
+            Update::Message(path, message) => {
+                if let Some(r) = self.rows.iter_mut().find(|x| x.path == path) {
+                    r.message = message;
+                }
+            }
+        }
+    }
+
+    fn visible(&self) -> Vec<&RepoRow> {
+        let filter = self.filter.to_lowercase();
+        let mut rows = self.rows
+            .iter()
+            .filter(|x| {
+                        filter.is_empty() ||
+                        self.display_path(&x.path).to_lowercase().contains(&filter) ||
+                        x.branch.to_lowercase().contains(&filter)
+                    })
+            .collect::<Vec<_>>();
+
+        rows.sort_by(|a, b| match self.sort {
+                         SortColumn::Path => a.path.cmp(&b.path),
+                         SortColumn::Branch => a.branch.cmp(&b.branch).then(a.path.cmp(&b.path)),
+                         SortColumn::Changes => compare_dirty(&b.dirty, &a.dirty).then(a.path.cmp(&b.path)),
+                     });
+
+        rows
+    }
+
+    // With several roots, the root's own name tells apart repos that would
+    // otherwise show up with the same relative path.
+    fn display_path(&self, path: &Path) -> String {
+        let relative = self.roots
+            .iter()
+            .filter_map(|root| path.strip_prefix(root).ok().map(|p| (root, p)))
+            .find(|&(_, p)| !p.as_os_str().is_empty());
+
+        match relative {
+            Some((root, p)) if self.roots.len() > 1 => {
+                match root.file_name() {
+                    Some(name) => Path::new(name).join(p).display().to_string(),
+                    None => p.display().to_string(),
+                }
+            }
+            Some((_, p)) => p.display().to_string(),
+            None => path.display().to_string(),
+        }
+    }
+
+    fn current(&self) -> Option<PathBuf> {
+        let visible = self.visible();
+
+        self.state
+            .selected()
+            .and_then(|i| visible.get(i))
+            .map(|x| x.path.clone())
+    }
+
+    // Actions apply to the marked repos, or the highlighted one if none are marked.
+    fn targets(&self) -> Vec<PathBuf> {
+        match self.selected.is_empty() {
+            true => self.current().into_iter().collect(),
+            false => self.selected.iter().cloned().collect(),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        match self.input {
+            Input::Filter => {
+                match key.code {
+                    KeyCode::Enter | KeyCode::Esc => self.input = Input::Normal,
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                    }
+                    KeyCode::Char(c) => self.filter.push(c),
+                    _ => (),
+                }
+
+                self.state.select(Some(0));
+                return Ok(());
+            }
+            Input::Checkout(ref mut branch) => {
+                match key.code {
+                    KeyCode::Esc => self.input = Input::Normal,
+                    KeyCode::Enter => {
+                        let branch = branch.clone();
+                        self.input = Input::Normal;
+                        self.checkout(branch);
+                    }
+                    KeyCode::Backspace => {
+                        branch.pop();
+                    }
+                    KeyCode::Char(c) => branch.push(c),
+                    _ => (),
+                }
+
+                return Ok(());
+            }
+            Input::Normal => (),
+        }
+
+        let len = self.visible().len();
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = self.state.selected().map_or(0, |i| i + 1);
+                self.state.select(Some(i.min(len.saturating_sub(1))));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.state.selected().map_or(0, |i| i.saturating_sub(1));
+                self.state.select(Some(i));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(path) = self.current() {
+                    if !self.selected.remove(&path) {
+                        self.selected.insert(path);
+                    }
+                }
+            }
+            KeyCode::Char('/') => self.input = Input::Filter,
+            KeyCode::Char('s') => self.sort = self.sort.next(),
+            KeyCode::Char('r') => {
+                let paths = self.rows.iter().map(|x| x.path.clone()).collect();
+                self.refresh(paths);
+            }
+            KeyCode::Char('f') => {
+                self.execute("fetched", |repo| repo.fetch(Prune::Nothing));
+            }
+            KeyCode::Char('z') => {
+                self.execute("stashed", |repo| repo.stash("git plz tui"));
+            }
+            KeyCode::Char('c') => self.input = Input::Checkout(String::new()),
+            KeyCode::Char('o') => {
+                if let Some(path) = self.current() {
+                    self.open(&path, terminal)?;
+                }
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn refresh(&mut self, paths: Vec<PathBuf>) {
+        for path in paths {
+            let tx = self.tx.clone();
+
+            self.pool.execute(move || {
+                tx.send(Update::Status(load_row(path))).expect(THREAD_SIGNAL);
+            });
+        }
+    }
+
+    fn execute<F, T>(&mut self, done: &'static str, op: F)
+        where F: Fn(&mut GitRepo) -> Result<T, ::gitlib::GitError> + Send + Sync + Copy + 'static
+    {
+        for path in self.targets() {
+            let tx = self.tx.clone();
+            self.apply(Update::Message(path.clone(), String::from("working...")));
+
+            self.pool.execute(move || {
+                let message = match GitRepo::new(&path) {
+                    Ok(mut repo) => {
+                        match op(&mut repo) {
+                            Ok(_) => String::from(done),
+                            Err(e) => format!("{:?}", e),
+                        }
+                    }
+                    Err(e) => format!("{:?}", e),
+                };
+
+                tx.send(Update::Message(path.clone(), message)).expect(THREAD_SIGNAL);
+                tx.send(Update::Status(load_row(path))).expect(THREAD_SIGNAL);
+            });
+        }
+    }
+
+    fn checkout(&mut self, branch: String) {
+        if branch.is_empty() {
+            return;
+        }
+
+        for path in self.targets() {
+            let tx = self.tx.clone();
+            let branch = branch.clone();
+
+            self.pool.execute(move || {
+                let message = match GitRepo::new(&path).and_then(|r| r.checkout(&branch)) {
+                    Ok(_) => format!("checked out {}", branch),
+                    Err(e) => format!("{:?}", e),
+                };
+
+                tx.send(Update::Message(path.clone(), message)).expect(THREAD_SIGNAL);
+                tx.send(Update::Status(load_row(path))).expect(THREAD_SIGNAL);
+            });
+        }
+    }
+
+    // Hands the terminal over to a shell in the repo, picking the dashboard back
+    // up once the shell exits.
+    fn open(&mut self, path: &Path, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let shell = match env::var("SHELL") {
+            Ok(s) => s,
+            Err(_) if cfg!(windows) => String::from("cmd"),
+            Err(_) => String::from("sh"),
+        };
+
+        ratatui::restore();
+        let status = Command::new(shell).current_dir(path).status();
+        *terminal = ratatui::init();
+        terminal.clear()?;
+
+        if let Err(e) = status {
+            self.apply(Update::Message(path.to_path_buf(), format!("{}", e)));
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, footer_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)])
+            .areas(frame.area());
+
+        let header = ["", "Path", "Branch", "Changes", ""]
+            .iter()
+            .enumerate()
+            .map(|(i, title)| {
+                     let sorted = matches!((i, self.sort),
+                                           (1, SortColumn::Path) |
+                                           (2, SortColumn::Branch) |
+                                           (3, SortColumn::Changes));
+
+                     match sorted {
+                         true => Cell::from(format!("{} ▾", title)),
+                         false => Cell::from(*title),
+                     }
+                 })
+            .collect::<Row>()
+            .style(Style::default().add_modifier(Modifier::BOLD));
+
+        let rows = self.visible()
+            .into_iter()
+            .map(|x| {
+                let mark = match self.selected.contains(&x.path) {
+                    true => "*",
+                    false => " ",
+                };
+
+                let (changes, colour) = match x.dirty {
+                    Dirty::Loading => (String::from("..."), Color::DarkGray),
+                    Dirty::Changes(0) => (String::from("clean"), Color::Green),
+                    Dirty::Changes(n) => (format!("{} changed", n), Color::Cyan),
+                    Dirty::Error => (String::from("error"), Color::Red),
+                };
+
+                Row::new(vec![Cell::from(mark),
+                              Cell::from(self.display_path(&x.path)),
+                              Cell::from(x.branch.clone()).style(Style::default().fg(Color::LightCyan)),
+                              Cell::from(changes).style(Style::default().fg(colour)),
+                              Cell::from(x.message.clone())])
+            })
+            .collect::<Vec<_>>();
+
+        let widths = [Constraint::Length(1),
+                      Constraint::Percentage(45),
+                      Constraint::Percentage(20),
+                      Constraint::Length(12),
+                      Constraint::Min(10)];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(table, table_area, &mut self.state);
+
+        let footer = match self.input {
+            Input::Normal if self.filter.is_empty() => {
+                String::from("q quit  / filter  s sort  space mark  r refresh  f fetch  z stash  c checkout  o open")
+            }
+            Input::Normal => format!("filter: {}  (/ to edit)", self.filter),
+            Input::Filter => format!("filter: {}_", self.filter),
+            Input::Checkout(ref b) => format!("checkout branch: {}_", b),
+        };
+
+        frame.render_widget(Line::from(footer), footer_area);
+    }
+}
+
+fn compare_dirty(a: &Dirty, b: &Dirty) -> Ordering {
+    fn rank(d: &Dirty) -> (usize, usize) {
+        match *d {
+            Dirty::Error => (2, 0),
+            Dirty::Changes(n) => (1, n),
+            Dirty::Loading => (0, 0),
+        }
+    }
+
+    rank(a).cmp(&rank(b))
+}
+
+fn load_row(path: PathBuf) -> RepoRow {
+    let repo = match GitRepo::new(&path) {
+        Ok(r) => r,
+        Err(_) => {
+            return RepoRow {
+                       dirty: Dirty::Error,
+                       ..RepoRow::loading(path)
+                   }
+        }
+    };
+
+    let branch = match repo.head() {
+        Ok(h) => h.shorthand().to_string(),
+        Err(_) => String::new(),
+    };
+
+    let dirty = match repo.statuses() {
+        Ok(s) => Dirty::Changes(s.len()),
+        Err(_) => Dirty::Error,
+    };
+
+    RepoRow {
+        branch,
+        dirty,
+        ..RepoRow::loading(path)
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::channel;
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+use threadpool::ThreadPool;
+
+use util::GitRepositories;
+
+const THREAD_SIGNAL: &str = "Could not signal main thread";
+
+// Repos that already have their full history are left alone.
+pub fn process_unshallow(repos: GitRepositories, pool: &ThreadPool) {
+    let (tx, rx) = channel();
+
+    for repo in repos.filter(|r| r.is_shallow()) {
+        let tx = tx.clone();
+
+        pool.execute(move || {
+                         let result = repo.unshallow();
+                         tx.send((repo.path().to_path_buf(), result)).expect(THREAD_SIGNAL);
+                     });
+    }
+
+    drop(tx);
+
+    let results = rx.iter().collect::<BTreeMap<_, _>>();
+
+    for (path, result) in &results {
+        match *result {
+            Ok(_) => println!("{}  {}", path.display(), BrightGreen.paint("full history fetched")),
+            Err(_) => println!("{}  {}", path.display(), BrightRed.paint("could not fetch history")),
+        }
+    }
+
+    match results.len() {
+        0 => println!("No shallow repositories"),
+        n => println!("{} of {} shallow repositories deepened", results.values().filter(|r| r.is_ok()).count(), n),
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use term_painter::Color::{BrightGreen, BrightRed};
+use term_painter::ToStyle;
+
+use util::{Manifest, ManifestError, ManifestFormat};
+
+// Checks everything Manifest::open would otherwise quietly start over from.
+// True when there was nothing to report.
+pub fn process_verify(manifest_path: &Path, root: &Path) -> bool {
+    let problems = problems(manifest_path, root);
+
+    for problem in &problems {
+        println!("{}  {}", BrightRed.paint("fail"), problem);
+    }
+
+    match problems.len() {
+        0 => println!("{}", BrightGreen.paint(format!("{} is valid", manifest_path.display()))),
+        1 => println!("{}", BrightRed.paint("1 problem found")),
+        n => println!("{}", BrightRed.paint(format!("{} problems found", n))),
+    }
+
+    problems.is_empty()
+}
+
+fn problems(manifest_path: &Path, root: &Path) -> Vec<String> {
+    match Manifest::validate(manifest_path) {
+        Ok(_) => (),
+        Err(ManifestError::PathNotFound) => return vec![format!("{} does not exist", manifest_path.display())],
+        Err(ManifestError::Invalid) => return vec![parse_problem(manifest_path)],
+        Err(_) => return vec![format!("{} can't be read", manifest_path.display())],
+    }
+
+    let manifest = Manifest::open(&manifest_path, &root);
+
+    if !manifest.root().is_dir() {
+        return vec![format!("root {} does not exist", manifest.root().display())];
+    }
+
+    let mut problems = manifest.stale_entries()
+        .iter()
+        .map(|entry| format!("{} is not a repository", entry.display()))
+        .collect::<Vec<_>>();
+
+    problems.extend(overlapping(&manifest));
+    problems.extend(duplicates(&manifest));
+    problems
+}
+
+// Says where the JSON, TOML or YAML breaks, or that it's well formed but not
+// a manifest.
+fn parse_problem(manifest_path: &Path) -> String {
+    let format = ManifestFormat::from_path(manifest_path);
+    let name = format.name().to_uppercase();
+
+    let problem = fs::read_to_string(manifest_path)
+        .map_err(|e| e.to_string())
+        .map(|text| format.syntax_error(&text));
+
+    match problem {
+        Ok(None) => format!("{} is {} but is missing a manifest's fields", manifest_path.display(), name),
+        Ok(Some(e)) | Err(e) => format!("{} is not valid {}: {}", manifest_path.display(), name, e),
+    }
+}
+
+// An entry inside another is searched both on its own and as part of its parent.
+fn overlapping(manifest: &Manifest) -> Vec<String> {
+    let entries = manifest.entries();
+
+    entries.iter()
+        .flat_map(|outer| {
+                      entries.iter()
+                          .filter(move |inner| *inner != outer && inner.starts_with(outer))
+                          .map(move |inner| format!("{} is inside {}", inner.display(), outer.display()))
+                  })
+        .collect()
+}
+
+// Different entries that are the same directory, through ".." or a symlink.
+fn duplicates(manifest: &Manifest) -> Vec<String> {
+    let mut by_target: BTreeMap<PathBuf, Vec<&PathBuf>> = BTreeMap::new();
+
+    for entry in manifest.entries() {
+        if let Ok(target) = manifest.root().join(entry).canonicalize() {
+            by_target.entry(target).or_default().push(entry);
+        }
+    }
+
+    by_target.values()
+        .filter(|entries| entries.len() > 1)
+        .map(|entries| {
+                 let names = entries.iter().map(|e| e.display().to_string()).collect::<Vec<_>>();
+                 format!("{} are the same repository", names.join(", "))
+             })
+        .collect()
+}
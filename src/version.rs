@@ -0,0 +1,36 @@
+use std::env;
+
+use gitlib::{self, GitLibrary};
+
+use cli;
+
+// Set by build.rs.
+const COMMIT: &str = env!("GIT_PLZ_COMMIT");
+const RUSTC: &str = env!("GIT_PLZ_RUSTC");
+const TARGET: &str = env!("GIT_PLZ_TARGET");
+
+pub fn print_version(verbose: bool) {
+    println!("{} {} ({})", cli::BIN_NAME, cli::VERSION, COMMIT);
+
+    if !verbose {
+        return;
+    }
+
+    let library = GitLibrary::linked();
+    let features = [("threads", library.threads()), ("https", library.https()), ("ssh", library.ssh())]
+        .iter()
+        .filter(|&&(_, enabled)| enabled)
+        .map(|&(name, _)| name)
+        .collect::<Vec<_>>();
+
+    let profile = match cfg!(debug_assertions) {
+        true => "debug",
+        false => "release",
+    };
+
+    println!("target:   {} ({})", TARGET, profile);
+    println!("rustc:    {}", RUSTC);
+    println!("libgit2:  {} [{}]", library.version(), features.join(", "));
+    println!("git:      {}", gitlib::git_version().unwrap_or_else(|e| e));
+    println!("os:       {} {}", env::consts::OS, env::consts::ARCH);
+}